@@ -0,0 +1,73 @@
+//! 编译期收集构建信息，通过环境变量传给 lib.rs
+
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn git(args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn main() {
+    let hash = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_else(|| "unknown".into());
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+        .map(|s| !s.is_empty())
+        .unwrap_or(false);
+
+    // 支持可重复构建: 优先使用 SOURCE_DATE_EPOCH
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".into());
+
+    println!("cargo:rustc-env=WCOS_GIT_HASH={}", hash);
+    println!("cargo:rustc-env=WCOS_GIT_DIRTY={}", dirty);
+    println!("cargo:rustc-env=WCOS_BUILD_TIMESTAMP={}", timestamp);
+    println!("cargo:rustc-env=WCOS_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=WCOS_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=WCOS_PROFILE={}", env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rustc-env=WCOS_BOARD={}", env::var("WCOS_BOARD").unwrap_or_else(|_| "rk3588".into()));
+    println!("cargo:rustc-env=WCOS_FEATURES={}", features());
+
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/index");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-env-changed=WCOS_BOARD");
+    println!("cargo:rerun-if-env-changed=WCOS_FEATURES");
+}
+
+/// 启用的 feature，逗号分隔
+///
+/// 依赖 crate 的构建脚本看不到最终镜像各 crate 的 feature 组合，
+/// 由构建镜像的一方 (scripts/build.sh) 通过 `WCOS_FEATURES` 传入
+fn features() -> String {
+    let list = env::var("WCOS_FEATURES").unwrap_or_default();
+    let mut features: Vec<&str> = list
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|f| !f.is_empty())
+        .collect();
+    features.sort_unstable();
+    features.dedup();
+    if features.is_empty() {
+        "default".into()
+    } else {
+        features.join(",")
+    }
+}
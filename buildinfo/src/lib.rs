@@ -0,0 +1,138 @@
+//! 构建信息
+//!
+//! 编译时由 `build.rs` 收集 git 版本、构建时间、工具链和目标板信息，
+//! 用于启动横幅、`version`/`buildinfo` 命令和问题报告，
+//! 确保能准确知道设备上运行的是哪个二进制。
+//!
+//! # 环境变量
+//! - `SOURCE_DATE_EPOCH`: 指定构建时间 (可重复构建)
+//! - `WCOS_BOARD`: 目标板名称，默认 `rk3588`
+//! - `WCOS_FEATURES`: 镜像启用的 feature (逗号或空格分隔)，`scripts/build.sh` 按传给
+//!   应用的 `--features` 设置；未设置时记为 `default`
+//!
+//! # 使用示例
+//! ```no_run
+//! let mut out = String::new();
+//! buildinfo::write_banner(&mut out).unwrap();
+//! ```
+
+#![no_std]
+
+use core::fmt;
+
+/// 版本号
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// git 提交哈希 (12 位)，不在 git 仓库中构建时为 `unknown`
+pub const GIT_HASH: &str = env!("WCOS_GIT_HASH");
+
+/// 构建时工作区是否有未提交的修改
+pub const GIT_DIRTY: bool = str_eq(env!("WCOS_GIT_DIRTY"), "true");
+
+/// 构建时间 (Unix 时间戳, 秒)
+pub const BUILD_TIMESTAMP: u64 = parse_u64(env!("WCOS_BUILD_TIMESTAMP"));
+
+/// rustc 版本
+pub const RUSTC_VERSION: &str = env!("WCOS_RUSTC_VERSION");
+
+/// 编译目标
+pub const TARGET: &str = env!("WCOS_TARGET");
+
+/// 构建配置 (`debug` / `release`)
+pub const PROFILE: &str = env!("WCOS_PROFILE");
+
+/// 目标板
+pub const BOARD: &str = env!("WCOS_BOARD");
+
+/// 启用的 feature，逗号分隔 (按名称排序)；只用默认 feature 构建时为 `default`
+pub const FEATURES: &str = env!("WCOS_FEATURES");
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn parse_u64(s: &str) -> u64 {
+    let s = s.as_bytes();
+    let mut value = 0u64;
+    let mut i = 0;
+    while i < s.len() {
+        assert!(s[i].is_ascii_digit(), "Invalid build timestamp");
+        value = value * 10 + (s[i] - b'0') as u64;
+        i += 1;
+    }
+    value
+}
+
+/// 构建时间的 UTC 显示
+///
+/// 格式为 `YYYY-MM-DD HH:MM:SS UTC`
+pub struct BuildTime(pub u64);
+
+impl fmt::Display for BuildTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0;
+        let days = (secs / 86_400) as i64;
+        let rem = secs % 86_400;
+
+        // Howard Hinnant 的 civil_from_days 算法
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            year,
+            month,
+            day,
+            rem / 3_600,
+            rem % 3_600 / 60,
+            rem % 60
+        )
+    }
+}
+
+/// 输出一行启动横幅
+///
+/// 例如 `WhitcloudOS v0.1.0 (1a2b3c4d5e6f-dirty, release) rk3588 built 2025-10-31 08:00:00 UTC`
+pub fn write_banner<W: fmt::Write>(w: &mut W) -> fmt::Result {
+    writeln!(
+        w,
+        "WhitcloudOS v{} ({}{}, {}) {} built {}",
+        VERSION,
+        GIT_HASH,
+        if GIT_DIRTY { "-dirty" } else { "" },
+        PROFILE,
+        BOARD,
+        BuildTime(BUILD_TIMESTAMP)
+    )
+}
+
+/// 输出完整构建信息，每项一行
+pub fn write_info<W: fmt::Write>(w: &mut W) -> fmt::Result {
+    writeln!(w, "version:   {}", VERSION)?;
+    writeln!(w, "git:       {}{}", GIT_HASH, if GIT_DIRTY { " (dirty)" } else { "" })?;
+    writeln!(w, "built:     {}", BuildTime(BUILD_TIMESTAMP))?;
+    writeln!(w, "profile:   {}", PROFILE)?;
+    writeln!(w, "target:    {}", TARGET)?;
+    writeln!(w, "board:     {}", BOARD)?;
+    writeln!(w, "features:  {}", FEATURES)?;
+    writeln!(w, "toolchain: {}", RUSTC_VERSION)
+}
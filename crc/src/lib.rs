@@ -0,0 +1,81 @@
+//! 校验和
+//!
+//! 各格式共用的 CRC 实现 (逐位计算，不占用查找表)：
+//!
+//! - [`crc16_xmodem`] CRC-16/XMODEM，用于 XMODEM 数据块和遥测记录
+//! - [`crc16_modbus`] CRC-16/MODBUS，用于 Modbus RTU 帧
+//! - [`crc32`] CRC-32/ISO-HDLC (与 zlib `crc32` 相同)，用于镜像头
+//!
+//! # 使用示例
+//! ```
+//! assert_eq!(crc::crc16_xmodem(b"123456789"), 0x31C3);
+//! assert_eq!(crc::crc16_modbus(b"123456789"), 0x4B37);
+//! assert_eq!(crc::crc32(b"123456789"), 0xCBF4_3926);
+//! ```
+
+#![no_std]
+
+/// CRC-16/XMODEM (多项式 0x1021，初值 0，不反射)
+///
+/// # 示例
+/// ```
+/// assert_eq!(crc::crc16_xmodem(b""), 0);
+/// ```
+pub fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-16/MODBUS (多项式 0x8005 反射即 0xA001，初值 0xFFFF)
+///
+/// 帧中按低字节在前发送
+///
+/// # 示例
+/// ```
+/// assert_eq!(crc::crc16_modbus(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]), 0x0A84);
+/// ```
+pub fn crc16_modbus(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-32/ISO-HDLC (多项式 0xEDB88320 反射，初值和结果异或 0xFFFFFFFF)
+///
+/// # 示例
+/// ```
+/// assert_eq!(crc::crc32(b""), 0);
+/// ```
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
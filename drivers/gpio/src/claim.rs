@@ -0,0 +1,274 @@
+//! 引脚占用登记与冲突检查
+//!
+//! 引脚配置本身是"后写者生效"：两个驱动配置到同一个引脚时硬件不会有任何提示，
+//! 只会表现为难以定位的异常。各驱动在初始化时登记自己使用的引脚，
+//! 得到一个 [`PinClaim`]，丢弃时释放。引脚已被占用时返回 [`PinConflict`]，
+//! 其中列出双方的使用者。
+//!
+//! 占用按实例计算：同一使用者 (例如两个 `FastGpio`) 再次占用同一引脚同样是冲突。
+//! 占用引脚的驱动在构造时登记，冲突时构造函数返回 `Err(PinConflict)`：
+//! `FastGpio` (`fast`)、`QuadratureEncoder` (`encoder`)、`SoftPwm::attach`
+//! (`soft-pwm`，`detach` 时释放)、`StatusLed` (`status-led`)、uart 的 `Rs485`
+//! 和 motion 的 `Stepper`。直接使用 [`GpioPin`] 的代码用 [`ClaimedPin`] 登记。
+//!
+//! 启动时可以先用 [`check_board`] 检查板级引脚分配表本身是否有冲突，
+//! 再用 [`claim_board`] 一次性预留：预留的引脚只能由同名使用者占用一次。
+//!
+//! # 注意
+//! 仓库中还没有 pinctrl/IOMUX 驱动，这里只按 GPIO 编号检查，
+//! 不检查引脚复用功能。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use gpio::claim::{self, ClaimedPin, PinAssignment};
+//!
+//! const BOARD_PINS: &[PinAssignment] = &[
+//!     PinAssignment::new(GpioBank::Gpio0, 13, "status-led"),
+//!     PinAssignment::new(GpioBank::Gpio3, 10, "encoder"),
+//!     PinAssignment::new(GpioBank::Gpio3, 11, "encoder"),
+//! ];
+//!
+//! if let Err(conflict) = claim::claim_board(BOARD_PINS) {
+//!     panic!("{}", conflict);
+//! }
+//!
+//! // 预留给 status-led，其他使用者占用失败
+//! assert!(ClaimedPin::new(GpioPin::new(GpioBank::Gpio0, 13), "relay").is_err());
+//! ```
+
+use core::fmt;
+use core::ops::Deref;
+
+use spinlock::SpinLock;
+
+use crate::{GpioBank, GpioPin, GPIO0_BASE, GPIO1_BASE, GPIO2_BASE, GPIO3_BASE, GPIO4_BASE};
+
+/// 引脚总数 (5 个 Bank × 32)
+const PIN_COUNT: usize = 5 * 32;
+
+/// 板级引脚分配表中的一项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinAssignment {
+    pub bank: GpioBank,
+    pub pin: u8,
+    /// 使用者名称 (驱动或功能名)
+    pub owner: &'static str,
+}
+
+impl PinAssignment {
+    pub const fn new(bank: GpioBank, pin: u8, owner: &'static str) -> Self {
+        Self { bank, pin, owner }
+    }
+}
+
+/// 引脚冲突
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinConflict {
+    pub bank: GpioBank,
+    pub pin: u8,
+    /// 已经占用该引脚的使用者
+    pub owner: &'static str,
+    /// 请求占用的使用者
+    pub requester: &'static str,
+}
+
+impl fmt::Display for PinConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GPIO{}_{}{} claimed by `{}`, requested by `{}`",
+            self.bank as u8,
+            (b'A' + self.pin / 8) as char,
+            self.pin % 8,
+            self.owner,
+            self.requester
+        )
+    }
+}
+
+/// 登记表中的一项
+#[derive(Clone, Copy)]
+struct Entry {
+    owner: &'static str,
+    /// 已被驱动实例占用；为 `false` 时只是 [`claim_board`] 的预留
+    in_use: bool,
+}
+
+/// 引脚使用者表
+static REGISTRY: SpinLock<[Option<Entry>; PIN_COUNT]> = SpinLock::new([None; PIN_COUNT]);
+
+fn index(bank: GpioBank, pin: u8) -> usize {
+    assert!(pin < 32, "Pin number must be less than 32");
+    bank as usize * 32 + pin as usize
+}
+
+fn bank_of(pin: &GpioPin) -> GpioBank {
+    match pin.base {
+        GPIO0_BASE => GpioBank::Gpio0,
+        GPIO1_BASE => GpioBank::Gpio1,
+        GPIO2_BASE => GpioBank::Gpio2,
+        GPIO3_BASE => GpioBank::Gpio3,
+        GPIO4_BASE => GpioBank::Gpio4,
+        _ => unreachable!(),
+    }
+}
+
+/// 引脚占用凭证，丢弃时释放引脚 (由 [`claim_board`] 预留的引脚恢复为预留)
+#[derive(Debug)]
+#[must_use = "dropping the claim releases the pin"]
+pub struct PinClaim {
+    bank: GpioBank,
+    pin: u8,
+    owner: &'static str,
+    /// 占用前引脚已由 claim_board 预留
+    reserved: bool,
+}
+
+impl PinClaim {
+    pub fn bank(&self) -> GpioBank {
+        self.bank
+    }
+
+    pub fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// 使用者名称
+    pub fn owner(&self) -> &'static str {
+        self.owner
+    }
+}
+
+impl Drop for PinClaim {
+    fn drop(&mut self) {
+        let entry = self.reserved.then_some(Entry {
+            owner: self.owner,
+            in_use: false,
+        });
+        REGISTRY.lock()[index(self.bank, self.pin)] = entry;
+    }
+}
+
+/// 登记占用一个引脚
+///
+/// 引脚空闲，或由 [`claim_board`] 预留给同名使用者且尚未被占用时成功
+///
+/// # 返回值
+/// - `Ok(PinClaim)`: 占用凭证，丢弃时释放
+/// - `Err(PinConflict)`: 引脚已被占用 (包括同一使用者的另一个实例)
+pub fn claim(bank: GpioBank, pin: u8, owner: &'static str) -> Result<PinClaim, PinConflict> {
+    let i = index(bank, pin);
+    let mut owners = REGISTRY.lock();
+    let reserved = match owners[i] {
+        None => false,
+        Some(Entry { owner: current, in_use: false }) if current == owner => true,
+        Some(Entry { owner: current, .. }) => {
+            let conflict = PinConflict {
+                bank,
+                pin,
+                owner: current,
+                requester: owner,
+            };
+            drop(owners);
+            klog::warn!("{}", conflict);
+            return Err(conflict);
+        }
+    };
+    owners[i] = Some(Entry { owner, in_use: true });
+    Ok(PinClaim {
+        bank,
+        pin,
+        owner,
+        reserved,
+    })
+}
+
+/// 登记占用 `pin`，参见 [`claim`]
+pub fn claim_pin(pin: &GpioPin, owner: &'static str) -> Result<PinClaim, PinConflict> {
+    claim(bank_of(pin), pin.pin, owner)
+}
+
+/// 登记了占用的 [`GpioPin`]，丢弃时释放
+///
+/// 通过 `Deref` 使用 `GpioPin` 的全部方法
+pub struct ClaimedPin {
+    pin: GpioPin,
+    _claim: PinClaim,
+}
+
+impl ClaimedPin {
+    /// 登记占用 `pin`，参见 [`claim`]
+    pub fn new(pin: GpioPin, owner: &'static str) -> Result<Self, PinConflict> {
+        let claim = claim_pin(&pin, owner)?;
+        Ok(Self { pin, _claim: claim })
+    }
+}
+
+impl Deref for ClaimedPin {
+    type Target = GpioPin;
+
+    fn deref(&self) -> &GpioPin {
+        &self.pin
+    }
+}
+
+/// 查询引脚的使用者
+pub fn owner(bank: GpioBank, pin: u8) -> Option<&'static str> {
+    let i = index(bank, pin);
+    REGISTRY.lock()[i].map(|entry| entry.owner)
+}
+
+/// 检查板级引脚分配表内部是否有冲突 (不修改登记表)
+///
+/// # 返回值
+/// 发现的第一个冲突，`owner` 为表中先出现的一项
+pub fn check_board(table: &[PinAssignment]) -> Result<(), PinConflict> {
+    for (i, a) in table.iter().enumerate() {
+        // 引脚号无效时在这里 panic，不会在持有登记表的锁时发生
+        index(a.bank, a.pin);
+        for b in &table[..i] {
+            if a.bank == b.bank && a.pin == b.pin && a.owner != b.owner {
+                return Err(PinConflict {
+                    bank: a.bank,
+                    pin: a.pin,
+                    owner: b.owner,
+                    requester: a.owner,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 检查并预留整张板级引脚分配表
+///
+/// 预留的引脚之后只能由表中的同名使用者通过 [`claim`] 占用 (每个引脚一个实例)。
+/// 表内冲突或与已登记的其他使用者冲突时返回错误，此时登记表不会被修改
+pub fn claim_board(table: &[PinAssignment]) -> Result<(), PinConflict> {
+    // 同时检查了引脚号，下面持锁时的 index() 不会 panic
+    check_board(table)?;
+    let mut owners = REGISTRY.lock();
+    for a in table {
+        if let Some(Entry { owner: current, .. }) = owners[index(a.bank, a.pin)] {
+            if current != a.owner {
+                return Err(PinConflict {
+                    bank: a.bank,
+                    pin: a.pin,
+                    owner: current,
+                    requester: a.owner,
+                });
+            }
+        }
+    }
+    for a in table {
+        let entry = &mut owners[index(a.bank, a.pin)];
+        if entry.is_none() {
+            *entry = Some(Entry {
+                owner: a.owner,
+                in_use: false,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! 正交编码器计数
+//!
+//! 对 A/B 两相信号做 4 倍频解码，并在可配置的时间窗口上估算速度，
+//! 用于电机闭环控制。
+//!
+//! # 采样方式
+//! 目前仓库中还没有定时器捕获通道驱动，GPIO 驱动也尚未实现中断，
+//! 因此由定时器中断以固定频率调用 [`QuadratureEncoder::sample`] 轮询两相电平。
+//! 采样频率必须高于编码器最高边沿频率，否则会丢计数
+//! (通过 [`QuadratureEncoder::errors`] 可以观察到)。
+//!
+//! 后续加入定时器捕获或 GPIO 中断时，可以直接把边沿交给 [`QuadratureDecoder`]。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use gpio::encoder::QuadratureEncoder;
+//!
+//! // 50kHz 采样，速度窗口 500 个采样 (10ms)
+//! let mut enc: QuadratureEncoder<500> = QuadratureEncoder::new(
+//!     GpioPin::new(GpioBank::Gpio3, 10),
+//!     GpioPin::new(GpioBank::Gpio3, 11),
+//!     50_000,
+//! )
+//! .unwrap();
+//!
+//! // 在 50kHz 定时器中断中:
+//! enc.sample();
+//!
+//! let pos = enc.count();
+//! let vel = enc.velocity();   // 计数/秒
+//! ```
+
+use crate::claim::{self, PinClaim, PinConflict};
+use crate::{GpioDirection, GpioLevel, GpioPin};
+
+/// 4 倍频解码查找表
+///
+/// 索引为 `(旧状态 << 2) | 新状态`，状态为 `(A << 1) | B`。
+/// 0 表示无变化，2 表示两相同时变化 (非法跳变，丢失了边沿)。
+#[rustfmt::skip]
+const TRANSITION: [i8; 16] = [
+    0, -1, 1, 2,
+    1, 0, 2, -1,
+    -1, 2, 0, 1,
+    2, 1, -1, 0,
+];
+
+/// 正交解码状态机
+///
+/// 与信号来源无关，可由轮询、GPIO 中断或定时器捕获驱动
+#[derive(Debug, Clone, Copy)]
+pub struct QuadratureDecoder {
+    state: u8,
+    count: i32,
+    errors: u32,
+}
+
+impl QuadratureDecoder {
+    /// 以当前两相电平创建解码器
+    pub const fn new(a: bool, b: bool) -> Self {
+        Self {
+            state: ((a as u8) << 1) | b as u8,
+            count: 0,
+            errors: 0,
+        }
+    }
+
+    /// 输入一次两相电平
+    ///
+    /// # 返回值
+    /// 本次计数变化 (-1, 0, +1)
+    pub fn update(&mut self, a: bool, b: bool) -> i8 {
+        let new = ((a as u8) << 1) | b as u8;
+        let delta = TRANSITION[((self.state << 2) | new) as usize];
+        self.state = new;
+
+        if delta == 2 {
+            self.errors = self.errors.wrapping_add(1);
+            return 0;
+        }
+        self.count = self.count.wrapping_add(delta as i32);
+        delta
+    }
+
+    /// 累计计数
+    pub fn count(&self) -> i32 {
+        self.count
+    }
+
+    /// 非法跳变次数 (采样过慢或信号干扰)
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// 重设计数
+    pub fn set_count(&mut self, count: i32) {
+        self.count = count;
+    }
+}
+
+/// 轮询式正交编码器
+///
+/// `W` 为速度估算窗口的采样数，窗口时长为 `W / sample_hz` 秒。
+/// 窗口越长速度越平滑，但响应越慢。
+pub struct QuadratureEncoder<const W: usize> {
+    a: GpioPin,
+    b: GpioPin,
+    decoder: QuadratureDecoder,
+    sample_hz: u32,
+    /// 最近 W 次采样时的计数值 (环形缓冲)
+    history: [i32; W],
+    head: usize,
+    filled: usize,
+    _claims: [PinClaim; 2],
+}
+
+impl<const W: usize> QuadratureEncoder<W> {
+    /// 创建编码器，A/B 引脚会被设置为输入模式
+    ///
+    /// # 参数
+    /// - `a`, `b`: 编码器 A/B 相引脚
+    /// - `sample_hz`: 调用 `sample()` 的频率
+    ///
+    /// # 返回值
+    /// - `Ok(QuadratureEncoder)`: 两个引脚已登记 (使用者 `encoder`)，实例丢弃时释放
+    /// - `Err(PinConflict)`: 引脚已被占用，或 A/B 是同一个引脚；引脚配置不变
+    pub fn new(a: GpioPin, b: GpioPin, sample_hz: u32) -> Result<Self, PinConflict> {
+        assert!(W > 0, "Velocity window must not be empty");
+        let claims = [claim::claim_pin(&a, "encoder")?, claim::claim_pin(&b, "encoder")?];
+
+        a.set_direction(GpioDirection::Input);
+        b.set_direction(GpioDirection::Input);
+        let decoder = QuadratureDecoder::new(
+            a.get_level() == GpioLevel::High,
+            b.get_level() == GpioLevel::High,
+        );
+
+        Ok(Self {
+            a,
+            b,
+            decoder,
+            sample_hz,
+            history: [0; W],
+            head: 0,
+            filled: 0,
+            _claims: claims,
+        })
+    }
+
+    /// 采样一次两相电平，应在定时器中断中以 `sample_hz` 频率调用
+    pub fn sample(&mut self) {
+        self.decoder.update(
+            self.a.get_level() == GpioLevel::High,
+            self.b.get_level() == GpioLevel::High,
+        );
+
+        self.history[self.head] = self.decoder.count();
+        self.head = (self.head + 1) % W;
+        if self.filled < W {
+            self.filled += 1;
+        }
+    }
+
+    /// 累计计数 (4 倍频)
+    pub fn count(&self) -> i32 {
+        self.decoder.count()
+    }
+
+    /// 重设计数 (例如回零)，同时清空速度窗口
+    pub fn set_count(&mut self, count: i32) {
+        self.decoder.set_count(count);
+        self.filled = 0;
+        self.head = 0;
+    }
+
+    /// 非法跳变次数
+    pub fn errors(&self) -> u32 {
+        self.decoder.errors()
+    }
+
+    /// 窗口内的平均速度 (计数/秒)
+    ///
+    /// 窗口尚未填满时按已有采样计算，没有采样时返回 0
+    pub fn velocity(&self) -> i32 {
+        if self.filled < 2 {
+            return 0;
+        }
+        let newest = self.history[(self.head + W - 1) % W];
+        let oldest = self.history[(self.head + W - self.filled) % W];
+        let span = (self.filled - 1) as i64;
+        ((newest.wrapping_sub(oldest)) as i64 * self.sample_hz as i64 / span) as i32
+    }
+}
@@ -0,0 +1,106 @@
+//! GPIO 快速路径
+//!
+//! 用于软件模拟 SPI/I2C/单总线等需要数 MHz 翻转速率的协议。
+//! [`GpioPin`] 每次操作都要匹配方向/电平枚举并重新计算寄存器地址，
+//! [`FastGpio`] 在构造时预先算好寄存器地址和写入值，输出电平只需一次带写使能的写入
+//! (一条 `str`)，没有读-改-写、分支和边界检查，全部强制内联。
+//!
+//! # 注意
+//! - 构造时引脚被设置为输出，之后不再检查方向
+//! - 构造时登记引脚 (使用者 `fast-gpio`)，引脚已被占用时返回 [`PinConflict`]
+//! - 写使能位保证只改变本引脚，同一 Bank 的其他引脚在中断中被修改也不会冲突，
+//!   不需要关中断
+//! - [`toggle`](FastGpio::toggle) 需要先读数据寄存器，比 `set_high`/`set_low` 多一次
+//!   总线读取；对时序要求高的协议应按位写入电平
+//!
+//! # 基准测试
+//! 在目标板上用示波器测量下面循环输出的方波频率，每个周期包含一次 `set_high()`
+//! 和一次 `set_low()`：
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use gpio::fast::FastGpio;
+//!
+//! let pin = FastGpio::new(GpioPin::new(GpioBank::Gpio1, 2)).unwrap();
+//! loop {
+//!     pin.set_high();
+//!     pin.set_low();
+//! }
+//! ```
+//! 单次写入的耗时即为方波半周期。编译结果应为两条 `str` 加一条跳转，
+//! 可以用 `cargo objdump --release` 确认热路径中没有函数调用和 `ldr`。
+//! 实际耗时取决于外设总线的写入延迟 (设备内存不经过缓存)，尚未在目标板上实测，
+//! 不保证单次翻转在 100ns 以内。
+
+use mmio::{read_volatile, write_volatile};
+
+use crate::claim::{self, PinClaim, PinConflict};
+use crate::{masked_reg, GpioDirection, GpioPin, GPIO_EXT_PORT, GPIO_SWPORT_DR_L};
+
+/// 预先计算寄存器地址的 GPIO 输出引脚
+pub struct FastGpio {
+    /// 数据寄存器 (DR_L 或 DR_H) 地址
+    dr: usize,
+    /// 外部端口寄存器地址
+    ext: usize,
+    /// 数据寄存器中的位掩码
+    mask: u32,
+    /// 外部端口寄存器中的位掩码
+    ext_mask: u32,
+    _claim: PinClaim,
+}
+
+impl FastGpio {
+    /// 由 `GpioPin` 创建，引脚被设置为输出
+    ///
+    /// # 返回值
+    /// - `Ok(FastGpio)`: 引脚已登记，实例丢弃时释放
+    /// - `Err(PinConflict)`: 引脚已被占用，引脚配置不变
+    pub fn new(pin: GpioPin) -> Result<Self, PinConflict> {
+        let claim = claim::claim_pin(&pin, "fast-gpio")?;
+        pin.set_direction(GpioDirection::Output);
+        let (reg, mask) = masked_reg(GPIO_SWPORT_DR_L, pin.pin);
+        Ok(Self {
+            dr: pin.base + reg,
+            ext: pin.base + GPIO_EXT_PORT,
+            mask,
+            ext_mask: 1 << pin.pin,
+            _claim: claim,
+        })
+    }
+
+    /// 输出高电平
+    #[inline(always)]
+    pub fn set_high(&self) {
+        unsafe { write_volatile(self.dr as *mut u32, (self.mask << 16) | self.mask) }
+    }
+
+    /// 输出低电平
+    #[inline(always)]
+    pub fn set_low(&self) {
+        unsafe { write_volatile(self.dr as *mut u32, self.mask << 16) }
+    }
+
+    /// 输出指定电平，`high` 为 `true` 时输出高电平
+    ///
+    /// 用掩码运算代替分支，适合逐位移出数据
+    #[inline(always)]
+    pub fn write(&self, high: bool) {
+        let set = (high as u32).wrapping_neg() & self.mask;
+        unsafe { write_volatile(self.dr as *mut u32, (self.mask << 16) | set) }
+    }
+
+    /// 翻转输出电平
+    #[inline(always)]
+    pub fn toggle(&self) {
+        unsafe {
+            let dr = self.dr as *mut u32;
+            write_volatile(dr, (self.mask << 16) | (!read_volatile(dr) & self.mask));
+        }
+    }
+
+    /// 读取引脚实际电平
+    #[inline(always)]
+    pub fn is_high(&self) -> bool {
+        unsafe { read_volatile(self.ext as *const u32) & self.ext_mask != 0 }
+    }
+}
@@ -21,7 +21,21 @@
 
 #![no_std]
 
-use core::ptr::{read_volatile, write_volatile};
+use mmio::{read_volatile, write_volatile};
+
+pub mod claim;
+#[cfg(feature = "encoder")]
+pub mod encoder;
+#[cfg(feature = "fast")]
+pub mod fast;
+#[cfg(feature = "regset")]
+pub mod regs;
+#[cfg(feature = "soft-pwm")]
+pub mod soft_pwm;
+#[cfg(feature = "status-led")]
+pub mod status_led;
+#[cfg(feature = "typed")]
+pub mod typed;
 
 /// RK3588 GPIO 寄存器基址
 /// 
@@ -35,9 +49,33 @@ pub const GPIO4_BASE: usize = 0xFEC50000;
 /// GPIO 寄存器偏移
 /// 
 /// 参考: RK3588 TRM Section 20.2 - Register Description
-const GPIO_SWPORT_DR: usize = 0x0000;      // 数据寄存器 (读写引脚电平)
-const GPIO_SWPORT_DDR: usize = 0x0004;     // 方向寄存器 (0=输入, 1=输出)
-const GPIO_EXT_PORT: usize = 0x0050;       // 外部端口寄存器 (只读, 读取实际引脚电平)
+/// 
+/// 数据和方向寄存器按引脚拆成 _L (引脚 0-15) 和 _H (引脚 16-31)，
+/// 高 16 位是低 16 位的写使能：写入只改变使能位对应的引脚，不需要读-改-写，
+/// 同一 Bank 的其他引脚不受影响。
+const GPIO_SWPORT_DR_L: usize = 0x0000;    // 数据寄存器 (引脚 0-15)
+const GPIO_SWPORT_DR_H: usize = 0x0004;    // 数据寄存器 (引脚 16-31)
+const GPIO_SWPORT_DDR_L: usize = 0x0008;   // 方向寄存器 (引脚 0-15, 0=输入, 1=输出)
+const GPIO_SWPORT_DDR_H: usize = 0x000C;   // 方向寄存器 (引脚 16-31)
+const GPIO_EXT_PORT: usize = 0x0070;       // 外部端口寄存器 (只读, 读取实际引脚电平)
+
+/// 引脚在 _L/_H 寄存器对中的寄存器偏移和位掩码
+/// 
+/// # 参数
+/// - `reg_l`: _L 寄存器偏移
+/// - `pin`: 引脚号 (0-31)
+const fn masked_reg(reg_l: usize, pin: u8) -> (usize, u32) {
+    (reg_l + (pin as usize / 16) * 4, 1 << (pin % 16))
+}
+
+/// 写带写使能的寄存器，只改变 `mask` 对应的位
+/// 
+/// # Safety
+/// `addr` 必须是 _L/_H 寄存器地址
+#[inline(always)]
+unsafe fn write_masked(addr: usize, mask: u32, value: u32) {
+    write_volatile(addr as *mut u32, (mask << 16) | (value & mask));
+}
 
 /// GPIO Bank 枚举
 /// 
@@ -135,19 +173,16 @@ impl GpioPin {
     /// - `direction`: 引脚方向
     /// 
     /// # 硬件操作
-    /// 修改 GPIO_SWPORT_DDR 寄存器对应位
+    /// 带写使能写 GPIO_SWPORT_DDR_L/H 寄存器对应位
     /// - 0: 输入模式
     /// - 1: 输出模式
     pub fn set_direction(&self, direction: GpioDirection) {
-        let addr = (self.base + GPIO_SWPORT_DDR) as *mut u32;
-        unsafe {
-            let mut val = read_volatile(addr);
-            match direction {
-                GpioDirection::Output => val |= 1 << self.pin,
-                GpioDirection::Input => val &= !(1 << self.pin),
-            }
-            write_volatile(addr, val);
-        }
+        let (reg, mask) = masked_reg(GPIO_SWPORT_DDR_L, self.pin);
+        let value = match direction {
+            GpioDirection::Output => mask,
+            GpioDirection::Input => 0,
+        };
+        unsafe { write_masked(self.base + reg, mask, value) }
     }
     
     /// 设置输出电平 (仅输出模式有效)
@@ -159,17 +194,14 @@ impl GpioPin {
     /// 调用此函数前应先调用 `set_direction(GpioDirection::Output)`
     /// 
     /// # 硬件操作
-    /// 修改 GPIO_SWPORT_DR 寄存器对应位
+    /// 带写使能写 GPIO_SWPORT_DR_L/H 寄存器对应位
     pub fn set_level(&self, level: GpioLevel) {
-        let addr = (self.base + GPIO_SWPORT_DR) as *mut u32;
-        unsafe {
-            let mut val = read_volatile(addr);
-            match level {
-                GpioLevel::High => val |= 1 << self.pin,
-                GpioLevel::Low => val &= !(1 << self.pin),
-            }
-            write_volatile(addr, val);
-        }
+        let (reg, mask) = masked_reg(GPIO_SWPORT_DR_L, self.pin);
+        let value = match level {
+            GpioLevel::High => mask,
+            GpioLevel::Low => 0,
+        };
+        unsafe { write_masked(self.base + reg, mask, value) }
     }
     
     /// 读取引脚电平
@@ -198,17 +230,14 @@ impl GpioPin {
     /// 翻转输出电平 (仅输出模式有效)
     /// 
     /// # 硬件操作
-    /// 对 GPIO_SWPORT_DR 寄存器对应位执行 XOR 操作
+    /// 读取 GPIO_SWPORT_DR_L/H，带写使能写回对应位的反码
     /// 
     /// # 用途
     /// 常用于 LED 闪烁等场景
     pub fn toggle(&self) {
-        let addr = (self.base + GPIO_SWPORT_DR) as *mut u32;
-        unsafe {
-            let mut val = read_volatile(addr);
-            val ^= 1 << self.pin;
-            write_volatile(addr, val);
-        }
+        let (reg, mask) = masked_reg(GPIO_SWPORT_DR_L, self.pin);
+        let addr = self.base + reg;
+        unsafe { write_masked(addr, mask, !read_volatile(addr as *const u32)) }
     }
 }
 
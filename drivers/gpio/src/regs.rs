@@ -0,0 +1,22 @@
+//! GPIO 寄存器集合
+//!
+//! 供 `regdump` 调试输出使用，见 [`regset`]。
+//! 每一位对应一个引脚，不再拆分位域；_L/_H 寄存器读出的高 16 位 (写使能) 恒为 0。
+
+use regset::{Access, Register, RegisterSet};
+
+use crate::{
+    GPIO_EXT_PORT, GPIO_SWPORT_DDR_H, GPIO_SWPORT_DDR_L, GPIO_SWPORT_DR_H, GPIO_SWPORT_DR_L,
+};
+
+/// GPIO Bank 关键寄存器
+///
+/// 写入 _L/_H 寄存器需要带上写使能位，按快照直接写回的值不会改变任何引脚，
+/// 因此全部声明为只读，这个集合只用于输出
+pub const GPIO_REGS: RegisterSet = RegisterSet::new("gpio", &[
+    Register::new("DR_L", GPIO_SWPORT_DR_L, Access::ReadOnly, &[]),
+    Register::new("DR_H", GPIO_SWPORT_DR_H, Access::ReadOnly, &[]),
+    Register::new("DDR_L", GPIO_SWPORT_DDR_L, Access::ReadOnly, &[]),
+    Register::new("DDR_H", GPIO_SWPORT_DDR_H, Access::ReadOnly, &[]),
+    Register::new("EXT", GPIO_EXT_PORT, Access::ReadOnly, &[]),
+]);
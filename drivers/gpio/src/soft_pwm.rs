@@ -0,0 +1,189 @@
+//! GPIO 软件 PWM
+//!
+//! 为没有连到硬件 PWM 的引脚提供软件 PWM，适合 LED 调光和低速舵机控制。
+//!
+//! # 工作方式
+//! 由定时器中断以固定频率调用 [`SoftPwm::tick`]，每个 PWM 周期包含
+//! `tick_hz / pwm_hz` 个节拍 (即占空比分辨率)。周期开始时输出高电平，
+//! 计数到占空比阈值时拉低。
+//!
+//! 占空比修改先写入暂存值，调用 [`SoftPwm::commit`] 后在下一个周期开始时
+//! 统一生效，保证同一组引脚的更新是同步的，不会出现半个周期的毛刺。
+//!
+//! # 频率选择
+//! 节拍频率受中断开销限制，一般不超过 100kHz：
+//! - LED 调光: `tick_hz = 100_000`, `pwm_hz = 1_000` → 分辨率 100 级
+//! - 舵机: `tick_hz = 50_000`, `pwm_hz = 50` → 分辨率 1000 级 (20µs)
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use gpio::soft_pwm::SoftPwm;
+//!
+//! let mut pwm: SoftPwm<4> = SoftPwm::new(100_000, 1_000).unwrap();
+//! let led = pwm.attach(GpioPin::new(GpioBank::Gpio0, 13)).unwrap();
+//! pwm.set_duty(led, 250).unwrap();   // 25.0%
+//! pwm.commit();
+//!
+//! // 在 100kHz 定时器中断中:
+//! pwm.tick();
+//! ```
+
+use crate::claim::{self, PinClaim, PinConflict};
+use crate::{GpioDirection, GpioLevel, GpioPin};
+
+/// 占空比满量程 (千分比)
+pub const DUTY_MAX: u16 = 1000;
+
+/// 软件 PWM 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PwmError {
+    /// 节拍频率不足以产生所需的 PWM 频率 (每周期至少 2 个节拍)
+    InvalidFrequency,
+    /// 所有通道都已占用
+    NoFreeChannel,
+    /// 通道号无效或未连接引脚
+    InvalidChannel,
+    /// 占空比超出 0..=1000
+    InvalidDuty,
+    /// 引脚已被占用 (包括已连接到本控制器或其他 SoftPwm 的通道)
+    PinInUse(PinConflict),
+}
+
+/// 单个 PWM 通道
+struct PwmChannel {
+    pin: GpioPin,
+    /// 当前周期使用的高电平节拍数
+    active: u32,
+    /// 暂存的高电平节拍数，commit 后生效
+    pending: u32,
+    _claim: PinClaim,
+}
+
+/// 软件 PWM 控制器
+///
+/// `N` 为最多可连接的引脚数，所有通道共用同一个 PWM 频率。
+pub struct SoftPwm<const N: usize> {
+    channels: [Option<PwmChannel>; N],
+    period_ticks: u32,
+    counter: u32,
+    committed: bool,
+}
+
+impl<const N: usize> SoftPwm<N> {
+    /// 创建软件 PWM 控制器
+    ///
+    /// # 参数
+    /// - `tick_hz`: 调用 `tick()` 的频率
+    /// - `pwm_hz`: PWM 输出频率
+    pub fn new(tick_hz: u32, pwm_hz: u32) -> Result<Self, PwmError> {
+        if pwm_hz == 0 || tick_hz / pwm_hz < 2 {
+            return Err(PwmError::InvalidFrequency);
+        }
+
+        Ok(Self {
+            channels: [const { None }; N],
+            period_ticks: tick_hz / pwm_hz,
+            counter: 0,
+            committed: false,
+        })
+    }
+
+    /// 每个 PWM 周期的节拍数 (占空比分辨率)
+    pub fn resolution(&self) -> u32 {
+        self.period_ticks
+    }
+
+    /// 连接一个引脚，初始占空比为 0
+    ///
+    /// 引脚会被设置为输出模式并拉低，并登记为 `soft-pwm` 占用
+    ///
+    /// # 返回值
+    /// - `Ok(channel)`: 通道号，用于后续设置占空比
+    /// - `Err(PwmError::NoFreeChannel)`: 所有通道都已占用
+    /// - `Err(PwmError::PinInUse)`: 引脚已被占用，引脚配置不变
+    pub fn attach(&mut self, pin: GpioPin) -> Result<usize, PwmError> {
+        let slot = self
+            .channels
+            .iter()
+            .position(|c| c.is_none())
+            .ok_or(PwmError::NoFreeChannel)?;
+
+        let claim = claim::claim_pin(&pin, "soft-pwm").map_err(PwmError::PinInUse)?;
+        pin.set_direction(GpioDirection::Output);
+        pin.set_level(GpioLevel::Low);
+        self.channels[slot] = Some(PwmChannel {
+            pin,
+            active: 0,
+            pending: 0,
+            _claim: claim,
+        });
+        Ok(slot)
+    }
+
+    /// 断开通道，引脚保持低电平并归还给调用者 (同时释放登记)
+    pub fn detach(&mut self, channel: usize) -> Option<GpioPin> {
+        let ch = self.channels.get_mut(channel)?.take()?;
+        ch.pin.set_level(GpioLevel::Low);
+        Some(ch.pin)
+    }
+
+    /// 设置暂存占空比 (千分比, 0..=1000)
+    ///
+    /// 需要调用 [`commit`](Self::commit) 才会生效
+    pub fn set_duty(&mut self, channel: usize, duty: u16) -> Result<(), PwmError> {
+        if duty > DUTY_MAX {
+            return Err(PwmError::InvalidDuty);
+        }
+        let ticks = duty as u32 * self.period_ticks / DUTY_MAX as u32;
+        self.set_duty_ticks(channel, ticks)
+    }
+
+    /// 以节拍数设置暂存的高电平宽度 (0..=resolution)
+    ///
+    /// 比千分比更精细，用于舵机脉宽等需要完整分辨率的场合，
+    /// 需要调用 [`commit`](Self::commit) 才会生效
+    pub fn set_duty_ticks(&mut self, channel: usize, ticks: u32) -> Result<(), PwmError> {
+        if ticks > self.period_ticks {
+            return Err(PwmError::InvalidDuty);
+        }
+        let ch = self
+            .channels
+            .get_mut(channel)
+            .and_then(|c| c.as_mut())
+            .ok_or(PwmError::InvalidChannel)?;
+        ch.pending = ticks;
+        Ok(())
+    }
+
+    /// 提交所有暂存的占空比，在下一个周期开始时同步生效
+    pub fn commit(&mut self) {
+        self.committed = true;
+    }
+
+    /// 定时器节拍处理，应在定时器中断中以 `tick_hz` 频率调用
+    pub fn tick(&mut self) {
+        if self.counter == 0 {
+            let latch = core::mem::take(&mut self.committed);
+            for ch in self.channels.iter_mut().flatten() {
+                if latch {
+                    ch.active = ch.pending;
+                }
+                if ch.active > 0 {
+                    ch.pin.set_level(GpioLevel::High);
+                }
+            }
+        }
+
+        for ch in self.channels.iter_mut().flatten() {
+            if self.counter == ch.active && ch.active < self.period_ticks {
+                ch.pin.set_level(GpioLevel::Low);
+            }
+        }
+
+        self.counter += 1;
+        if self.counter == self.period_ticks {
+            self.counter = 0;
+        }
+    }
+}
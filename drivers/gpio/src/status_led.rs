@@ -0,0 +1,178 @@
+//! 状态指示灯
+//!
+//! 用一个 GPIO LED 的闪烁模式表示系统状态，没有串口时也能判断板子卡在哪里。
+//!
+//! | 状态 | 闪烁模式 |
+//! |------|----------|
+//! | 启动中 | 2Hz 均匀闪烁 |
+//! | 运行中 | 心跳 (每秒两次短闪) |
+//! | 错误码 N | 闪 N 次 (300ms 亮/300ms 灭)，停 1.5s，循环 |
+//! | Panic | 10Hz 快闪 |
+//!
+//! # 工作方式
+//! 与软件 PWM 相同，由定时器中断以固定频率调用 [`StatusLed::tick`]。
+//! panic 处理函数中定时器可能已经不可用，此时使用 [`panic_blink`] 忙等闪烁。
+//!
+//! # 注意
+//! 目前只支持普通 GPIO LED，WS2812 等串行 RGB LED 需要等 SPI/PWM 驱动完成后再支持。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use gpio::status_led::{StatusLed, SystemState};
+//!
+//! // 100Hz 节拍
+//! let mut led = StatusLed::new(GpioPin::new(GpioBank::Gpio0, 13), 100).unwrap();
+//! led.set_state(SystemState::Running);
+//!
+//! // 在 100Hz 定时器中断中:
+//! led.tick();
+//! ```
+
+use crate::claim::{self, PinClaim, PinConflict};
+use crate::{GpioDirection, GpioLevel, GpioPin};
+
+/// 系统状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemState {
+    /// 启动中
+    Booting,
+    /// 正常运行
+    Running,
+    /// 错误码 (1-9 次闪烁便于人工计数)
+    Error(u8),
+    /// 发生 panic
+    Panic,
+}
+
+/// 错误码每次闪烁的周期 (毫秒)
+const ERROR_BLINK_MS: u32 = 600;
+
+/// 错误码两组闪烁之间的停顿 (毫秒)
+const ERROR_PAUSE_MS: u32 = 1500;
+
+/// 状态对应的闪烁周期 (毫秒)
+fn period_ms(state: SystemState) -> u32 {
+    match state {
+        SystemState::Booting => 500,
+        SystemState::Running => 1000,
+        SystemState::Error(code) => code.max(1) as u32 * ERROR_BLINK_MS + ERROR_PAUSE_MS,
+        SystemState::Panic => 100,
+    }
+}
+
+/// 计算状态在周期内 `t_ms` 时刻 LED 是否点亮
+pub fn pattern_level(state: SystemState, t_ms: u32) -> bool {
+    let t = t_ms % period_ms(state);
+    match state {
+        SystemState::Booting => t < 250,
+        // 心跳: 亮 70ms, 灭 210ms, 亮 70ms, 其余时间熄灭
+        SystemState::Running => t < 70 || (280..350).contains(&t),
+        SystemState::Error(code) => {
+            t < code.max(1) as u32 * ERROR_BLINK_MS && t % ERROR_BLINK_MS < ERROR_BLINK_MS / 2
+        }
+        SystemState::Panic => t < 50,
+    }
+}
+
+/// 状态指示灯
+pub struct StatusLed {
+    pin: GpioPin,
+    state: SystemState,
+    /// 每个节拍的毫秒数
+    tick_ms: u32,
+    /// 当前状态已持续的时间 (毫秒)
+    elapsed_ms: u32,
+    /// LED 低电平点亮
+    active_low: bool,
+    _claim: PinClaim,
+}
+
+impl StatusLed {
+    /// 创建状态指示灯，初始状态为 `Booting`
+    ///
+    /// # 参数
+    /// - `pin`: LED 引脚，会被设置为输出模式
+    /// - `tick_hz`: 调用 `tick()` 的频率，建议 100Hz (不超过 1000Hz)
+    ///
+    /// # 返回值
+    /// - `Ok(StatusLed)`: 引脚已登记 (使用者 `status-led`)，实例丢弃时释放
+    /// - `Err(PinConflict)`: 引脚已被占用，引脚配置不变
+    pub fn new(pin: GpioPin, tick_hz: u32) -> Result<Self, PinConflict> {
+        assert!(tick_hz > 0 && tick_hz <= 1000, "Tick rate must be 1-1000 Hz");
+        let claim = claim::claim_pin(&pin, "status-led")?;
+
+        pin.set_direction(GpioDirection::Output);
+        let led = Self {
+            pin,
+            state: SystemState::Booting,
+            tick_ms: 1000 / tick_hz,
+            elapsed_ms: 0,
+            active_low: false,
+            _claim: claim,
+        };
+        led.apply(true);
+        Ok(led)
+    }
+
+    /// 设置 LED 为低电平点亮
+    pub fn active_low(mut self) -> Self {
+        self.active_low = true;
+        self
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> SystemState {
+        self.state
+    }
+
+    /// 切换状态，新模式从头开始
+    pub fn set_state(&mut self, state: SystemState) {
+        if state != self.state {
+            self.state = state;
+            self.elapsed_ms = 0;
+        }
+    }
+
+    /// 定时器节拍处理，应在定时器中断中以 `tick_hz` 频率调用
+    pub fn tick(&mut self) {
+        self.elapsed_ms = (self.elapsed_ms + self.tick_ms) % period_ms(self.state);
+        self.apply(pattern_level(self.state, self.elapsed_ms));
+    }
+
+    fn apply(&self, on: bool) {
+        let high = on != self.active_low;
+        self.pin.set_level(if high { GpioLevel::High } else { GpioLevel::Low });
+    }
+}
+
+/// panic 时的快闪 (不返回)
+///
+/// 不依赖定时器，用忙等循环计时。`spins_per_ms` 为每毫秒的空循环次数，
+/// 只需大致准确，能让人眼分辨快闪即可。
+///
+/// gpio 不依赖 panic_dump，由板级代码把它注册为 panic 后的停机钩子。
+/// 这时 [`StatusLed`] 可能仍持有该引脚，直接按编号重新构造即可。
+///
+/// # 示例
+/// ```no_run
+/// use gpio::status_led::panic_blink;
+/// use gpio::{GpioBank, GpioPin};
+///
+/// fn blink_status_led() {
+///     // 1.8GHz 下每毫秒约 20 万次空循环
+///     panic_blink(&GpioPin::new(GpioBank::Gpio0, 13), 200_000);
+/// }
+///
+/// // panic 信息输出后快闪状态灯
+/// panic_dump::set_halt_hook(blink_status_led);
+/// ```
+pub fn panic_blink(pin: &GpioPin, spins_per_ms: u32) -> ! {
+    pin.set_direction(GpioDirection::Output);
+    loop {
+        pin.toggle();
+        for _ in 0..spins_per_ms.saturating_mul(50) {
+            core::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! 编译期确定的 GPIO 引脚
+//!
+//! Bank 和引脚号作为 const 泛型参数，方向作为类型状态：
+//! - 引脚号越界在编译时报错 (而不是 [`GpioPin::new`] 的运行时 panic)
+//! - 输入引脚没有 `set_high`，未配置方向的引脚不能读写，误用在编译时报错
+//! - 零大小类型，寄存器地址在编译时算好，没有运行时开销
+//!
+//! 需要动态选择引脚时用 [`Pin::erase`] 转换为 [`GpioPin`]。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::typed::Pin;
+//!
+//! // GPIO0_B5
+//! let led = Pin::<0, 13>::new().into_output();
+//! led.set_high();
+//!
+//! let button = Pin::<1, 2>::new().into_input();
+//! if button.is_low() {
+//!     led.toggle();
+//! }
+//! ```
+//!
+//! 下面的代码无法通过编译：
+//! ```compile_fail
+//! use gpio::typed::Pin;
+//!
+//! let button = Pin::<1, 2>::new().into_input();
+//! button.set_high();
+//! ```
+
+use core::marker::PhantomData;
+use mmio::read_volatile;
+
+use crate::{
+    masked_reg, write_masked, GpioBank, GpioPin, GPIO0_BASE, GPIO1_BASE, GPIO2_BASE, GPIO3_BASE,
+    GPIO4_BASE, GPIO_EXT_PORT, GPIO_SWPORT_DDR_L, GPIO_SWPORT_DR_L,
+};
+
+/// 未配置方向
+pub struct Unconfigured;
+/// 输入模式
+pub struct Input;
+/// 输出模式
+pub struct Output;
+
+/// 编译期确定的 GPIO 引脚
+///
+/// - `BANK`: Bank 号 (0-4)
+/// - `PIN`: 引脚号 (0-31)
+/// - `MODE`: 方向状态
+pub struct Pin<const BANK: u8, const PIN: u8, MODE = Unconfigured> {
+    _mode: PhantomData<MODE>,
+}
+
+impl<const BANK: u8, const PIN: u8, MODE> Pin<BANK, PIN, MODE> {
+    /// 寄存器基址，参数越界时编译失败
+    const BASE: usize = {
+        assert!(PIN < 32, "Pin number must be less than 32");
+        match BANK {
+            0 => GPIO0_BASE,
+            1 => GPIO1_BASE,
+            2 => GPIO2_BASE,
+            3 => GPIO3_BASE,
+            4 => GPIO4_BASE,
+            _ => panic!("Bank must be 0-4"),
+        }
+    };
+
+    const MASK: u32 = 1 << PIN;
+
+    /// 数据寄存器 (DR_L 或 DR_H) 偏移和其中的位掩码
+    const DR: (usize, u32) = masked_reg(GPIO_SWPORT_DR_L, PIN);
+
+    /// 方向寄存器 (DDR_L 或 DDR_H) 偏移和其中的位掩码
+    const DDR: (usize, u32) = masked_reg(GPIO_SWPORT_DDR_L, PIN);
+
+    const fn transition<NEW>() -> Pin<BANK, PIN, NEW> {
+        Pin { _mode: PhantomData }
+    }
+
+    fn set_ddr(output: bool) {
+        let (reg, mask) = Self::DDR;
+        unsafe { write_masked(Self::BASE + reg, mask, if output { mask } else { 0 }) }
+    }
+
+    /// 设置为输出模式
+    pub fn into_output(self) -> Pin<BANK, PIN, Output> {
+        Self::set_ddr(true);
+        Self::transition()
+    }
+
+    /// 设置为输入模式
+    pub fn into_input(self) -> Pin<BANK, PIN, Input> {
+        Self::set_ddr(false);
+        Self::transition()
+    }
+
+    /// 转换为运行时引脚，方向保持不变
+    pub fn erase(self) -> GpioPin {
+        let bank = match BANK {
+            0 => GpioBank::Gpio0,
+            1 => GpioBank::Gpio1,
+            2 => GpioBank::Gpio2,
+            3 => GpioBank::Gpio3,
+            _ => GpioBank::Gpio4,
+        };
+        GpioPin::new(bank, PIN)
+    }
+}
+
+impl<const BANK: u8, const PIN: u8> Pin<BANK, PIN, Unconfigured> {
+    /// 创建引脚，Bank 或引脚号越界时编译失败
+    pub const fn new() -> Self {
+        let _ = Self::BASE;
+        Self::transition()
+    }
+}
+
+impl<const BANK: u8, const PIN: u8> Default for Pin<BANK, PIN, Unconfigured> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BANK: u8, const PIN: u8> Pin<BANK, PIN, Output> {
+    fn write_dr(value: u32) {
+        let (reg, mask) = Self::DR;
+        unsafe { write_masked(Self::BASE + reg, mask, value) }
+    }
+
+    /// 输出高电平
+    pub fn set_high(&self) {
+        Self::write_dr(u32::MAX);
+    }
+
+    /// 输出低电平
+    pub fn set_low(&self) {
+        Self::write_dr(0);
+    }
+
+    /// 翻转输出电平
+    pub fn toggle(&self) {
+        let (reg, _) = Self::DR;
+        Self::write_dr(!unsafe { read_volatile((Self::BASE + reg) as *const u32) });
+    }
+}
+
+impl<const BANK: u8, const PIN: u8> Pin<BANK, PIN, Input> {
+    /// 引脚是否为高电平
+    pub fn is_high(&self) -> bool {
+        unsafe { read_volatile((Self::BASE + GPIO_EXT_PORT) as *const u32) & Self::MASK != 0 }
+    }
+
+    /// 引脚是否为低电平
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+}
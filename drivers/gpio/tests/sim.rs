@@ -0,0 +1,169 @@
+//! 基于主机端寄存器模型的 GPIO 驱动测试
+//!
+//! 运行: `cargo test -p gpio --features sim`
+
+#![cfg(feature = "sim")]
+
+use gpio::claim::{self, PinAssignment};
+use gpio::encoder::QuadratureEncoder;
+use gpio::fast::FastGpio;
+use gpio::soft_pwm::{PwmError, SoftPwm};
+use gpio::status_led::StatusLed;
+use gpio::typed::Pin;
+use gpio::{GpioBank, GpioDirection, GpioLevel, GpioPin, GPIO1_BASE, GPIO2_BASE};
+use mmio::sim::{self, GpioModel};
+
+#[test]
+fn output_drives_pin() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let pin = GpioPin::new(GpioBank::Gpio1, 5);
+
+    pin.set_direction(GpioDirection::Output);
+    pin.set_level(GpioLevel::High);
+    assert!(model.borrow().is_output(5));
+    assert!(model.borrow().level(5));
+
+    pin.toggle();
+    assert!(!model.borrow().level(5));
+}
+
+#[test]
+fn input_reads_external_level() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let pin = GpioPin::new(GpioBank::Gpio1, 7);
+
+    pin.set_direction(GpioDirection::Input);
+    assert_eq!(pin.get_level(), GpioLevel::Low);
+    model.borrow_mut().set_input(7, true);
+    assert_eq!(pin.get_level(), GpioLevel::High);
+}
+
+#[test]
+fn pins_in_same_bank_are_independent() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let a = GpioPin::new(GpioBank::Gpio1, 0);
+    let b = GpioPin::new(GpioBank::Gpio1, 31);
+
+    a.set_direction(GpioDirection::Output);
+    b.set_direction(GpioDirection::Output);
+    a.set_level(GpioLevel::High);
+    b.set_level(GpioLevel::High);
+    a.set_level(GpioLevel::Low);
+    assert!(!model.borrow().level(0));
+    assert!(model.borrow().level(31));
+}
+
+#[test]
+fn fast_gpio_write_and_toggle() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let pin = FastGpio::new(GpioPin::new(GpioBank::Gpio1, 2)).unwrap();
+
+    pin.write(true);
+    assert!(model.borrow().level(2));
+    assert!(pin.is_high());
+    pin.toggle();
+    assert!(!model.borrow().level(2));
+}
+
+#[test]
+fn fast_gpio_leaves_other_pins_alone() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let other = GpioPin::new(GpioBank::Gpio1, 4);
+    other.set_direction(GpioDirection::Output);
+    other.set_level(GpioLevel::High);
+
+    // 引脚 16-31 在 DR_H 中
+    let pin = FastGpio::new(GpioPin::new(GpioBank::Gpio1, 21)).unwrap();
+    pin.set_high();
+    assert!(model.borrow().level(21));
+    pin.set_low();
+    assert!(!model.borrow().level(21));
+    pin.toggle();
+    assert!(model.borrow().level(21));
+    assert!(model.borrow().level(4));
+}
+
+#[test]
+fn fast_gpio_refuses_claimed_pin() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let _cs = claim::claim(GpioBank::Gpio1, 20, "spi-cs").unwrap();
+    let Err(conflict) = FastGpio::new(GpioPin::new(GpioBank::Gpio1, 20)) else {
+        panic!("claimed pin accepted");
+    };
+    assert_eq!(
+        conflict.to_string(),
+        "GPIO1_C4 claimed by `spi-cs`, requested by `fast-gpio`"
+    );
+    assert!(!model.borrow().is_output(20));
+}
+
+// 以下测试各用 GPIO2 的不同引脚：登记表是全局的，测试并行运行
+
+#[test]
+fn second_fast_gpio_on_pin_is_rejected_until_first_dropped() {
+    let _model = sim::map(GPIO2_BASE, 0x100, GpioModel::new());
+    let first = FastGpio::new(GpioPin::new(GpioBank::Gpio2, 1)).unwrap();
+    assert!(FastGpio::new(GpioPin::new(GpioBank::Gpio2, 1)).is_err());
+    drop(first);
+    assert!(FastGpio::new(GpioPin::new(GpioBank::Gpio2, 1)).is_ok());
+}
+
+#[test]
+fn encoder_rejects_same_pin_for_both_phases() {
+    let _model = sim::map(GPIO2_BASE, 0x100, GpioModel::new());
+    let a = GpioPin::new(GpioBank::Gpio2, 2);
+    let b = GpioPin::new(GpioBank::Gpio2, 2);
+    assert!(QuadratureEncoder::<4>::new(a, b, 1_000).is_err());
+    // A 相的登记随构造失败一起释放
+    assert_eq!(claim::owner(GpioBank::Gpio2, 2), None);
+}
+
+#[test]
+fn soft_pwm_rejects_pin_attached_to_another_channel() {
+    let _model = sim::map(GPIO2_BASE, 0x100, GpioModel::new());
+    let mut a: SoftPwm<2> = SoftPwm::new(10_000, 100).unwrap();
+    let mut b: SoftPwm<2> = SoftPwm::new(10_000, 100).unwrap();
+
+    let ch = a.attach(GpioPin::new(GpioBank::Gpio2, 3)).unwrap();
+    let conflict = match b.attach(GpioPin::new(GpioBank::Gpio2, 3)) {
+        Err(PwmError::PinInUse(conflict)) => conflict,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(conflict.owner, "soft-pwm");
+    assert!(matches!(
+        a.attach(GpioPin::new(GpioBank::Gpio2, 3)),
+        Err(PwmError::PinInUse(_))
+    ));
+
+    a.detach(ch).unwrap();
+    assert!(b.attach(GpioPin::new(GpioBank::Gpio2, 3)).is_ok());
+}
+
+#[test]
+fn board_reservation_admits_one_instance_of_its_owner() {
+    let _model = sim::map(GPIO2_BASE, 0x100, GpioModel::new());
+    claim::claim_board(&[PinAssignment::new(GpioBank::Gpio2, 4, "status-led")]).unwrap();
+
+    assert!(FastGpio::new(GpioPin::new(GpioBank::Gpio2, 4)).is_err());
+    let led = StatusLed::new(GpioPin::new(GpioBank::Gpio2, 4), 100).unwrap();
+    assert!(StatusLed::new(GpioPin::new(GpioBank::Gpio2, 4), 100).is_err());
+
+    // 实例丢弃后恢复为预留，不会变成空闲
+    drop(led);
+    assert_eq!(claim::owner(GpioBank::Gpio2, 4), Some("status-led"));
+    assert!(FastGpio::new(GpioPin::new(GpioBank::Gpio2, 4)).is_err());
+}
+
+#[test]
+fn typed_pin_sets_direction() {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+
+    let led = Pin::<1, 3>::new().into_output();
+    led.set_high();
+    assert!(model.borrow().is_output(3));
+    assert!(model.borrow().level(3));
+
+    let button = Pin::<1, 4>::new().into_input();
+    model.borrow_mut().set_input(4, true);
+    assert!(button.is_high());
+}
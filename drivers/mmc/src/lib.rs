@@ -4,7 +4,7 @@
 
 #![no_std]
 
-use core::ptr::{read_volatile, write_volatile};
+use mmio::{read_volatile, write_volatile};
 
 /// SDMMC0 基址 (TF卡接口)
 pub const SDMMC0_BASE: usize = 0xFE2C0000;
@@ -25,9 +25,11 @@ const SDMMC_RESP0: usize = 0x030;     // 响应寄存器0
 const SDMMC_RESP1: usize = 0x034;     // 响应寄存器1
 const SDMMC_RESP2: usize = 0x038;     // 响应寄存器2
 const SDMMC_RESP3: usize = 0x03C;     // 响应寄存器3
+const SDMMC_RINTSTS: usize = 0x044;   // 原始中断状态寄存器 (写 1 清除)
 const SDMMC_STATUS: usize = 0x048;    // 状态寄存器
 const SDMMC_FIFOTH: usize = 0x04C;    // FIFO 阈值寄存器
 const SDMMC_CDETECT: usize = 0x050;   // 卡检测寄存器
+const SDMMC_DATA: usize = 0x200;      // 数据 FIFO
 
 /// 控制寄存器位定义
 const CTRL_RESET: u32 = 1 << 0;           // 控制器复位
@@ -38,14 +40,67 @@ const CTRL_DMA_ENABLE: u32 = 1 << 5;      // DMA 使能
 
 /// 命令寄存器位定义
 const CMD_START: u32 = 1 << 31;           // 开始命令
+const CMD_RESP_EXP: u32 = 1 << 6;         // 需要响应
+const CMD_CHECK_CRC: u32 = 1 << 8;        // 检查响应 CRC
+const CMD_DATA_EXP: u32 = 1 << 9;         // 带数据传输
+const CMD_WRITE: u32 = 1 << 10;           // 数据方向: 写卡
 const CMD_WAIT_PRVDATA: u32 = 1 << 13;    // 等待前一个数据传输完成
 const CMD_SEND_INIT: u32 = 1 << 15;       // 发送初始化序列
 
+/// 原始中断状态位定义
+const RINTSTS_RE: u32 = 1 << 1;           // 响应错误
+const RINTSTS_CD: u32 = 1 << 2;           // 命令完成
+const RINTSTS_DTO: u32 = 1 << 3;          // 数据传输结束
+const RINTSTS_TXDR: u32 = 1 << 4;         // 发送 FIFO 低于阈值
+const RINTSTS_RXDR: u32 = 1 << 5;         // 接收 FIFO 达到阈值
+const RINTSTS_RCRC: u32 = 1 << 6;         // 响应 CRC 错误
+const RINTSTS_DCRC: u32 = 1 << 7;         // 数据 CRC 错误
+const RINTSTS_RTO: u32 = 1 << 8;          // 响应超时
+const RINTSTS_DRTO: u32 = 1 << 9;         // 数据读超时
+const RINTSTS_HTO: u32 = 1 << 10;         // 主机数据超时 (FIFO 饥饿)
+const RINTSTS_FRUN: u32 = 1 << 11;        // FIFO 上溢/下溢
+const RINTSTS_SBE: u32 = 1 << 13;         // 起始位错误
+const RINTSTS_EBE: u32 = 1 << 15;         // 结束位错误
+const RINTSTS_CMD_ERR: u32 = RINTSTS_RE | RINTSTS_RCRC | RINTSTS_RTO;
+const RINTSTS_DATA_ERR: u32 =
+    RINTSTS_DCRC | RINTSTS_DRTO | RINTSTS_HTO | RINTSTS_FRUN | RINTSTS_SBE | RINTSTS_EBE;
+
+/// 状态寄存器位定义
+const STATUS_FIFO_COUNT_SHIFT: u32 = 17;  // FIFO 中的字数 [29:17]
+const STATUS_FIFO_COUNT_MASK: u32 = 0x1FFF;
+const STATUS_DATA_BUSY: u32 = 1 << 9;     // 卡忙 (DAT0 低电平)
+
+/// FIFOTH 发送阈值 TX_WMark [11:0]
+const FIFOTH_TX_WMARK_MASK: u32 = 0xFFF;
+
+/// 数据 FIFO 深度 (字)
+const FIFO_DEPTH: usize = 256;
+
+/// R1 卡状态中的错误位 (地址越界、块长度错误、写保护、卡内部错误等)
+const R1_ERRORS: u32 = 0xFDF9_8008;
+
+/// 数据块大小 (字节)
+pub const BLOCK_SIZE: usize = 512;
+
+/// 等待数据传输的最大轮询次数
+const DATA_POLLS: u32 = 1_000_000;
+/// 写入后等待卡退出忙状态的最大轮询次数
+const BUSY_POLLS: u32 = 10_000_000;
+
 /// SD 卡命令定义
 const CMD0_GO_IDLE_STATE: u32 = 0;
 const CMD8_SEND_IF_COND: u32 = 8;
 const CMD55_APP_CMD: u32 = 55;
 const ACMD41_SD_SEND_OP_COND: u32 = 41;
+const CMD17_READ_SINGLE_BLOCK: u32 = 17;
+const CMD24_WRITE_BLOCK: u32 = 24;
+
+/// 故障注入：命令超时
+#[cfg(feature = "fault-inject")]
+static CMD_TIMEOUT_FAULT: fault::FaultPoint = fault::FaultPoint::new("mmc.cmd_timeout");
+/// 故障注入：数据 CRC 错误
+#[cfg(feature = "fault-inject")]
+static DATA_CRC_FAULT: fault::FaultPoint = fault::FaultPoint::new("mmc.data_crc");
 
 #[derive(Debug)]
 pub enum MmcError {
@@ -54,6 +109,18 @@ pub enum MmcError {
     CommandTimeout,
     CardNotPresent,
     UnsupportedCard,
+    /// 缓冲区长度不是 [`BLOCK_SIZE`]
+    InvalidBuffer,
+    /// 命令响应错误 (响应超时或 CRC 错误)
+    ResponseError,
+    /// 数据 CRC 错误 (含起始位/结束位错误)
+    DataCrc,
+    /// 数据传输超时
+    DataTimeout,
+    /// 卡在 R1 响应中报告错误 (地址越界、写保护等)
+    CardError,
+    /// 写入后卡一直处于忙状态
+    BusyTimeout,
 }
 
 pub struct SdMmc {
@@ -68,8 +135,14 @@ impl SdMmc {
     
     /// 初始化 SDMMC 控制器
     pub fn init(&self) -> Result<(), MmcError> {
+        #[cfg(feature = "fault-inject")]
+        let _ = fault::register(&CMD_TIMEOUT_FAULT);
+        #[cfg(feature = "fault-inject")]
+        let _ = fault::register(&DATA_CRC_FAULT);
+        
         // 1. 检测卡是否插入
         if !self.card_detect() {
+            klog::debug!("no card present");
             return Err(MmcError::CardNotPresent);
         }
         
@@ -110,6 +183,7 @@ impl SdMmc {
             while read_volatile(ctrl_addr) & 0x07 != 0 {
                 timeout -= 1;
                 if timeout == 0 {
+                    klog::warn!("controller reset timed out");
                     return Err(MmcError::ResetTimeout);
                 }
             }
@@ -144,6 +218,7 @@ impl SdMmc {
                 0
             };
             write_volatile(clkdiv_addr, div);
+            klog::debug!("clock {} Hz, div {}", freq, div);
             
             // 3. 使能时钟
             write_volatile(clkena_addr, 1);
@@ -221,31 +296,254 @@ impl SdMmc {
             let cmd_addr = (self.base + SDMMC_CMD) as *mut u32;
             write_volatile(cmd_addr, CMD_START | cmd);
             
-            // 3. 等待命令完成
+            // 3. 等待命令完成 (注入故障时按超时处理)
+            #[cfg(feature = "fault-inject")]
+            let injected = CMD_TIMEOUT_FAULT.should_fail();
+            #[cfg(not(feature = "fault-inject"))]
+            let injected = false;
             let mut timeout = 10000;
-            while read_volatile(cmd_addr) & CMD_START != 0 {
+            while injected || read_volatile(cmd_addr) & CMD_START != 0 {
                 timeout -= 1;
                 if timeout == 0 {
+                    klog::warn!("CMD{} timed out (arg {:#010x})", cmd & 0x3F, arg);
                     return Err(MmcError::CommandTimeout);
                 }
             }
             
             // 4. 读取响应
             let resp0_addr = (self.base + SDMMC_RESP0) as *const u32;
-            Ok(read_volatile(resp0_addr))
+            let resp = read_volatile(resp0_addr);
+            klog::trace!("CMD{} arg {:#010x} -> {:#010x}", cmd & 0x3F, arg, resp);
+            Ok(resp)
         }
     }
     
-    /// 读取块数据
+    /// 读取一个数据块 (CMD17，PIO 方式从数据 FIFO 读出)
+    /// 
+    /// # 参数
+    /// - `block_addr`: 块地址 (SDHC/SDXC 卡按块编址，SDSC 卡需要传入字节地址)
+    /// - `buffer`: 长度为 [`BLOCK_SIZE`] 的缓冲区
+    /// 
+    /// # 返回值
+    /// - `Ok(())`: 读取成功
+    /// - `Err(MmcError)`: 缓冲区长度错误、命令或数据传输出错
+    /// 
+    /// # 注意
+    /// 调用前卡应已完成识别并处于传输状态 (CMD7 选中)
     pub fn read_block(&self, block_addr: u32, buffer: &mut [u8]) -> Result<(), MmcError> {
-        // TODO: 实现块读取功能
-        // 这需要实现完整的 SD 卡协议
+        if buffer.len() != BLOCK_SIZE {
+            return Err(MmcError::InvalidBuffer);
+        }
+        self.prepare_data(BLOCK_SIZE as u32)?;
+        self.send_data_command(CMD17_READ_SINGLE_BLOCK, block_addr)?;
+        
+        let data_addr = (self.base + SDMMC_DATA) as *const u32;
+        let mut offset = 0;
+        let mut timeout = DATA_POLLS;
+        loop {
+            let rintsts = self.data_status();
+            if rintsts & RINTSTS_DATA_ERR != 0 {
+                return Err(self.data_error(CMD17_READ_SINGLE_BLOCK, rintsts));
+            }
+            
+            // DTO 置位时剩余数据都已在 FIFO 中，先读 RINTSTS 再取数据
+            let count = self.fifo_count().min((BLOCK_SIZE - offset) / 4);
+            for _ in 0..count {
+                let word = unsafe { read_volatile(data_addr) };
+                buffer[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+                offset += 4;
+            }
+            if rintsts & RINTSTS_RXDR != 0 {
+                self.clear_status(RINTSTS_RXDR);
+            }
+            
+            if rintsts & RINTSTS_DTO != 0 {
+                self.clear_status(u32::MAX);
+                if offset != BLOCK_SIZE {
+                    klog::warn!("CMD17 short read: {} of {} bytes", offset, BLOCK_SIZE);
+                    return Err(MmcError::DataTimeout);
+                }
+                return Ok(());
+            }
+            if count == 0 {
+                timeout -= 1;
+                if timeout == 0 {
+                    self.clear_status(u32::MAX);
+                    klog::warn!("CMD17 data timed out ({} bytes read)", offset);
+                    return Err(MmcError::DataTimeout);
+                }
+            }
+        }
+    }
+    
+    /// 数据传输前复位 FIFO、清除中断状态，并设置块大小和传输字节数
+    fn prepare_data(&self, len: u32) -> Result<(), MmcError> {
+        unsafe {
+            let ctrl_addr = (self.base + SDMMC_CTRL) as *mut u32;
+            write_volatile(ctrl_addr, read_volatile(ctrl_addr) | CTRL_FIFO_RESET);
+            
+            let mut timeout = 10000;
+            while read_volatile(ctrl_addr) & CTRL_FIFO_RESET != 0 {
+                timeout -= 1;
+                if timeout == 0 {
+                    klog::warn!("FIFO reset timed out");
+                    return Err(MmcError::ResetTimeout);
+                }
+            }
+            
+            self.clear_status(u32::MAX);
+            write_volatile((self.base + SDMMC_BLKSIZ) as *mut u32, BLOCK_SIZE as u32);
+            write_volatile((self.base + SDMMC_BYTCNT) as *mut u32, len);
+        }
         Ok(())
     }
     
-    /// 写入块数据
+    /// 发送带数据传输的命令，等待命令完成并检查响应和 R1 卡状态
+    /// 
+    /// `cmd` 为命令号，写命令需要附加 `CMD_WRITE`
+    fn send_data_command(&self, cmd: u32, arg: u32) -> Result<(), MmcError> {
+        let flags = CMD_RESP_EXP | CMD_CHECK_CRC | CMD_DATA_EXP | CMD_WAIT_PRVDATA;
+        self.send_command(cmd | flags, arg)?;
+        let cmd = cmd & 0x3F;
+        
+        let mut timeout = 10000;
+        loop {
+            let rintsts = self.status();
+            if rintsts & RINTSTS_CMD_ERR != 0 {
+                self.clear_status(u32::MAX);
+                klog::warn!("CMD{} response error (RINTSTS {:#x})", cmd, rintsts);
+                return Err(MmcError::ResponseError);
+            }
+            if rintsts & RINTSTS_CD != 0 {
+                self.clear_status(RINTSTS_CD);
+                let r1 = unsafe { read_volatile((self.base + SDMMC_RESP0) as *const u32) };
+                if r1 & R1_ERRORS != 0 {
+                    self.clear_status(u32::MAX);
+                    klog::warn!("CMD{} card status error (R1 {:#010x})", cmd, r1);
+                    return Err(MmcError::CardError);
+                }
+                return Ok(());
+            }
+            timeout -= 1;
+            if timeout == 0 {
+                klog::warn!("CMD{} not done (arg {:#010x})", cmd, arg);
+                return Err(MmcError::CommandTimeout);
+            }
+        }
+    }
+    
+    /// 读取原始中断状态
+    fn status(&self) -> u32 {
+        unsafe { read_volatile((self.base + SDMMC_RINTSTS) as *const u32) }
+    }
+    
+    /// 读取数据传输阶段的中断状态 (注入故障时附加数据 CRC 错误)
+    fn data_status(&self) -> u32 {
+        let rintsts = self.status();
+        #[cfg(feature = "fault-inject")]
+        let rintsts = if DATA_CRC_FAULT.should_fail() { rintsts | RINTSTS_DCRC } else { rintsts };
+        rintsts
+    }
+    
+    /// 清除中断状态位
+    fn clear_status(&self, bits: u32) {
+        unsafe { write_volatile((self.base + SDMMC_RINTSTS) as *mut u32, bits) }
+    }
+    
+    /// 数据 FIFO 中的字数
+    fn fifo_count(&self) -> usize {
+        let status = unsafe { read_volatile((self.base + SDMMC_STATUS) as *const u32) };
+        ((status >> STATUS_FIFO_COUNT_SHIFT) & STATUS_FIFO_COUNT_MASK) as usize
+    }
+    
+    /// 清除中断状态并把数据传输错误转换为 [`MmcError`]
+    fn data_error(&self, cmd: u32, rintsts: u32) -> MmcError {
+        self.clear_status(u32::MAX);
+        klog::warn!("CMD{} data error (RINTSTS {:#x})", cmd, rintsts);
+        if rintsts & (RINTSTS_DCRC | RINTSTS_SBE | RINTSTS_EBE) != 0 {
+            MmcError::DataCrc
+        } else {
+            MmcError::DataTimeout
+        }
+    }
+    
+    /// 写入一个数据块 (CMD24，PIO 方式写入数据 FIFO)
+    /// 
+    /// 数据传输结束后等待卡退出忙状态 (编程完成) 才返回
+    /// 
+    /// # 参数
+    /// - `block_addr`: 块地址 (SDHC/SDXC 卡按块编址，SDSC 卡需要传入字节地址)
+    /// - `buffer`: 长度为 [`BLOCK_SIZE`] 的数据
+    /// 
+    /// # 返回值
+    /// - `Ok(())`: 卡已确认写入
+    /// - `Err(MmcError)`: 缓冲区长度错误、卡拒绝写入 (写保护、CRC 状态错误)、超时
+    /// 
+    /// # 注意
+    /// 调用前卡应已完成识别并处于传输状态 (CMD7 选中)
     pub fn write_block(&self, block_addr: u32, buffer: &[u8]) -> Result<(), MmcError> {
-        // TODO: 实现块写入功能
+        if buffer.len() != BLOCK_SIZE {
+            return Err(MmcError::InvalidBuffer);
+        }
+        self.prepare_data(BLOCK_SIZE as u32)?;
+        self.send_data_command(CMD24_WRITE_BLOCK | CMD_WRITE, block_addr)?;
+        
+        let data_addr = (self.base + SDMMC_DATA) as *mut u32;
+        let tx_wmark = unsafe { read_volatile((self.base + SDMMC_FIFOTH) as *const u32) }
+            & FIFOTH_TX_WMARK_MASK;
+        let mut offset = 0;
+        let mut timeout = DATA_POLLS;
+        loop {
+            let rintsts = self.data_status();
+            if rintsts & RINTSTS_DATA_ERR != 0 {
+                return Err(self.data_error(CMD24_WRITE_BLOCK, rintsts));
+            }
+            if rintsts & RINTSTS_DTO != 0 {
+                self.clear_status(u32::MAX);
+                if offset != BLOCK_SIZE {
+                    klog::warn!("CMD24 short write: {} of {} bytes", offset, BLOCK_SIZE);
+                    return Err(MmcError::DataTimeout);
+                }
+                break;
+            }
+            
+            // FIFO 水位降到 TX_WMark 以下 (TXDR) 时补满 FIFO
+            let level = self.fifo_count();
+            let mut written = 0;
+            if offset < BLOCK_SIZE && level <= tx_wmark as usize {
+                written = (FIFO_DEPTH - level).min((BLOCK_SIZE - offset) / 4);
+                for _ in 0..written {
+                    let word = u32::from_le_bytes([
+                        buffer[offset],
+                        buffer[offset + 1],
+                        buffer[offset + 2],
+                        buffer[offset + 3],
+                    ]);
+                    unsafe { write_volatile(data_addr, word) };
+                    offset += 4;
+                }
+                self.clear_status(RINTSTS_TXDR);
+            }
+            if written == 0 {
+                timeout -= 1;
+                if timeout == 0 {
+                    self.clear_status(u32::MAX);
+                    klog::warn!("CMD24 data timed out ({} bytes written)", offset);
+                    return Err(MmcError::DataTimeout);
+                }
+            }
+        }
+        
+        // 等待卡编程完成 (释放 DAT0)
+        let status_addr = (self.base + SDMMC_STATUS) as *const u32;
+        let mut timeout = BUSY_POLLS;
+        while unsafe { read_volatile(status_addr) } & STATUS_DATA_BUSY != 0 {
+            timeout -= 1;
+            if timeout == 0 {
+                klog::warn!("card busy after CMD24 (block {})", block_addr);
+                return Err(MmcError::BusyTimeout);
+            }
+        }
         Ok(())
     }
 }
\ No newline at end of file
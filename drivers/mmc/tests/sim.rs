@@ -0,0 +1,130 @@
+//! 基于主机端寄存器模型的 SDMMC 驱动测试
+//!
+//! 运行: `cargo test -p mmc --features sim`
+
+#![cfg(feature = "sim")]
+
+use mmc::{MmcError, SdMmc, BLOCK_SIZE, SDMMC0_BASE};
+use mmio::sim::{self, MshcModel};
+
+#[test]
+fn init_configures_controller() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    SdMmc::new(SDMMC0_BASE).init().unwrap();
+
+    let model = model.borrow();
+    assert!(model.powered());
+    assert!(model.clock_enabled());
+    // 50MHz / (2 * 400kHz)
+    assert_eq!(model.clock_div(), 62);
+    assert_eq!(model.clock_updates(), 2);
+}
+
+#[test]
+fn init_without_card_fails() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    model.borrow_mut().set_card_present(false);
+
+    let sdmmc = SdMmc::new(SDMMC0_BASE);
+    assert!(!sdmmc.card_detect());
+    assert!(matches!(sdmmc.init(), Err(MmcError::CardNotPresent)));
+    assert!(!model.borrow().powered());
+}
+
+#[test]
+fn send_command_returns_response() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    let sdmmc = SdMmc::new(SDMMC0_BASE);
+
+    assert_eq!(sdmmc.send_command(8, 0x1AA).unwrap(), 0x1AA);
+    model.borrow_mut().set_response(13, 0x900);
+    assert_eq!(sdmmc.send_command(13, 0x1234_0000).unwrap(), 0x900);
+    assert_eq!(model.borrow().commands(), &[(8, 0x1AA), (13, 0x1234_0000)]);
+}
+
+#[test]
+fn read_block_drains_data_fifo() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    let data: Vec<u8> = (0..BLOCK_SIZE).map(|i| i as u8).collect();
+    model.borrow_mut().set_block(42, &data);
+
+    let mut buf = [0u8; BLOCK_SIZE];
+    SdMmc::new(SDMMC0_BASE).read_block(42, &mut buf).unwrap();
+    assert_eq!(&buf[..], &data[..]);
+
+    let model = model.borrow();
+    assert_eq!(model.commands(), &[(17, 42)]);
+    assert_eq!(model.reg(0x01C), BLOCK_SIZE as u32);
+    assert_eq!(model.reg(0x020), BLOCK_SIZE as u32);
+}
+
+#[test]
+fn read_block_reports_data_crc_error() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    model.borrow_mut().fail_next_data(1 << 7);
+
+    let sdmmc = SdMmc::new(SDMMC0_BASE);
+    let mut buf = [0u8; BLOCK_SIZE];
+    assert!(matches!(sdmmc.read_block(0, &mut buf), Err(MmcError::DataCrc)));
+    // 出错后下一次读取正常
+    sdmmc.read_block(0, &mut buf).unwrap();
+}
+
+#[test]
+fn read_block_rejects_short_buffer() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    let mut buf = [0u8; 256];
+    let result = SdMmc::new(SDMMC0_BASE).read_block(0, &mut buf);
+    assert!(matches!(result, Err(MmcError::InvalidBuffer)));
+    assert!(model.borrow().commands().is_empty());
+}
+
+#[test]
+fn write_block_feeds_fifo_and_waits_for_busy() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    model.borrow_mut().set_busy_reads(3);
+    let sdmmc = SdMmc::new(SDMMC0_BASE);
+    sdmmc.init().unwrap();
+
+    let data: Vec<u8> = (0..BLOCK_SIZE).map(|i| (i * 7) as u8).collect();
+    sdmmc.write_block(7, &data).unwrap();
+    assert_eq!(model.borrow().block(7), Some(&data[..]));
+
+    let mut buf = [0u8; BLOCK_SIZE];
+    sdmmc.read_block(7, &mut buf).unwrap();
+    assert_eq!(&buf[..], &data[..]);
+}
+
+#[test]
+fn write_block_reports_rejected_data() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    let sdmmc = SdMmc::new(SDMMC0_BASE);
+    sdmmc.init().unwrap();
+
+    model.borrow_mut().fail_next_data(1 << 7);
+    let data = [0xA5u8; BLOCK_SIZE];
+    assert!(matches!(sdmmc.write_block(3, &data), Err(MmcError::DataCrc)));
+    assert_eq!(model.borrow().block(3), None);
+}
+
+#[test]
+fn write_block_reports_card_status_error() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    // R1 WP_VIOLATION
+    model.borrow_mut().set_response(24, 1 << 26);
+
+    let data = [0u8; BLOCK_SIZE];
+    let result = SdMmc::new(SDMMC0_BASE).write_block(0, &data);
+    assert!(matches!(result, Err(MmcError::CardError)));
+}
+
+#[test]
+fn write_block_times_out_when_card_stays_busy() {
+    let model = sim::map(SDMMC0_BASE, 0x1000, MshcModel::new());
+    model.borrow_mut().set_busy_reads(u32::MAX);
+    let sdmmc = SdMmc::new(SDMMC0_BASE);
+    sdmmc.init().unwrap();
+
+    let data = [0u8; BLOCK_SIZE];
+    assert!(matches!(sdmmc.write_block(0, &data), Err(MmcError::BusyTimeout)));
+}
@@ -0,0 +1,261 @@
+//! Modbus RTU 协议栈
+//!
+//! # 参考资料
+//! - Modbus Application Protocol Specification V1.1b3
+//! - Modbus over Serial Line Specification and Implementation Guide V1.02
+//!
+//! # 功能
+//! - [`master`] - 主站：读写从站的线圈和寄存器
+//! - [`slave`] - 从站：通过 [`slave::DataModel`] 暴露本机数据
+//!
+//! 支持的功能码：01 读线圈、03 读保持寄存器、04 读输入寄存器、
+//! 05 写单个线圈、06 写单个寄存器、16 写多个寄存器。
+//!
+//! # 帧格式
+//! ```text
+//! | 地址 (1B) | 功能码 (1B) | 数据 (0..252B) | CRC16 (2B, 低字节在前) |
+//! ```
+//! 帧之间以不少于 3.5 个字符时间的静默分隔。
+//!
+//! # 链路
+//! 主站和从站通过 [`HalfDuplex`] 收发，RS-485 总线上用 [`Rs485`]：
+//! 每帧 (含 CRC) 一次 [`Rs485::write`] 发出，DE 在最后一个停止位移出后才切回接收。
+//!
+//! # 时序
+//! 应答超时和 3.5 字符帧间隔用 ARM 通用定时器计时 ([`timer::Timeout`])，
+//! 按波特率计算的默认值见 [`ModbusConfig::for_baud`]。
+//!
+//! # 重试
+//! 主站在应答超时、CRC 错误或应答帧不符时重发请求，最多 [`ModbusConfig::retries`] 次；
+//! 异常应答是从站的明确答复，不重试。广播请求没有应答，也不重试。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use uart::{Uart, UART3_BASE};
+//! use uart::rs485::Rs485;
+//! use modbus::{ModbusConfig, master::ModbusMaster};
+//!
+//! let uart = Uart::new(UART3_BASE);
+//! uart.init(9600).unwrap();
+//! let bus = Rs485::new(&uart, GpioPin::new(GpioBank::Gpio3, 12)).unwrap();
+//!
+//! let master = ModbusMaster::new(&bus, ModbusConfig::for_baud(9600));
+//! let mut regs = [0u16; 4];
+//! master.read_holding_registers(1, 0x0000, &mut regs).unwrap();
+//! ```
+
+#![no_std]
+
+use crc::crc16_modbus;
+use timer::Timeout;
+use uart::rs485::Rs485;
+
+pub mod master;
+pub mod slave;
+
+/// RTU 帧最大长度 (字节)
+pub const MAX_FRAME_LEN: usize = 256;
+
+/// 广播地址，从站处理但不应答
+pub const BROADCAST_ADDR: u8 = 0;
+
+/// 功能码
+pub const FC_READ_COILS: u8 = 0x01;
+pub const FC_READ_HOLDING_REGISTERS: u8 = 0x03;
+pub const FC_READ_INPUT_REGISTERS: u8 = 0x04;
+pub const FC_WRITE_SINGLE_COIL: u8 = 0x05;
+pub const FC_WRITE_SINGLE_REGISTER: u8 = 0x06;
+pub const FC_WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+/// Modbus 异常码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionCode {
+    /// 01: 不支持的功能码
+    IllegalFunction,
+    /// 02: 地址超出范围
+    IllegalDataAddress,
+    /// 03: 数据值非法
+    IllegalDataValue,
+    /// 04: 从站设备故障
+    ServerDeviceFailure,
+    /// 其他异常码
+    Other(u8),
+}
+
+impl ExceptionCode {
+    /// 异常码数值
+    pub const fn code(self) -> u8 {
+        match self {
+            ExceptionCode::IllegalFunction => 0x01,
+            ExceptionCode::IllegalDataAddress => 0x02,
+            ExceptionCode::IllegalDataValue => 0x03,
+            ExceptionCode::ServerDeviceFailure => 0x04,
+            ExceptionCode::Other(code) => code,
+        }
+    }
+
+    /// 由数值得到异常码
+    pub const fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => ExceptionCode::IllegalFunction,
+            0x02 => ExceptionCode::IllegalDataAddress,
+            0x03 => ExceptionCode::IllegalDataValue,
+            0x04 => ExceptionCode::ServerDeviceFailure,
+            code => ExceptionCode::Other(code),
+        }
+    }
+}
+
+/// Modbus 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusError {
+    /// 等待应答超时
+    Timeout,
+    /// CRC 校验失败
+    CrcMismatch,
+    /// 帧过短、过长或内容与请求不符
+    InvalidFrame,
+    /// 从站返回异常应答
+    Exception(ExceptionCode),
+    /// 请求的数量超出协议限制或缓冲区大小
+    InvalidQuantity,
+}
+
+/// 链路时序参数
+///
+/// 默认值对应 9600 波特率，其他波特率用 [`ModbusConfig::for_baud`]
+#[derive(Debug, Clone, Copy)]
+pub struct ModbusConfig {
+    /// 等待应答首字节的时间 (微秒)
+    pub response_timeout_us: u32,
+    /// 静默多久视为帧结束 (微秒，3.5 字符时间)
+    pub frame_gap_us: u32,
+    /// 主站失败后的重试次数 (不含第一次发送)
+    pub retries: u8,
+}
+
+impl ModbusConfig {
+    /// 按波特率计算帧间隔，应答超时 1 秒，重试 2 次
+    ///
+    /// 帧间隔为 3.5 个字符 (每字符 11 位) 的时间；波特率高于 19200 时
+    /// 按规范固定为 1750µs
+    pub const fn for_baud(baud: u32) -> Self {
+        let frame_gap_us = if baud > 19_200 {
+            1_750
+        } else {
+            // 3.5 * 11 位 * 1_000_000 µs / baud，向上取整
+            38_500_000u32.div_ceil(baud)
+        };
+        Self {
+            response_timeout_us: 1_000_000,
+            frame_gap_us,
+            retries: 2,
+        }
+    }
+}
+
+impl Default for ModbusConfig {
+    fn default() -> Self {
+        Self::for_baud(9600)
+    }
+}
+
+/// Modbus CRC16，实现在 `crc` crate
+pub use crc::crc16_modbus as crc16;
+
+/// 半双工链路
+///
+/// 同一时刻只有一方发送；[`write`](HalfDuplex::write) 返回时链路已切回接收方向
+pub trait HalfDuplex {
+    /// 发送一帧 (阻塞直到最后一个字节发送完成)
+    fn write(&self, frame: &[u8]);
+
+    /// 读取一个接收到的字节 (非阻塞)
+    fn read(&self) -> Option<u8>;
+}
+
+impl HalfDuplex for Rs485<'_> {
+    fn write(&self, frame: &[u8]) {
+        Rs485::write(self, frame);
+    }
+
+    fn read(&self) -> Option<u8> {
+        self.uart().getc()
+    }
+}
+
+/// 发送一帧，自动追加 CRC
+///
+/// 帧和 CRC 拼在一起一次写出，方向引脚只切换一次
+pub(crate) fn send_frame<L: HalfDuplex + ?Sized>(link: &L, frame: &[u8]) {
+    let len = frame.len();
+    assert!(len + 2 <= MAX_FRAME_LEN, "Modbus frame too long");
+
+    let mut buf = [0u8; MAX_FRAME_LEN];
+    buf[..len].copy_from_slice(frame);
+    let crc = crc16_modbus(frame);
+    buf[len] = crc as u8;
+    buf[len + 1] = (crc >> 8) as u8;
+    link.write(&buf[..len + 2]);
+}
+
+/// 接收一帧并校验 CRC
+///
+/// # 参数
+/// - `first_byte_timeout_us`: 等待首字节的时间 (微秒)，0 表示只检查一次
+///
+/// # 返回值
+/// 不含 CRC 的帧长度
+pub(crate) fn recv_frame<L: HalfDuplex + ?Sized>(
+    link: &L,
+    buf: &mut [u8; MAX_FRAME_LEN],
+    first_byte_timeout_us: u32,
+    config: &ModbusConfig,
+) -> Result<usize, ModbusError> {
+    let mut timeout = Timeout::new(first_byte_timeout_us as u64);
+    let first = loop {
+        if let Some(b) = link.read() {
+            break b;
+        }
+        if timeout.expired() {
+            return Err(ModbusError::Timeout);
+        }
+    };
+
+    buf[0] = first;
+    let mut len = 1;
+    let mut gap = Timeout::new(config.frame_gap_us as u64);
+    let mut overflow = false;
+    loop {
+        match link.read() {
+            Some(b) => {
+                gap.restart();
+                if len < MAX_FRAME_LEN {
+                    buf[len] = b;
+                    len += 1;
+                } else {
+                    overflow = true;
+                }
+            }
+            None if gap.expired() => break,
+            None => {}
+        }
+    }
+
+    if overflow || len < 4 {
+        return Err(ModbusError::InvalidFrame);
+    }
+
+    let body = len - 2;
+    let crc = buf[body] as u16 | ((buf[body + 1] as u16) << 8);
+    if crc16_modbus(&buf[..body]) != crc {
+        return Err(ModbusError::CrcMismatch);
+    }
+    Ok(body)
+}
+
+/// 大端读取 u16
+pub(crate) fn be16(data: &[u8]) -> u16 {
+    ((data[0] as u16) << 8) | data[1] as u16
+}
@@ -0,0 +1,180 @@
+//! Modbus RTU 主站
+
+use uart::rs485::Rs485;
+
+use crate::{
+    be16, recv_frame, send_frame, ExceptionCode, HalfDuplex, ModbusConfig, ModbusError, BROADCAST_ADDR,
+    FC_READ_COILS, FC_READ_HOLDING_REGISTERS, FC_READ_INPUT_REGISTERS, FC_WRITE_MULTIPLE_REGISTERS,
+    FC_WRITE_SINGLE_COIL, FC_WRITE_SINGLE_REGISTER, MAX_FRAME_LEN,
+};
+
+/// 单次读取寄存器的最大数量
+pub const MAX_READ_REGISTERS: usize = 125;
+
+/// 单次写入寄存器的最大数量
+pub const MAX_WRITE_REGISTERS: usize = 123;
+
+/// 单次读取线圈的最大数量
+pub const MAX_READ_COILS: usize = 2000;
+
+/// Modbus RTU 主站
+///
+/// 链路默认为 [`Rs485`]
+pub struct ModbusMaster<'a, L: HalfDuplex + ?Sized = Rs485<'a>> {
+    link: &'a L,
+    config: ModbusConfig,
+}
+
+impl<'a, L: HalfDuplex + ?Sized> ModbusMaster<'a, L> {
+    /// 在已初始化的链路上创建主站
+    pub const fn new(link: &'a L, config: ModbusConfig) -> Self {
+        Self { link, config }
+    }
+
+    /// 读保持寄存器 (功能码 03)
+    ///
+    /// 读取数量为 `out.len()`，最多 125 个
+    pub fn read_holding_registers(&self, slave: u8, addr: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        self.read_registers(FC_READ_HOLDING_REGISTERS, slave, addr, out)
+    }
+
+    /// 读输入寄存器 (功能码 04)
+    ///
+    /// 读取数量为 `out.len()`，最多 125 个
+    pub fn read_input_registers(&self, slave: u8, addr: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        self.read_registers(FC_READ_INPUT_REGISTERS, slave, addr, out)
+    }
+
+    /// 读线圈 (功能码 01)
+    ///
+    /// 读取数量为 `out.len()`，最多 2000 个
+    pub fn read_coils(&self, slave: u8, addr: u16, out: &mut [bool]) -> Result<(), ModbusError> {
+        let count = out.len();
+        if count == 0 || count > MAX_READ_COILS {
+            return Err(ModbusError::InvalidQuantity);
+        }
+
+        let mut resp = [0u8; MAX_FRAME_LEN];
+        let len = self.transact(slave, &request(slave, FC_READ_COILS, addr, count as u16), &mut resp)?;
+
+        let bytes = count.div_ceil(8);
+        if len != 3 + bytes || resp[2] as usize != bytes {
+            return Err(ModbusError::InvalidFrame);
+        }
+        for (i, coil) in out.iter_mut().enumerate() {
+            *coil = resp[3 + i / 8] & (1 << (i % 8)) != 0;
+        }
+        Ok(())
+    }
+
+    /// 写单个线圈 (功能码 05)
+    pub fn write_single_coil(&self, slave: u8, addr: u16, on: bool) -> Result<(), ModbusError> {
+        let value = if on { 0xFF00 } else { 0x0000 };
+        self.write_single(FC_WRITE_SINGLE_COIL, slave, addr, value)
+    }
+
+    /// 写单个保持寄存器 (功能码 06)
+    pub fn write_single_register(&self, slave: u8, addr: u16, value: u16) -> Result<(), ModbusError> {
+        self.write_single(FC_WRITE_SINGLE_REGISTER, slave, addr, value)
+    }
+
+    /// 写多个保持寄存器 (功能码 16)
+    ///
+    /// 最多 123 个寄存器
+    pub fn write_multiple_registers(&self, slave: u8, addr: u16, values: &[u16]) -> Result<(), ModbusError> {
+        let count = values.len();
+        if count == 0 || count > MAX_WRITE_REGISTERS {
+            return Err(ModbusError::InvalidQuantity);
+        }
+
+        let mut req = [0u8; MAX_FRAME_LEN];
+        req[..6].copy_from_slice(&request(slave, FC_WRITE_MULTIPLE_REGISTERS, addr, count as u16));
+        req[6] = (count * 2) as u8;
+        for (i, v) in values.iter().enumerate() {
+            req[7 + i * 2] = (v >> 8) as u8;
+            req[8 + i * 2] = *v as u8;
+        }
+
+        let mut resp = [0u8; MAX_FRAME_LEN];
+        let len = self.transact(slave, &req[..7 + count * 2], &mut resp)?;
+        if slave != BROADCAST_ADDR && (len != 6 || resp[2..6] != req[2..6]) {
+            return Err(ModbusError::InvalidFrame);
+        }
+        Ok(())
+    }
+
+    fn read_registers(&self, function: u8, slave: u8, addr: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        let count = out.len();
+        if count == 0 || count > MAX_READ_REGISTERS {
+            return Err(ModbusError::InvalidQuantity);
+        }
+
+        let mut resp = [0u8; MAX_FRAME_LEN];
+        let len = self.transact(slave, &request(slave, function, addr, count as u16), &mut resp)?;
+
+        if len != 3 + count * 2 || resp[2] as usize != count * 2 {
+            return Err(ModbusError::InvalidFrame);
+        }
+        for (i, reg) in out.iter_mut().enumerate() {
+            *reg = be16(&resp[3 + i * 2..]);
+        }
+        Ok(())
+    }
+
+    fn write_single(&self, function: u8, slave: u8, addr: u16, value: u16) -> Result<(), ModbusError> {
+        let req = request(slave, function, addr, value);
+        let mut resp = [0u8; MAX_FRAME_LEN];
+        let len = self.transact(slave, &req, &mut resp)?;
+
+        // 写单个线圈/寄存器的正常应答是请求的回显
+        if slave != BROADCAST_ADDR && (len != 6 || resp[..6] != req) {
+            return Err(ModbusError::InvalidFrame);
+        }
+        Ok(())
+    }
+
+    /// 发送请求并等待应答，超时或应答损坏时按 [`ModbusConfig::retries`] 重发
+    ///
+    /// 广播请求不等待应答，返回长度 0
+    fn transact(&self, slave: u8, req: &[u8], resp: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, ModbusError> {
+        let mut attempt = 0;
+        loop {
+            match self.transact_once(slave, req, resp) {
+                Err(e @ (ModbusError::Timeout | ModbusError::CrcMismatch | ModbusError::InvalidFrame))
+                    if attempt < self.config.retries =>
+                {
+                    attempt += 1;
+                    klog::debug!("slave {} fc {:#04x}: {:?}, retry {}", slave, req[1], e, attempt);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn transact_once(&self, slave: u8, req: &[u8], resp: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, ModbusError> {
+        // 丢弃上一次事务残留的数据
+        while self.link.read().is_some() {}
+
+        send_frame(self.link, req);
+        if slave == BROADCAST_ADDR {
+            return Ok(0);
+        }
+
+        let len = recv_frame(self.link, resp, self.config.response_timeout_us, &self.config)?;
+        if resp[0] != slave {
+            return Err(ModbusError::InvalidFrame);
+        }
+        if resp[1] == req[1] | 0x80 {
+            return Err(ModbusError::Exception(ExceptionCode::from_code(resp[2])));
+        }
+        if resp[1] != req[1] {
+            return Err(ModbusError::InvalidFrame);
+        }
+        Ok(len)
+    }
+}
+
+/// 构造 `地址 + 功能码 + 两个 u16 字段` 的请求头
+fn request(slave: u8, function: u8, a: u16, b: u16) -> [u8; 6] {
+    [slave, function, (a >> 8) as u8, a as u8, (b >> 8) as u8, b as u8]
+}
@@ -0,0 +1,206 @@
+//! Modbus RTU 从站
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use uart::{Uart, UART3_BASE};
+//! use uart::rs485::Rs485;
+//! use modbus::{ExceptionCode, ModbusConfig};
+//! use modbus::slave::{DataModel, ModbusSlave};
+//!
+//! struct Regs([u16; 16]);
+//!
+//! impl DataModel for Regs {
+//!     fn read_holding(&mut self, addr: u16) -> Result<u16, ExceptionCode> {
+//!         self.0.get(addr as usize).copied().ok_or(ExceptionCode::IllegalDataAddress)
+//!     }
+//!     fn write_holding(&mut self, addr: u16, value: u16) -> Result<(), ExceptionCode> {
+//!         let reg = self.0.get_mut(addr as usize).ok_or(ExceptionCode::IllegalDataAddress)?;
+//!         *reg = value;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let uart = Uart::new(UART3_BASE);
+//! uart.init(9600).unwrap();
+//! let bus = Rs485::new(&uart, GpioPin::new(GpioBank::Gpio3, 12)).unwrap();
+//!
+//! let mut slave = ModbusSlave::new(&bus, 0x11, ModbusConfig::default(), Regs([0; 16]));
+//! loop {
+//!     let _ = slave.poll();
+//! }
+//! ```
+
+use uart::rs485::Rs485;
+
+use crate::{
+    be16, recv_frame, send_frame, ExceptionCode, HalfDuplex, ModbusConfig, ModbusError, BROADCAST_ADDR,
+    FC_READ_COILS, FC_READ_HOLDING_REGISTERS, FC_READ_INPUT_REGISTERS, FC_WRITE_MULTIPLE_REGISTERS,
+    FC_WRITE_SINGLE_COIL, FC_WRITE_SINGLE_REGISTER, MAX_FRAME_LEN,
+};
+
+/// 从站数据模型
+///
+/// 默认实现均返回 `IllegalFunction`，只需实现用到的数据区
+pub trait DataModel {
+    /// 读保持寄存器
+    fn read_holding(&mut self, _addr: u16) -> Result<u16, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// 写保持寄存器
+    fn write_holding(&mut self, _addr: u16, _value: u16) -> Result<(), ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// 读输入寄存器
+    fn read_input(&mut self, _addr: u16) -> Result<u16, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// 读线圈
+    fn read_coil(&mut self, _addr: u16) -> Result<bool, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// 写线圈
+    fn write_coil(&mut self, _addr: u16, _on: bool) -> Result<(), ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+}
+
+/// Modbus RTU 从站
+///
+/// 链路默认为 [`Rs485`]
+pub struct ModbusSlave<'a, D: DataModel, L: HalfDuplex + ?Sized = Rs485<'a>> {
+    link: &'a L,
+    address: u8,
+    config: ModbusConfig,
+    data: D,
+}
+
+impl<'a, D: DataModel, L: HalfDuplex + ?Sized> ModbusSlave<'a, D, L> {
+    /// 创建从站
+    ///
+    /// # 参数
+    /// - `address`: 从站地址 (1-247)
+    pub fn new(link: &'a L, address: u8, config: ModbusConfig, data: D) -> Self {
+        assert!((1..=247).contains(&address), "Slave address must be 1-247");
+        Self { link, address, config, data }
+    }
+
+    /// 访问数据模型
+    pub fn data(&mut self) -> &mut D {
+        &mut self.data
+    }
+
+    /// 处理一个请求 (非阻塞)
+    ///
+    /// 没有数据时立即返回 `Ok(false)`，处理完一帧返回 `Ok(true)`。
+    /// 发给其他从站的帧会被忽略。
+    pub fn poll(&mut self) -> Result<bool, ModbusError> {
+        let mut req = [0u8; MAX_FRAME_LEN];
+        let len = match recv_frame(self.link, &mut req, 0, &self.config) {
+            Err(ModbusError::Timeout) => return Ok(false),
+            other => other?,
+        };
+
+        let target = req[0];
+        if target != self.address && target != BROADCAST_ADDR {
+            return Ok(true);
+        }
+
+        let mut resp = [0u8; MAX_FRAME_LEN];
+        resp[0] = self.address;
+        resp[1] = req[1];
+        let resp_len = match self.handle(&req[..len], &mut resp) {
+            Ok(n) => n,
+            Err(e) => {
+                resp[1] = req[1] | 0x80;
+                resp[2] = e.code();
+                3
+            }
+        };
+
+        // 广播请求不应答
+        if target != BROADCAST_ADDR {
+            send_frame(self.link, &resp[..resp_len]);
+        }
+        Ok(true)
+    }
+
+    /// 执行请求，返回应答长度 (不含 CRC)
+    fn handle(&mut self, req: &[u8], resp: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, ExceptionCode> {
+        if req.len() < 6 {
+            return Err(ExceptionCode::IllegalDataValue);
+        }
+        let function = req[1];
+        let addr = be16(&req[2..]);
+        let value = be16(&req[4..]);
+
+        match function {
+            FC_READ_HOLDING_REGISTERS | FC_READ_INPUT_REGISTERS => {
+                let count = value as usize;
+                if count == 0 || count > 125 {
+                    return Err(ExceptionCode::IllegalDataValue);
+                }
+                resp[2] = (count * 2) as u8;
+                for i in 0..count {
+                    let reg = addr.checked_add(i as u16).ok_or(ExceptionCode::IllegalDataAddress)?;
+                    let v = if function == FC_READ_HOLDING_REGISTERS {
+                        self.data.read_holding(reg)?
+                    } else {
+                        self.data.read_input(reg)?
+                    };
+                    resp[3 + i * 2] = (v >> 8) as u8;
+                    resp[4 + i * 2] = v as u8;
+                }
+                Ok(3 + count * 2)
+            }
+            FC_READ_COILS => {
+                let count = value as usize;
+                if count == 0 || count > 2000 {
+                    return Err(ExceptionCode::IllegalDataValue);
+                }
+                let bytes = count.div_ceil(8);
+                resp[2] = bytes as u8;
+                resp[3..3 + bytes].fill(0);
+                for i in 0..count {
+                    let coil = addr.checked_add(i as u16).ok_or(ExceptionCode::IllegalDataAddress)?;
+                    if self.data.read_coil(coil)? {
+                        resp[3 + i / 8] |= 1 << (i % 8);
+                    }
+                }
+                Ok(3 + bytes)
+            }
+            FC_WRITE_SINGLE_COIL => {
+                let on = match value {
+                    0xFF00 => true,
+                    0x0000 => false,
+                    _ => return Err(ExceptionCode::IllegalDataValue),
+                };
+                self.data.write_coil(addr, on)?;
+                resp[..6].copy_from_slice(&req[..6]);
+                Ok(6)
+            }
+            FC_WRITE_SINGLE_REGISTER => {
+                self.data.write_holding(addr, value)?;
+                resp[..6].copy_from_slice(&req[..6]);
+                Ok(6)
+            }
+            FC_WRITE_MULTIPLE_REGISTERS => {
+                let count = value as usize;
+                if count == 0 || count > 123 || req.len() != 7 + count * 2 || req[6] as usize != count * 2 {
+                    return Err(ExceptionCode::IllegalDataValue);
+                }
+                for i in 0..count {
+                    let reg = addr.checked_add(i as u16).ok_or(ExceptionCode::IllegalDataAddress)?;
+                    self.data.write_holding(reg, be16(&req[7 + i * 2..]))?;
+                }
+                resp[..6].copy_from_slice(&req[..6]);
+                Ok(6)
+            }
+            _ => Err(ExceptionCode::IllegalFunction),
+        }
+    }
+}
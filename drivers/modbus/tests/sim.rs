@@ -0,0 +1,296 @@
+//! 主站与从站经 RS-485 往返的测试
+//!
+//! 主站一侧是真实的 [`Rs485`] (UART/GPIO 寄存器模型)，写出的请求交给内存链路上的
+//! [`ModbusSlave`] 处理，应答再放回主站 UART 的接收端。链路上可以丢弃或损坏帧。
+//!
+//! 运行: `cargo test -p modbus --features sim`
+
+#![cfg(feature = "sim")]
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use gpio::{GpioBank, GpioPin, GPIO3_BASE};
+use mmio::sim::{self, GpioModel, Handle, UartModel};
+use modbus::master::ModbusMaster;
+use modbus::slave::{DataModel, ModbusSlave};
+use modbus::{ExceptionCode, HalfDuplex, ModbusConfig, ModbusError};
+use uart::rs485::Rs485;
+use uart::{Uart, UartClock, UartConfig, UART3_BASE};
+
+const SLAVE_ADDR: u8 = 0x11;
+
+/// 主机上按轮询次数计时，取较短的超时让重试测试跑得快
+const CONFIG: ModbusConfig = ModbusConfig {
+    response_timeout_us: 1_000,
+    frame_gap_us: 100,
+    retries: 2,
+};
+
+/// 从站数据：8 个保持寄存器、16 个线圈，没有输入寄存器
+#[derive(Default)]
+struct Regs {
+    holding: [u16; 8],
+    coils: [bool; 16],
+}
+
+impl DataModel for Regs {
+    fn read_holding(&mut self, addr: u16) -> Result<u16, ExceptionCode> {
+        self.holding.get(addr as usize).copied().ok_or(ExceptionCode::IllegalDataAddress)
+    }
+
+    fn write_holding(&mut self, addr: u16, value: u16) -> Result<(), ExceptionCode> {
+        let reg = self.holding.get_mut(addr as usize).ok_or(ExceptionCode::IllegalDataAddress)?;
+        *reg = value;
+        Ok(())
+    }
+
+    fn read_coil(&mut self, addr: u16) -> Result<bool, ExceptionCode> {
+        self.coils.get(addr as usize).copied().ok_or(ExceptionCode::IllegalDataAddress)
+    }
+
+    fn write_coil(&mut self, addr: u16, on: bool) -> Result<(), ExceptionCode> {
+        let coil = self.coils.get_mut(addr as usize).ok_or(ExceptionCode::IllegalDataAddress)?;
+        *coil = on;
+        Ok(())
+    }
+}
+
+/// 从站一侧的内存链路
+#[derive(Default)]
+struct Wire {
+    rx: RefCell<VecDeque<u8>>,
+    tx: RefCell<Vec<u8>>,
+}
+
+impl HalfDuplex for Wire {
+    fn write(&self, frame: &[u8]) {
+        self.tx.borrow_mut().extend_from_slice(frame);
+    }
+
+    fn read(&self) -> Option<u8> {
+        self.rx.borrow_mut().pop_front()
+    }
+}
+
+/// 主站一侧的链路
+struct Bus<'a> {
+    port: Rs485<'a>,
+    model: Handle<UartModel>,
+    gpio: Handle<GpioModel>,
+    de: u8,
+    wire: &'a Wire,
+    slave: RefCell<ModbusSlave<'a, Regs, Wire>>,
+    /// 主站发出的请求 (含 CRC)
+    requests: RefCell<Vec<Vec<u8>>>,
+    /// 从站 `poll` 返回的错误
+    slave_errors: RefCell<Vec<ModbusError>>,
+    /// 接下来丢弃几个应答
+    drop_replies: Cell<u32>,
+    /// 接下来损坏几个应答的 CRC
+    corrupt_replies: Cell<u32>,
+    /// 接下来损坏几个请求的 CRC
+    corrupt_requests: Cell<u32>,
+}
+
+impl<'a> Bus<'a> {
+    fn new(uart: &'a Uart, model: Handle<UartModel>, wire: &'a Wire, de: u8) -> Self {
+        let gpio = sim::map(GPIO3_BASE, 0x100, GpioModel::new());
+        Self {
+            port: Rs485::new(uart, GpioPin::new(GpioBank::Gpio3, de)).unwrap(),
+            model,
+            gpio,
+            de,
+            wire,
+            slave: RefCell::new(ModbusSlave::new(wire, SLAVE_ADDR, CONFIG, Regs::default())),
+            requests: RefCell::new(Vec::new()),
+            slave_errors: RefCell::new(Vec::new()),
+            drop_replies: Cell::new(0),
+            corrupt_replies: Cell::new(0),
+            corrupt_requests: Cell::new(0),
+        }
+    }
+
+    fn request_count(&self) -> usize {
+        self.requests.borrow().len()
+    }
+}
+
+/// 数值不为 0 时减一并返回 true
+fn take(counter: &Cell<u32>) -> bool {
+    let n = counter.get();
+    counter.set(n.saturating_sub(1));
+    n > 0
+}
+
+impl HalfDuplex for Bus<'_> {
+    fn write(&self, frame: &[u8]) {
+        self.port.write(frame);
+        assert!(!self.gpio.borrow().level(self.de), "DE left in transmit direction");
+
+        let mut request = self.model.borrow_mut().take_transmitted();
+        assert_eq!(request, frame);
+        self.requests.borrow_mut().push(request.clone());
+        if take(&self.corrupt_requests) {
+            *request.last_mut().unwrap() ^= 0xFF;
+        }
+
+        self.wire.rx.borrow_mut().extend(request);
+        if let Err(e) = self.slave.borrow_mut().poll() {
+            self.slave_errors.borrow_mut().push(e);
+        }
+
+        let mut reply = std::mem::take(&mut *self.wire.tx.borrow_mut());
+        if reply.is_empty() || take(&self.drop_replies) {
+            return;
+        }
+        if take(&self.corrupt_replies) {
+            *reply.last_mut().unwrap() ^= 0xFF;
+        }
+        self.model.borrow_mut().queue(&reply);
+    }
+
+    fn read(&self) -> Option<u8> {
+        HalfDuplex::read(&self.port)
+    }
+}
+
+/// 建立主站 UART 和总线，`de` 为本测试独占的 GPIO3 引脚
+fn with_bus(de: u8, test: impl FnOnce(&ModbusMaster<'_, Bus<'_>>, &Bus<'_>)) {
+    let model = sim::map(UART3_BASE, 0x100, UartModel::new());
+    let uart = Uart::new(UART3_BASE);
+    uart.init_with_config(&UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(115200)
+    })
+    .unwrap();
+
+    let wire = Wire::default();
+    let bus = Bus::new(&uart, model, &wire, de);
+    let master = ModbusMaster::new(&bus, CONFIG);
+    test(&master, &bus);
+}
+
+#[test]
+fn request_goes_out_as_one_frame_with_crc() {
+    with_bus(20, |master, bus| {
+        let mut regs = [0u16; 1];
+        master.read_holding_registers(SLAVE_ADDR, 0, &mut regs).unwrap();
+        assert_eq!(
+            bus.requests.borrow()[0],
+            [SLAVE_ADDR, 0x03, 0x00, 0x00, 0x00, 0x01, 0x86, 0x9A]
+        );
+        assert!(bus.gpio.borrow().is_output(bus.de));
+    });
+}
+
+#[test]
+fn registers_round_trip() {
+    with_bus(21, |master, bus| {
+        master.write_multiple_registers(SLAVE_ADDR, 2, &[0x1111, 0x2222, 0x3333]).unwrap();
+        master.write_single_register(SLAVE_ADDR, 0, 0xBEEF).unwrap();
+
+        let mut regs = [0u16; 5];
+        master.read_holding_registers(SLAVE_ADDR, 0, &mut regs).unwrap();
+        assert_eq!(regs, [0xBEEF, 0, 0x1111, 0x2222, 0x3333]);
+        assert_eq!(bus.slave.borrow_mut().data().holding[4], 0x3333);
+        assert_eq!(bus.request_count(), 3);
+    });
+}
+
+#[test]
+fn coils_round_trip() {
+    with_bus(22, |master, _bus| {
+        master.write_single_coil(SLAVE_ADDR, 3, true).unwrap();
+        master.write_single_coil(SLAVE_ADDR, 9, true).unwrap();
+
+        let mut coils = [false; 10];
+        master.read_coils(SLAVE_ADDR, 0, &mut coils).unwrap();
+        let expected: Vec<bool> = (0..10).map(|i| i == 3 || i == 9).collect();
+        assert_eq!(coils.to_vec(), expected);
+    });
+}
+
+#[test]
+fn exception_replies_are_not_retried() {
+    with_bus(23, |master, bus| {
+        let mut regs = [0u16; 4];
+        assert_eq!(
+            master.read_holding_registers(SLAVE_ADDR, 6, &mut regs),
+            Err(ModbusError::Exception(ExceptionCode::IllegalDataAddress))
+        );
+        assert_eq!(
+            master.read_input_registers(SLAVE_ADDR, 0, &mut regs),
+            Err(ModbusError::Exception(ExceptionCode::IllegalFunction))
+        );
+        assert_eq!(
+            master.write_single_register(SLAVE_ADDR, 8, 1),
+            Err(ModbusError::Exception(ExceptionCode::IllegalDataAddress))
+        );
+        assert_eq!(bus.request_count(), 3);
+    });
+}
+
+#[test]
+fn lost_reply_is_retried() {
+    with_bus(24, |master, bus| {
+        bus.drop_replies.set(1);
+        master.write_single_register(SLAVE_ADDR, 1, 42).unwrap();
+        assert_eq!(bus.request_count(), 2);
+
+        // 从站执行了两次同样的写入，结果不变
+        assert_eq!(bus.slave.borrow_mut().data().holding[1], 42);
+    });
+}
+
+#[test]
+fn corrupted_reply_is_retried_until_retries_run_out() {
+    with_bus(25, |master, bus| {
+        let mut regs = [0u16; 2];
+        bus.corrupt_replies.set(1);
+        master.read_holding_registers(SLAVE_ADDR, 0, &mut regs).unwrap();
+        assert_eq!(bus.request_count(), 2);
+
+        bus.corrupt_replies.set(u32::MAX);
+        assert_eq!(
+            master.read_holding_registers(SLAVE_ADDR, 0, &mut regs),
+            Err(ModbusError::CrcMismatch)
+        );
+        assert_eq!(bus.request_count(), 2 + 1 + CONFIG.retries as usize);
+    });
+}
+
+#[test]
+fn corrupted_request_is_dropped_by_slave() {
+    with_bus(26, |master, bus| {
+        bus.corrupt_requests.set(1);
+        master.write_single_coil(SLAVE_ADDR, 0, true).unwrap();
+
+        assert_eq!(*bus.slave_errors.borrow(), [ModbusError::CrcMismatch]);
+        assert_eq!(bus.request_count(), 2);
+        assert!(bus.slave.borrow_mut().data().coils[0]);
+    });
+}
+
+#[test]
+fn other_slave_address_times_out() {
+    with_bus(27, |master, bus| {
+        let mut regs = [0u16; 1];
+        assert_eq!(
+            master.read_holding_registers(SLAVE_ADDR + 1, 0, &mut regs),
+            Err(ModbusError::Timeout)
+        );
+        assert_eq!(bus.request_count(), 1 + CONFIG.retries as usize);
+        assert!(bus.slave_errors.borrow().is_empty());
+    });
+}
+
+#[test]
+fn broadcast_is_applied_without_reply() {
+    with_bus(28, |master, bus| {
+        master.write_single_register(0, 5, 0x0505).unwrap();
+        assert_eq!(bus.request_count(), 1);
+        assert_eq!(bus.slave.borrow_mut().data().holding[5], 0x0505);
+        assert!(bus.model.borrow().transmitted().is_empty());
+    });
+}
@@ -0,0 +1,20 @@
+//! WhitcloudOS-1 运动控制辅助库
+//!
+//! 在 GPIO 驱动之上提供常用的电机控制功能，避免每个机器人项目
+//! 都在裸 GPIO 上重新实现一遍。
+//!
+//! # 功能
+//! - [`servo`] - 50Hz RC 舵机脉冲输出 (基于软件 PWM)
+//! - [`stepper`] - 步进电机驱动 (STEP/DIR 接口, 梯形加减速, 位置跟踪)
+//!
+//! # 注意
+//! 两者都是节拍驱动的：需要在定时器中断中周期性调用 `tick()`。
+//! 硬件 PWM 驱动完成后，舵机可以直接改用硬件 PWM 输出。
+
+#![no_std]
+
+pub mod servo;
+pub mod stepper;
+
+pub use servo::Servo;
+pub use stepper::{Stepper, StepperConfig};
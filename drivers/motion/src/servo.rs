@@ -0,0 +1,90 @@
+//! RC 舵机控制
+//!
+//! 标准 RC 舵机使用 50Hz (20ms 周期) 的脉冲控制角度：
+//! - 1000µs: 最小角度
+//! - 1500µs: 中位
+//! - 2000µs: 最大角度
+//!
+//! 不同型号的舵机脉宽范围不同，可通过 [`Servo::with_range`] 调整。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use gpio::soft_pwm::SoftPwm;
+//! use motion::Servo;
+//!
+//! // 50kHz 节拍 → 20µs 分辨率
+//! let mut pwm: SoftPwm<2> = SoftPwm::new(50_000, motion::servo::SERVO_HZ).unwrap();
+//! let ch = pwm.attach(GpioPin::new(GpioBank::Gpio1, 4)).unwrap();
+//!
+//! let servo = Servo::new(ch);
+//! servo.set_angle(&mut pwm, 90).unwrap();
+//! pwm.commit();
+//! ```
+
+use gpio::soft_pwm::{PwmError, SoftPwm};
+
+/// 舵机 PWM 频率
+pub const SERVO_HZ: u32 = 50;
+
+/// 舵机 PWM 周期 (微秒)
+pub const SERVO_PERIOD_US: u32 = 1_000_000 / SERVO_HZ;
+
+/// RC 舵机
+///
+/// 只记录软件 PWM 通道号和脉宽范围，`SoftPwm` 由调用者持有，
+/// 这样多个舵机可以共用一个控制器并同步更新。
+#[derive(Debug, Clone, Copy)]
+pub struct Servo {
+    channel: usize,
+    min_us: u32,
+    max_us: u32,
+    max_angle: u16,
+}
+
+impl Servo {
+    /// 创建标准舵机 (1000-2000µs 对应 0-180°)
+    ///
+    /// # 参数
+    /// - `channel`: `SoftPwm::attach` 返回的通道号，控制器频率必须为 [`SERVO_HZ`]
+    pub const fn new(channel: usize) -> Self {
+        Self::with_range(channel, 1000, 2000, 180)
+    }
+
+    /// 创建自定义脉宽范围的舵机
+    ///
+    /// # 参数
+    /// - `min_us`: 0° 对应的脉宽
+    /// - `max_us`: `max_angle` 对应的脉宽
+    /// - `max_angle`: 最大角度
+    ///
+    /// # 注意
+    /// `max_angle` 为 0 或 `min_us > max_us` 时 panic；在 const 上下文中调用时为编译错误
+    pub const fn with_range(channel: usize, min_us: u32, max_us: u32, max_angle: u16) -> Self {
+        assert!(max_angle > 0, "servo max_angle must be non-zero");
+        assert!(min_us <= max_us, "servo min_us must not exceed max_us");
+        Self { channel, min_us, max_us, max_angle }
+    }
+
+    /// 设置脉宽 (微秒)
+    ///
+    /// 超出舵机范围的脉宽会被限制到范围内，避免舵机顶死
+    pub fn set_pulse_us<const N: usize>(&self, pwm: &mut SoftPwm<N>, us: u32) -> Result<(), PwmError> {
+        let us = us.clamp(self.min_us, self.max_us);
+        let ticks = us * pwm.resolution() / SERVO_PERIOD_US;
+        pwm.set_duty_ticks(self.channel, ticks)
+    }
+
+    /// 设置角度 (度)
+    pub fn set_angle<const N: usize>(&self, pwm: &mut SoftPwm<N>, angle: u16) -> Result<(), PwmError> {
+        let angle = angle.min(self.max_angle) as u32;
+        let span = self.max_us - self.min_us;
+        let us = self.min_us + span * angle / self.max_angle as u32;
+        self.set_pulse_us(pwm, us)
+    }
+
+    /// 停止输出脉冲，舵机失去保持力矩
+    pub fn release<const N: usize>(&self, pwm: &mut SoftPwm<N>) -> Result<(), PwmError> {
+        pwm.set_duty_ticks(self.channel, 0)
+    }
+}
@@ -0,0 +1,215 @@
+//! 步进电机驱动
+//!
+//! 适用于 STEP/DIR 接口的步进驱动器 (A4988、DRV8825、TMC2209 等)。
+//!
+//! # 工作方式
+//! 在定时器中断中以 `tick_hz` 频率调用 [`Stepper::tick`]：
+//! - 每个节拍按梯形速度曲线更新速度 (匀加速 → 匀速 → 匀减速)
+//! - 速度积分达到一步时输出 STEP 脉冲，脉冲宽度为一个节拍
+//! - 剩余步数小于当前速度下的刹车距离 `v² / 2a` 时开始减速
+//!
+//! 由于 STEP 脉冲需要一个节拍拉高、一个节拍拉低，
+//! 最高步进速率为 `tick_hz / 2`。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use motion::{Stepper, StepperConfig};
+//!
+//! let config = StepperConfig {
+//!     tick_hz: 20_000,
+//!     max_speed: 4_000,
+//!     acceleration: 8_000,
+//!     invert_dir: false,
+//! };
+//! let mut stepper = Stepper::new(
+//!     GpioPin::new(GpioBank::Gpio1, 2),
+//!     GpioPin::new(GpioBank::Gpio1, 3),
+//!     config,
+//! )
+//! .unwrap();
+//!
+//! stepper.move_to(3200);
+//! // 在 20kHz 定时器中断中:
+//! stepper.tick();
+//! ```
+
+use gpio::claim::{self, PinClaim, PinConflict};
+use gpio::{GpioDirection, GpioLevel, GpioPin};
+
+/// 步进电机参数
+#[derive(Debug, Clone, Copy)]
+pub struct StepperConfig {
+    /// 调用 `tick()` 的频率 (Hz)
+    pub tick_hz: u32,
+    /// 最高速度 (步/秒)，不超过 `tick_hz / 2`
+    pub max_speed: u32,
+    /// 加速度 (步/秒²)
+    pub acceleration: u32,
+    /// 反转 DIR 引脚极性
+    pub invert_dir: bool,
+}
+
+/// 步进电机
+pub struct Stepper {
+    step: GpioPin,
+    dir: GpioPin,
+    config: StepperConfig,
+    /// 当前位置 (步)
+    position: i32,
+    /// 目标位置 (步)
+    target: i32,
+    /// 当前速度 (步/秒, 非负)
+    speed: f32,
+    /// 当前运动方向 (+1 / -1)
+    direction: i32,
+    /// 步进相位累加器
+    phase: f32,
+    /// STEP 引脚当前是否为高电平
+    pulse_high: bool,
+    _claims: [PinClaim; 2],
+}
+
+impl Stepper {
+    /// 创建步进电机实例
+    ///
+    /// STEP/DIR 引脚会被设置为输出模式并拉低
+    ///
+    /// # 返回值
+    /// - `Ok(Stepper)`: 两个引脚已登记 (使用者 `stepper`)，实例丢弃时释放
+    /// - `Err(PinConflict)`: 引脚已被占用，或 STEP/DIR 是同一个引脚；引脚配置不变
+    pub fn new(step: GpioPin, dir: GpioPin, config: StepperConfig) -> Result<Self, PinConflict> {
+        let claims = [claim::claim_pin(&step, "stepper")?, claim::claim_pin(&dir, "stepper")?];
+        step.set_direction(GpioDirection::Output);
+        step.set_level(GpioLevel::Low);
+        dir.set_direction(GpioDirection::Output);
+
+        let mut config = config;
+        config.max_speed = config.max_speed.min(config.tick_hz / 2);
+
+        let mut stepper = Self {
+            step,
+            dir,
+            config,
+            position: 0,
+            target: 0,
+            speed: 0.0,
+            direction: 1,
+            phase: 0.0,
+            pulse_high: false,
+            _claims: claims,
+        };
+        stepper.apply_direction(1);
+        Ok(stepper)
+    }
+
+    /// 当前位置 (步)
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// 目标位置 (步)
+    pub fn target(&self) -> i32 {
+        self.target
+    }
+
+    /// 当前速度 (步/秒)，带方向符号
+    pub fn speed(&self) -> f32 {
+        self.speed * self.direction as f32
+    }
+
+    /// 是否仍在运动
+    pub fn is_moving(&self) -> bool {
+        self.position != self.target || self.speed > 0.0
+    }
+
+    /// 重新设定当前位置 (例如回零后设为 0)，电机静止时调用
+    pub fn set_position(&mut self, position: i32) {
+        self.position = position;
+        self.target = position;
+    }
+
+    /// 移动到绝对位置
+    pub fn move_to(&mut self, target: i32) {
+        self.target = target;
+    }
+
+    /// 相对当前目标移动
+    pub fn move_by(&mut self, delta: i32) {
+        self.target = self.target.saturating_add(delta);
+    }
+
+    /// 按加速度减速停止
+    pub fn stop(&mut self) {
+        let steps = self.stopping_distance() as i32 + 1;
+        self.target = self.position + self.direction * steps;
+    }
+
+    /// 立即停止 (不减速，可能丢步)
+    pub fn halt(&mut self) {
+        self.target = self.position;
+        self.speed = 0.0;
+        self.phase = 0.0;
+    }
+
+    /// 当前速度下的刹车距离 (步)
+    fn stopping_distance(&self) -> f32 {
+        self.speed * self.speed / (2.0 * self.config.acceleration as f32)
+    }
+
+    fn apply_direction(&mut self, direction: i32) {
+        self.direction = direction;
+        let forward = (direction > 0) != self.config.invert_dir;
+        self.dir.set_level(if forward { GpioLevel::High } else { GpioLevel::Low });
+    }
+
+    /// 定时器节拍处理，应在定时器中断中以 `tick_hz` 频率调用
+    pub fn tick(&mut self) {
+        if self.pulse_high {
+            self.step.set_level(GpioLevel::Low);
+            self.pulse_high = false;
+            return;
+        }
+
+        let remaining = self.target - self.position;
+        if self.speed == 0.0 {
+            if remaining == 0 {
+                return;
+            }
+            // 静止时才允许换向
+            self.apply_direction(remaining.signum());
+        }
+
+        let dt = 1.0 / self.config.tick_hz as f32;
+        let dv = self.config.acceleration as f32 * dt;
+        let max_speed = self.config.max_speed as f32;
+
+        if remaining.signum() != self.direction {
+            // 目标在反方向 (或已到达但仍有速度)，先减速到 0
+            self.speed = (self.speed - dv).max(0.0);
+        } else if remaining.unsigned_abs() as f32 <= self.stopping_distance() {
+            // 保留最低速度，避免在目标前停住
+            self.speed = (self.speed - dv).max(dv);
+        } else {
+            self.speed = (self.speed + dv).min(max_speed);
+        }
+
+        if self.speed == 0.0 {
+            self.phase = 0.0;
+            return;
+        }
+
+        self.phase += self.speed * dt;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.step.set_level(GpioLevel::High);
+            self.pulse_high = true;
+            self.position += self.direction;
+
+            if self.position == self.target && self.speed <= dv * 2.0 {
+                self.speed = 0.0;
+                self.phase = 0.0;
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! 基于主机端 GPIO 寄存器模型的运动控制测试
+//!
+//! 运行: `cargo test -p motion --features sim`
+
+#![cfg(feature = "sim")]
+
+use gpio::{GpioBank, GpioPin, GPIO1_BASE};
+use mmio::sim::{self, GpioModel, Handle};
+use motion::{Stepper, StepperConfig};
+
+const CONFIG: StepperConfig = StepperConfig {
+    tick_hz: 10_000,
+    max_speed: 1_000,
+    acceleration: 20_000,
+    invert_dir: false,
+};
+
+#[test]
+fn stepper_claims_step_and_dir_pins() {
+    let _model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let stepper = Stepper::new(
+        GpioPin::new(GpioBank::Gpio1, 10),
+        GpioPin::new(GpioBank::Gpio1, 11),
+        CONFIG,
+    )
+    .unwrap();
+    assert_eq!(gpio::claim::owner(GpioBank::Gpio1, 10), Some("stepper"));
+    assert_eq!(gpio::claim::owner(GpioBank::Gpio1, 11), Some("stepper"));
+
+    let Err(conflict) = Stepper::new(
+        GpioPin::new(GpioBank::Gpio1, 12),
+        GpioPin::new(GpioBank::Gpio1, 11),
+        CONFIG,
+    ) else {
+        panic!("DIR pin claimed twice");
+    };
+    assert_eq!(conflict.pin, 11);
+    // 失败的构造释放已经登记的 STEP 引脚
+    assert_eq!(gpio::claim::owner(GpioBank::Gpio1, 12), None);
+
+    drop(stepper);
+    assert_eq!(gpio::claim::owner(GpioBank::Gpio1, 10), None);
+}
+
+#[test]
+fn stepper_rejects_shared_step_and_dir_pin() {
+    let _model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let pin = || GpioPin::new(GpioBank::Gpio1, 13);
+    assert!(Stepper::new(pin(), pin(), CONFIG).is_err());
+}
+
+/// 在 GPIO 模型上观察 STEP/DIR 引脚
+struct Probe {
+    model: Handle<GpioModel>,
+    step: u8,
+    dir: u8,
+    /// 上一节拍 STEP 电平
+    last: bool,
+    /// 自上一个 STEP 上升沿以来的节拍数
+    since_edge: u32,
+    /// 相邻上升沿的最小节拍间隔
+    min_interval: u32,
+    /// 每个上升沿时的 DIR 电平 (高为正向)
+    edges: Vec<bool>,
+}
+
+impl Probe {
+    fn new(model: Handle<GpioModel>, step: u8, dir: u8) -> Self {
+        Self {
+            model,
+            step,
+            dir,
+            last: false,
+            since_edge: 0,
+            min_interval: u32::MAX,
+            edges: Vec::new(),
+        }
+    }
+
+    fn tick(&mut self, stepper: &mut Stepper) {
+        stepper.tick();
+        let model = self.model.borrow();
+        let level = model.level(self.step);
+        self.since_edge += 1;
+        if level {
+            assert!(!self.last, "STEP held high for more than one tick");
+            if !self.edges.is_empty() {
+                self.min_interval = self.min_interval.min(self.since_edge);
+            }
+            self.edges.push(model.level(self.dir));
+            self.since_edge = 0;
+        }
+        self.last = level;
+    }
+
+    /// 运行到停止，最多 `max_ticks` 个节拍
+    fn run(&mut self, stepper: &mut Stepper, max_ticks: u32) {
+        for _ in 0..max_ticks {
+            if !stepper.is_moving() && !self.last {
+                return;
+            }
+            self.tick(stepper);
+        }
+        panic!("stepper still moving after {max_ticks} ticks");
+    }
+
+    fn count(&self, forward: bool) -> usize {
+        self.edges.iter().filter(|&&dir| dir == forward).count()
+    }
+}
+
+fn stepper_on(step: u8, dir: u8, config: StepperConfig) -> (Stepper, Probe) {
+    let model = sim::map(GPIO1_BASE, 0x100, GpioModel::new());
+    let stepper = Stepper::new(
+        GpioPin::new(GpioBank::Gpio1, step),
+        GpioPin::new(GpioBank::Gpio1, dir),
+        config,
+    )
+    .unwrap();
+    (stepper, Probe::new(model, step, dir))
+}
+
+#[test]
+fn tick_emits_one_pulse_per_step() {
+    let (mut stepper, mut probe) = stepper_on(14, 15, CONFIG);
+    stepper.move_to(200);
+    probe.run(&mut stepper, 10_000);
+
+    assert_eq!(probe.count(true), 200);
+    assert_eq!(probe.count(false), 0);
+    assert_eq!(stepper.position(), 200);
+    assert_eq!(stepper.speed(), 0.0);
+    assert!(!probe.model.borrow().level(14));
+    // 最高 1000 步/秒，10kHz 节拍下相邻脉冲至少间隔 10 个节拍
+    assert!(probe.min_interval >= CONFIG.tick_hz / CONFIG.max_speed);
+
+    // 已到达目标时 tick 不再输出脉冲
+    for _ in 0..100 {
+        probe.tick(&mut stepper);
+    }
+    assert_eq!(probe.edges.len(), 200);
+}
+
+#[test]
+fn tick_drives_dir_for_negative_moves() {
+    let config = StepperConfig {
+        invert_dir: true,
+        ..CONFIG
+    };
+    let (mut stepper, mut probe) = stepper_on(16, 17, config);
+    stepper.move_to(-50);
+    probe.run(&mut stepper, 10_000);
+
+    // DIR 极性反转：反向运动时 DIR 为高
+    assert_eq!(probe.count(true), 50);
+    assert_eq!(probe.count(false), 0);
+    assert_eq!(stepper.position(), -50);
+}
+
+#[test]
+fn target_flip_mid_move_stops_before_reversing() {
+    let (mut stepper, mut probe) = stepper_on(18, 19, CONFIG);
+    stepper.move_to(1_000);
+    while stepper.speed() < CONFIG.max_speed as f32 {
+        probe.tick(&mut stepper);
+    }
+    let flipped_at = stepper.position();
+    assert!(flipped_at > 0 && flipped_at < 1_000);
+
+    stepper.move_to(0);
+    let mut peak = flipped_at;
+    let mut speed = stepper.speed();
+    while stepper.is_moving() || probe.last {
+        probe.tick(&mut stepper);
+        // 速度经过 0 才变号
+        assert!(speed * stepper.speed() >= 0.0, "speed jumped from {speed} to {}", stepper.speed());
+        speed = stepper.speed();
+        peak = peak.max(stepper.position());
+        assert!(probe.edges.len() < 10_000, "stepper did not settle");
+    }
+
+    // 先减速冲过翻转点 (刹车距离 v²/2a = 25 步)，停下后才换向
+    assert!(peak > flipped_at && peak <= flipped_at + 26, "peak {peak}, flipped at {flipped_at}");
+    let reversal = probe.edges.iter().position(|&forward| !forward).unwrap();
+    assert!(probe.edges[..reversal].iter().all(|&forward| forward));
+    assert!(probe.edges[reversal..].iter().all(|&forward| !forward));
+    assert_eq!(probe.count(true), peak as usize);
+    assert_eq!(probe.count(false), peak as usize);
+    assert_eq!(stepper.position(), 0);
+}
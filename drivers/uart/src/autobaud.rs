@@ -0,0 +1,223 @@
+//! 波特率自动检测
+//!
+//! 用于恢复控制台等不知道主机终端波特率的场合，主机发送约定字符 (通常是回车或 `'U'`)。
+//! 有两种方式：
+//!
+//! - [`Uart::autobaud`] (`autobaud` feature)：通过 GPIO 采样 RX 线路，用通用定时器
+//!   测量约定字符的起始位和数据位边沿，按测得的位宽重新设置分频器。一个字符即可完成，
+//!   不受候选列表限制
+//! - [`Uart::probe_baud`]：依次用候选波特率初始化 UART，收到无线路错误且内容正确的
+//!   字符即认为匹配。不需要 GPIO 和定时器分辨率，但每个候选波特率都要等主机再发一次
+//!
+//! # 实现说明
+//! 边沿测量按 8 位数据位取约定字符的位模式，从起始位下降沿计时到数据位中最后一个
+//! 电平跳变：`'U'` (0x55) 每一位都跳变，跨度 8 位；回车 (0x0D) 最后一个跳变在
+//! 第 5 位。跨度越长，采样间隔带来的误差越小。各个边沿与测得位宽推算的位置相差
+//! 超过半位时 (主机发的不是约定字符，或采样太慢漏掉了边沿) 丢弃这次测量，等待下一个字符。
+//!
+//! 仓库中还没有 pinctrl/IOMUX 驱动，RX 焊盘的复用由调用方负责：
+//! 需要保证通过传入的 GPIO 引脚能读到 RX 线路电平。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use uart::{Uart, UartConfig, UART2_BASE};
+//!
+//! let uart = Uart::new(UART2_BASE);
+//! // 调试串口 UART2_RX_M0 (GPIO0_B6) 已切换为 GPIO 功能
+//! let rx = GpioPin::new(GpioBank::Gpio0, 14);
+//! uart.puts("press <Enter>\n");
+//! let baud = loop {
+//!     // 每次最多等待 10 秒
+//!     if let Ok(baud) = uart.autobaud(&UartConfig::default(), &rx, b'\r', 10_000_000) {
+//!         break baud;
+//!     }
+//! };
+//! ```
+
+#[cfg(feature = "autobaud")]
+use gpio::claim::{self, PinConflict};
+#[cfg(feature = "autobaud")]
+use gpio::{GpioDirection, GpioLevel, GpioPin};
+
+#[cfg(feature = "autobaud")]
+use crate::UartError;
+use crate::{RxError, Uart, UartConfig};
+
+/// 常用波特率，按 RK3588 上的使用频率排列
+pub const COMMON_BAUD_RATES: &[u32] = &[
+    1_500_000, 115_200, 921_600, 460_800, 230_400, 57_600, 38_400, 19_200, 9_600,
+];
+
+/// 测得的波特率与 [`COMMON_BAUD_RATES`] 相差在 1/25 (4%) 以内时取标准值
+///
+/// 8N1 帧的采样点在一个字符内累积的误差需要小于半位，4% 在两端合计仍有余量
+#[cfg(feature = "autobaud")]
+pub const SNAP_TOLERANCE: u32 = 25;
+
+/// 边沿测量失败的原因
+#[cfg(feature = "autobaud")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutobaudError {
+    /// 通用定时器不可用 (固件未设置 CNTFRQ_EL0)，无法测量
+    NoTimer,
+    /// RX 采样引脚已被其他功能占用
+    PinConflict(PinConflict),
+    /// 超时前没有测到与约定字符相符的一帧
+    Timeout,
+    /// 约定字符没有可测量的数据位跳变 (0x00)
+    NoEdges,
+    /// 按测得的波特率重新初始化 UART 失败
+    Uart(UartError),
+}
+
+/// 把接近标准值的测量结果取整到 [`COMMON_BAUD_RATES`]
+#[cfg(feature = "autobaud")]
+fn snap(measured: u32) -> u32 {
+    COMMON_BAUD_RATES
+        .iter()
+        .copied()
+        .find(|&baud| measured.abs_diff(baud) <= baud / SNAP_TOLERANCE)
+        .unwrap_or(measured)
+}
+
+/// 约定字符在起始位之后的电平跳变：(距起始位下降沿的位数, 跳变后的电平)
+#[cfg(feature = "autobaud")]
+fn transitions(expected: u8) -> ([(u8, GpioLevel); 8], usize) {
+    let mut edges = [(0, GpioLevel::Low); 8];
+    let mut count = 0;
+    let mut level = GpioLevel::Low;
+    for bit in 0..8u8 {
+        let next = if expected >> bit & 1 != 0 { GpioLevel::High } else { GpioLevel::Low };
+        if next != level {
+            edges[count] = (bit + 1, next);
+            count += 1;
+            level = next;
+        }
+    }
+    (edges, count)
+}
+
+/// 等待 `rx` 变为 `level`，返回采样到该电平时的定时器计数
+#[cfg(feature = "autobaud")]
+fn wait_level(rx: &GpioPin, level: GpioLevel, timeout: &mut timer::Timeout) -> Result<u64, AutobaudError> {
+    loop {
+        if rx.get_level() == level {
+            return timer::ticks().ok_or(AutobaudError::NoTimer);
+        }
+        if timeout.expired() {
+            return Err(AutobaudError::Timeout);
+        }
+    }
+}
+
+impl Uart {
+    /// 测量约定字符的位宽，自动设置波特率
+    ///
+    /// 等待 RX 线路空闲 (高电平) 后的起始位下降沿，记录约定字符每个数据位跳变的
+    /// 定时器计数，按最后一个跳变的位置算出位宽。测量结果接近 [`COMMON_BAUD_RATES`]
+    /// 中的标准值时取标准值。测得后等这一帧结束，再用新的波特率初始化 UART，
+    /// 并清除用旧波特率接收这一帧产生的线路错误。
+    ///
+    /// # 参数
+    /// - `config`: 除波特率以外的线路配置
+    /// - `rx`: 能读到 RX 线路电平的 GPIO 引脚，测量期间登记为 `autobaud` 占用并设为输入
+    /// - `expected`: 主机发送的约定字符 (`b'U'` 精度最高，`b'\r'` 便于用户按回车)
+    /// - `timeout_us`: 最长等待时间 (微秒)，应覆盖主机按键的间隔
+    ///
+    /// # 返回值
+    /// - `Ok(baud)`: 检测到的波特率，UART 已切换到该波特率
+    /// - `Err(AutobaudError::Timeout)`: 超时，可再次调用重试；UART 配置不变
+    /// - `Err(_)`: 见 [`AutobaudError`]
+    ///
+    /// # 注意
+    /// - 采样间隔是一次 GPIO 寄存器读取加一次定时器读取 (约 100–200ns)，
+    ///   1.5M 波特率 (一位约 667ns) 下只有 `'U'` 这样跨度长的字符能得到可靠结果
+    /// - 测量时忙等，不要在中断上下文调用
+    #[cfg(feature = "autobaud")]
+    pub fn autobaud(&self, config: &UartConfig, rx: &GpioPin, expected: u8, timeout_us: u32) -> Result<u32, AutobaudError> {
+        let freq = timer::frequency().ok_or(AutobaudError::NoTimer)?;
+        let (edges, count) = transitions(expected);
+        let Some(&(span_bits, _)) = edges[..count].last() else {
+            return Err(AutobaudError::NoEdges);
+        };
+        let _claim = claim::claim_pin(rx, "autobaud").map_err(AutobaudError::PinConflict)?;
+        rx.set_direction(GpioDirection::Input);
+
+        let mut timeout = timer::Timeout::new(timeout_us as u64);
+        let (start, span) = loop {
+            // 线路空闲后的下降沿是起始位
+            wait_level(rx, GpioLevel::High, &mut timeout)?;
+            let start = wait_level(rx, GpioLevel::Low, &mut timeout)?;
+            let mut times = [0u64; 8];
+            for (time, &(_, level)) in times.iter_mut().zip(&edges[..count]) {
+                *time = wait_level(rx, level, &mut timeout)? - start;
+            }
+
+            let span = times[count - 1];
+            let bit_ticks = span / span_bits as u64;
+            // 每个跳变都应落在推算位置的半位以内
+            let consistent = bit_ticks > 0
+                && times[..count].iter().zip(&edges[..count]).all(|(&time, &(bits, _))| {
+                    time.abs_diff(bits as u64 * span / span_bits as u64) <= bit_ticks / 2
+                });
+            if consistent {
+                break (start, span);
+            }
+        };
+
+        let measured = freq * span_bits as u64 / span;
+        let baud = snap(measured.min(u32::MAX as u64) as u32);
+
+        // 等这一帧 (起始位 + 8 数据位 + 校验位 + 停止位) 结束，避免在接收中途改分频器
+        let frame_end = start + 11 * span / span_bits as u64;
+        while timer::ticks().is_some_and(|now| now < frame_end) {
+            core::hint::spin_loop();
+        }
+
+        self.init_with_config(&UartConfig { baud, ..*config }).map_err(AutobaudError::Uart)?;
+        self.take_errors();
+        Ok(baud)
+    }
+
+    /// 逐个试探候选波特率
+    ///
+    /// 对每个候选波特率重新初始化 UART，然后等待一个字节。
+    /// 等待时间用 ARM 通用定时器计时 ([`timer::Timeout`])，与 CPU 频率和波特率无关。
+    /// 检测成功后 UART 保持在匹配的波特率上，失败时保持在最后一个候选波特率上。
+    ///
+    /// # 参数
+    /// - `config`: 除波特率以外的线路配置
+    /// - `candidates`: 候选波特率
+    /// - `expected`: 主机发送的约定字符 (如 `b'\r'`)
+    /// - `timeout_us`: 每个候选波特率的最长等待时间 (微秒)，应覆盖主机按键的间隔
+    ///
+    /// # 返回值
+    /// - `Some(baud)`: 检测到的波特率
+    /// - `None`: 所有候选波特率都没有收到正确的字符，可再次调用重试
+    ///
+    /// # 注意
+    /// 波特率不匹配时通常会出现帧错误或收到错误的字节；回车 (0x0D) 和 `'U'` (0x55)
+    /// 的位模式在相邻波特率下很难被误判，适合作为约定字符。
+    pub fn probe_baud(&self, config: &UartConfig, candidates: &[u32], expected: u8, timeout_us: u32) -> Option<u32> {
+        for &baud in candidates {
+            // 控制器持续忙 (线路上一直有数据) 时跳过这个波特率
+            if self.init_with_config(&UartConfig { baud, ..*config }).is_err() {
+                continue;
+            }
+            self.take_errors();
+
+            let mut timeout = timer::Timeout::new(timeout_us as u64);
+            while !timeout.expired() {
+                match self.try_getc() {
+                    Ok(Some(byte)) if byte == expected => return Some(baud),
+                    // 收到错误字节或线路错误，换下一个波特率
+                    Ok(Some(_)) | Err(RxError::Parity | RxError::Framing | RxError::Break) => break,
+                    Ok(None) | Err(RxError::Overrun) => {}
+                }
+                core::hint::spin_loop();
+            }
+        }
+        None
+    }
+}
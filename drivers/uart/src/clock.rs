@@ -0,0 +1,166 @@
+//! UART 时钟源查询
+//!
+//! # 参考资料
+//! - RK3588 TRM Part1 Chapter 3 - CRU
+//! - Linux Kernel: drivers/clk/rockchip/clk-rk3588.c
+//!
+//! # RK3588 UART1-UART9 时钟树
+//! ```text
+//! GPLL/CPLL ──► clk_uartN_src (整数分频) ──┬──────────────────► ┐
+//!                                          └► clk_uartN_frac ──► ├─ MUX ─► sclk_uartN
+//!                                     xin24m ──────────────────► ┘
+//! ```
+//! 每个 UART 占用三个 CLKSEL 寄存器 (N = 1..9, k = 41 + 2 * (N - 1))：
+//! - `CLKSEL_CON(k)`: bit14 源选择 (0=GPLL, 1=CPLL)，bit[13:9] 分频系数
+//! - `CLKSEL_CON(k + 1)`: 小数分频，高 16 位分子、低 16 位分母
+//! - `CLKSEL_CON(k + 2)`: bit[1:0] 输出选择 (0=src, 1=frac, 2=xin24m)
+//!
+//! UART0 位于 PMU 电源域，由 PMU1CRU 提供时钟，这里不做解析。
+//!
+//! # 限制
+//! 只解析 UART 自己的分频与选择器，PLL 频率按 U-Boot/Linux 的默认配置
+//! (GPLL 1188MHz, CPLL 1500MHz) 计算。PLL 被改动或时钟树无法解析的板子，
+//! 应通过 [`UartClock::Fixed`] 直接指定时钟频率。
+
+use mmio::read_volatile;
+
+use crate::UartId;
+
+/// CRU 基址
+const CRU_BASE: usize = 0xFD7C0000;
+
+/// CLKSEL_CON 寄存器偏移
+const CRU_CLKSEL_CON0: usize = 0x300;
+
+/// 默认 PLL 频率
+const GPLL_HZ: u64 = 1_188_000_000;
+const CPLL_HZ: u64 = 1_500_000_000;
+
+/// 晶振频率
+pub const XIN24M_HZ: u32 = 24_000_000;
+
+/// 无法查询 CRU 时使用的默认 UART 时钟
+pub const DEFAULT_UART_CLOCK: u32 = XIN24M_HZ;
+
+/// UART 时钟来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UartClock {
+    /// 从 CRU 寄存器读取实际的 sclk_uart 频率，无法解析时使用 24MHz
+    Cru,
+    /// 固定频率 (Hz)，用于时钟树无法解析的板子
+    Fixed(u32),
+}
+
+/// 读取 CLKSEL_CON(n)
+fn clksel(n: usize) -> u32 {
+    unsafe { read_volatile((CRU_BASE + CRU_CLKSEL_CON0 + n * 4) as *const u32) }
+}
+
+/// 查询 UART1-UART9 的 sclk 频率
+///
+/// # 参数
+/// - `index`: UART 编号 (1-9)
+///
+/// # 返回值
+/// 时钟频率 (Hz)，编号不支持或寄存器值无效时返回 `None`
+pub fn uart_sclk_hz(index: u8) -> Option<u32> {
+    if !(1..=9).contains(&index) {
+        return None;
+    }
+    let k = 41 + 2 * (index as usize - 1);
+
+    let src_con = clksel(k);
+    let pll = if (src_con >> 14) & 1 == 0 { GPLL_HZ } else { CPLL_HZ };
+    let div = ((src_con >> 9) & 0x1F) as u64 + 1;
+    let src_hz = pll / div;
+
+    let rate = match clksel(k + 2) & 0x3 {
+        0 => src_hz,
+        1 => {
+            let frac = clksel(k + 1);
+            let num = (frac >> 16) as u64;
+            let den = (frac & 0xFFFF) as u64;
+            if num == 0 || den == 0 || num > den {
+                return None;
+            }
+            src_hz * num / den
+        }
+        2 => XIN24M_HZ as u64,
+        _ => return None,
+    };
+
+    u32::try_from(rate).ok()
+}
+
+impl UartClock {
+    /// 解析出 `base` 处 UART 的时钟频率
+    pub(crate) fn resolve(self, base: usize) -> u32 {
+        match self {
+            UartClock::Fixed(hz) => hz,
+            UartClock::Cru => UartId::from_base(base)
+                .and_then(|id| uart_sclk_hz(id as u8))
+                .unwrap_or(DEFAULT_UART_CLOCK),
+        }
+    }
+}
+
+/// 波特率分频器设置
+///
+/// 分频系数为 `integer + fraction / 2^fraction_bits`，波特率 = clock / (16 * 分频系数)。
+/// 整数分频在高波特率下误差很大 (24MHz 下 921600 只能选 2，误差 -18.6%)，
+/// 带小数分频寄存器 (DLF) 的 Designware UART 可以把误差降到 1% 以内。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divisor {
+    /// DLL/DLH 中的整数部分 (1-65535)
+    pub integer: u32,
+    /// DLF 中的小数部分
+    pub fraction: u32,
+    /// DLF 位宽，0 表示只用整数分频
+    pub fraction_bits: u32,
+}
+
+impl Divisor {
+    /// 计算最接近目标波特率的分频器设置
+    ///
+    /// # 参数
+    /// - `clock`: UART 时钟 (Hz)
+    /// - `baud`: 目标波特率
+    /// - `fraction_bits`: DLF 位宽，控制器没有 DLF 时为 0
+    ///
+    /// # 示例
+    /// ```
+    /// use uart::clock::Divisor;
+    ///
+    /// // 没有 DLF: 24MHz / (16 * 2) = 750000
+    /// let d = Divisor::new(24_000_000, 921_600, 0);
+    /// assert_eq!((d.integer, d.fraction), (2, 0));
+    ///
+    /// // 4 位 DLF: 24MHz / (16 * 1.625) = 923077 (+0.16%)
+    /// let d = Divisor::new(24_000_000, 921_600, 4);
+    /// assert_eq!((d.integer, d.fraction), (1, 10));
+    /// assert_eq!(d.baud(24_000_000), 923_076);
+    /// ```
+    pub const fn new(clock: u32, baud: u32, fraction_bits: u32) -> Self {
+        // 以 1/2^fraction_bits 为单位四舍五入，整体误差最小
+        let unit = 16 * baud as u64;
+        let mut scaled = (((clock as u64) << fraction_bits) + unit / 2) / unit;
+        let min = 1 << fraction_bits;
+        let max = (0x1_0000 << fraction_bits) - 1;
+        if scaled < min {
+            scaled = min;
+        } else if scaled > max {
+            scaled = max;
+        }
+        Self {
+            integer: (scaled >> fraction_bits) as u32,
+            fraction: (scaled & (min - 1)) as u32,
+            fraction_bits,
+        }
+    }
+
+    /// 这组设置在 `clock` 下的实际波特率
+    pub const fn baud(&self, clock: u32) -> u32 {
+        let scaled = ((self.integer as u64) << self.fraction_bits) | self.fraction as u64;
+        (((clock as u64) << self.fraction_bits) / (16 * scaled)) as u32
+    }
+}
@@ -0,0 +1,357 @@
+//! 全局控制台
+//!
+//! `print!`/`println!` 的输出目标。输出送到所有通过 [`Console::register`] 注册的输出端
+//! (调试 UART、第二个 UART、JTAG DCC、帧缓冲控制台、内存日志缓冲区等)。
+//! [`init_console`] 是注册调试 UART 的简便写法，内部同样走 [`Console::register`]。
+//!
+//! 控制台由自旋锁保护，持锁期间屏蔽本核 IRQ，保证：
+//! - 多个上下文 (普通代码与中断处理函数，以及后续的多核) 同时输出时不会交错或产生数据竞争
+//! - 中断处理函数不会在本核持锁时再次加锁而死锁
+//!
+//! 输入同样来自所有后端：[`Console::getc`] 按注册顺序轮询实现了
+//! [`ConsoleSink::getc`] 的输出端，Shell 可以从任何一个控制台接收命令。
+//!
+//! 注册第一个输出端之前，输出送到 [`Console::set_early`] 设置的早期输出端
+//! (通常是 [`EarlyCon`](crate::early::EarlyCon))，之后不再使用。
+//!
+//! 一次 `println!` 的内容 (含换行) 在同一次持锁中输出；需要连续输出多行而不被打断时，
+//! 可以直接持有 [`Console::lock`] 返回的 [`ConsoleGuard`]。
+//!
+//! # 注意
+//! - 自旋锁依赖原子指令，多核使用前需要开启 MMU 和数据缓存
+//! - 在已持有控制台锁的代码中 panic 会导致 panic 输出死锁，panic 处理应使用
+//!   [`console_uart_base`] 不加锁地轮询输出
+//!
+//! # 使用示例
+//! ```no_run
+//! use core::fmt::Write;
+//! use uart::{console, println, Uart, UART2_BASE};
+//!
+//! static DEBUG_UART: Uart = Uart::new(UART2_BASE);
+//! DEBUG_UART.init(1_500_000).unwrap();
+//! console().register(&DEBUG_UART).unwrap();
+//! println!("boot: {} cores", 8);
+//!
+//! let mut out = console().lock();
+//! writeln!(out, "line 1").unwrap();
+//! writeln!(out, "line 2").unwrap();
+//! drop(out);
+//!
+//! // 同时输出到 UART3
+//! static MIRROR: uart::Uart = uart::Uart::new(uart::UART3_BASE);
+//! MIRROR.init(115200).unwrap();
+//! console().register(&MIRROR).unwrap();
+//! ```
+
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spinlock::{IrqSpinLockGuard, SpinLock};
+
+use crate::{Uart, UartError, UartId};
+
+/// 最多可注册的输出端数量 (包括控制台 UART)
+pub const MAX_CONSOLE_SINKS: usize = 4;
+
+/// 输出端为 UART 时 [`ConsoleSink::flush`] 等待发送完成的上限 (微秒)
+const UART_SINK_FLUSH_TIMEOUT_US: u32 = 100_000;
+
+/// 控制台输出端
+///
+/// 实现者需要能在关中断、持有控制台锁的情况下输出，不能再次使用 `print!`
+pub trait ConsoleSink: Sync {
+    /// 输出字符串
+    fn write_str(&self, s: &str);
+
+    /// 读取一个输入字节 (非阻塞)
+    ///
+    /// 只能输出的后端 (帧缓冲、内存日志等) 使用默认实现
+    fn getc(&self) -> Option<u8> {
+        None
+    }
+
+    /// 等待已写入的内容真正输出 (例如复位前)
+    ///
+    /// 没有发送缓冲的后端使用默认实现
+    fn flush(&self) {}
+
+    /// 输出端是 UART 时返回控制器基址
+    ///
+    /// 用于 [`console_uart_base`]，其他后端使用默认实现
+    fn uart_base(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl ConsoleSink for Uart {
+    fn write_str(&self, s: &str) {
+        self.puts(s);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        Uart::getc(self)
+    }
+
+    fn flush(&self) {
+        // 线路卡住时放弃，调用方 (复位、关机) 不能因为控制台而挂起
+        let _ = Uart::flush(self, UART_SINK_FLUSH_TIMEOUT_US);
+    }
+
+    fn uart_base(&self) -> Option<usize> {
+        Some(self.base)
+    }
+}
+
+/// 注册输出端失败 (已达到 [`MAX_CONSOLE_SINKS`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SinksFull;
+
+/// 由自旋锁保护的控制台
+///
+/// 中断处理函数中也会输出，加锁时屏蔽本核 IRQ ([`SpinLock::lock_irqsave`])
+pub struct Console {
+    state: SpinLock<ConsoleState>,
+}
+
+struct ConsoleState {
+    /// 注册第一个输出端之前的输出端
+    early: Option<&'static dyn ConsoleSink>,
+    /// 已注册的输出端
+    sinks: [Option<&'static dyn ConsoleSink>; MAX_CONSOLE_SINKS],
+}
+
+static CONSOLE: Console = Console::new();
+
+/// 第一个 UART 输出端的基址，0 表示没有
+///
+/// 在注册/注销时更新，读取不需要控制台锁
+static CONSOLE_UART_BASE: AtomicUsize = AtomicUsize::new(0);
+
+/// 控制台 UART 的基址 (不加锁)
+///
+/// 返回按注册顺序第一个 [`ConsoleSink::uart_base`] 不为 `None` 的输出端的基址。
+/// 供 panic 等不能等待控制台锁的场合用 [`EarlyCon`](crate::early::EarlyCon)
+/// 轮询输出到同一个串口：
+/// ```no_run
+/// use uart::console::console_uart_base;
+/// use uart::early::{EarlyCon, DEFAULT_EARLYCON};
+///
+/// let base = console_uart_base().unwrap_or(DEFAULT_EARLYCON.base());
+/// EarlyCon::new(base).puts("fatal: out of memory\n");
+/// ```
+pub fn console_uart_base() -> Option<usize> {
+    match CONSOLE_UART_BASE.load(Ordering::Acquire) {
+        0 => None,
+        base => Some(base),
+    }
+}
+
+/// [`init_console`] 使用的 UART 实例，每个控制器一个
+///
+/// 注册为输出端需要 `'static` 引用，按 [`UartId`] 的顺序排列
+static CONSOLE_UARTS: [Uart; 10] = [
+    Uart::new(crate::UART0_BASE),
+    Uart::new(crate::UART1_BASE),
+    Uart::new(crate::UART2_BASE),
+    Uart::new(crate::UART3_BASE),
+    Uart::new(crate::UART4_BASE),
+    Uart::new(crate::UART5_BASE),
+    Uart::new(crate::UART6_BASE),
+    Uart::new(crate::UART7_BASE),
+    Uart::new(crate::UART8_BASE),
+    Uart::new(crate::UART9_BASE),
+];
+
+/// 全局控制台
+pub fn console() -> &'static Console {
+    &CONSOLE
+}
+
+/// 初始化 UART 并注册为全局控制台的输出端
+///
+/// 等价于用一个 `static Uart` 调用 [`Uart::init`] 和 [`Console::register`]，
+/// UART 实例来自内部的静态表，调用方不需要自己定义。
+///
+/// # 参数
+/// - `base`: UART 基址 (`UART0_BASE`..`UART9_BASE`)
+/// - `baudrate`: 波特率
+///
+/// # 返回值
+/// - `Ok(())`: 控制台切换到新 UART
+/// - `Err(UartError::UnknownBase)`: `base` 不是 UART 控制器
+/// - `Err(UartError::LcrBusy)`: UART 初始化失败，控制台保持原来的输出端不变
+/// - `Err(UartError::SinksFull)`: 输出端已满 ([`MAX_CONSOLE_SINKS`])
+///
+/// 可以重复调用以切换到其他 UART：之前由本函数注册的 UART 会被注销，
+/// 其他方式注册的输出端不受影响。
+pub fn init_console(base: usize, baudrate: u32) -> Result<(), UartError> {
+    let id = UartId::from_base(base).ok_or(UartError::UnknownBase)?;
+    let uart = &CONSOLE_UARTS[id as usize];
+    uart.init(baudrate)?;
+    for other in CONSOLE_UARTS.iter().filter(|u| !core::ptr::eq(*u, uart)) {
+        CONSOLE.unregister(other);
+    }
+    CONSOLE.register(uart).map_err(|SinksFull| UartError::SinksFull)
+}
+
+impl Console {
+    const fn new() -> Self {
+        Self {
+            state: SpinLock::new(ConsoleState {
+                early: None,
+                sinks: [None; MAX_CONSOLE_SINKS],
+            }),
+        }
+    }
+
+    /// 加锁 (屏蔽本核 IRQ 后自旋等待)
+    pub fn lock(&self) -> ConsoleGuard<'_> {
+        ConsoleGuard {
+            state: self.state.lock_irqsave(),
+        }
+    }
+
+    /// 从任一控制台读取一个输入字节 (非阻塞)，参见 [`ConsoleGuard::getc`]
+    pub fn getc(&self) -> Option<u8> {
+        self.lock().getc()
+    }
+
+    /// 等待所有输出端把已写入的内容输出完，参见 [`ConsoleSink::flush`]
+    pub fn flush(&self) {
+        self.lock().flush();
+    }
+
+    /// 控制台是否已初始化 (至少注册了一个输出端)
+    pub fn is_initialized(&self) -> bool {
+        self.lock().sinks().iter().any(Option::is_some)
+    }
+
+    /// 设置注册第一个输出端之前使用的输出端 (earlycon)
+    ///
+    /// 输出端应是已经由 BootROM/U-Boot 初始化的串口，例如
+    /// [`EarlyCon`](crate::early::EarlyCon)。已经注册了输出端时不会被使用。
+    pub fn set_early(&self, sink: &'static dyn ConsoleSink) {
+        *self.lock().early() = Some(sink);
+    }
+
+    /// 注册输出端，之后的输出同时送到该输出端
+    ///
+    /// 重复注册同一输出端视为成功
+    pub fn register(&self, sink: &'static dyn ConsoleSink) -> Result<(), SinksFull> {
+        let mut guard = self.lock();
+        let sinks = guard.sinks();
+        if sinks.iter().flatten().any(|s| core::ptr::addr_eq(*s, sink)) {
+            return Ok(());
+        }
+        let slot = sinks.iter_mut().find(|s| s.is_none()).ok_or(SinksFull)?;
+        *slot = Some(sink);
+        guard.update_uart_base();
+        Ok(())
+    }
+
+    /// 注销输出端
+    ///
+    /// # 返回值
+    /// 输出端之前已注册时返回 `true`
+    pub fn unregister(&self, sink: &'static dyn ConsoleSink) -> bool {
+        let mut guard = self.lock();
+        let Some(slot) = guard
+            .sinks()
+            .iter_mut()
+            .find(|slot| matches!(slot, Some(s) if core::ptr::addr_eq(*s, sink)))
+        else {
+            return false;
+        };
+        *slot = None;
+        guard.update_uart_base();
+        true
+    }
+}
+
+/// 控制台锁，释放时解锁并恢复 IRQ 状态
+///
+/// 写入的内容送到所有已注册的输出端 (没有时为早期输出端)，都没有时被丢弃
+pub struct ConsoleGuard<'a> {
+    state: IrqSpinLockGuard<'a, ConsoleState>,
+}
+
+impl ConsoleGuard<'_> {
+    /// 读取一个输入字节 (非阻塞)
+    ///
+    /// 按注册顺序读取各输出端，返回第一个读到的字节
+    pub fn getc(&mut self) -> Option<u8> {
+        self.sinks().iter().flatten().find_map(|sink| sink.getc())
+    }
+
+    /// 等待所有输出端把已写入的内容输出完
+    pub fn flush(&mut self) {
+        for sink in self.sinks().iter().flatten() {
+            sink.flush();
+        }
+    }
+
+    /// 重新计算 [`CONSOLE_UART_BASE`]，注册/注销后调用
+    fn update_uart_base(&mut self) {
+        let base = self
+            .sinks()
+            .iter()
+            .flatten()
+            .find_map(|sink| sink.uart_base())
+            .unwrap_or(0);
+        CONSOLE_UART_BASE.store(base, Ordering::Release);
+    }
+
+    fn early(&mut self) -> &mut Option<&'static dyn ConsoleSink> {
+        &mut self.state.early
+    }
+
+    fn sinks(&mut self) -> &mut [Option<&'static dyn ConsoleSink>; MAX_CONSOLE_SINKS] {
+        &mut self.state.sinks
+    }
+}
+
+impl fmt::Write for ConsoleGuard<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut written = false;
+        for sink in self.sinks().iter().flatten() {
+            sink.write_str(s);
+            written = true;
+        }
+        if !written {
+            if let Some(early) = *self.early() {
+                early.write_str(s);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 向控制台输出格式化内容
+///
+/// `print!`/`println!` 的实现，也可以直接作为日志输出函数:
+/// ```no_run
+/// klog::set_sink(uart::console::write_args);
+/// ```
+pub fn write_args(args: fmt::Arguments) {
+    use fmt::Write;
+    let _ = CONSOLE.lock().write_fmt(args);
+}
+
+/// print! 宏实现
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::console::write_args(format_args!($($arg)*))
+    };
+}
+
+/// println! 宏实现
+///
+/// 内容与换行在同一次持锁中输出
+#[macro_export]
+macro_rules! println {
+    () => ($crate::print!("\n"));
+    ($($arg:tt)*) => {
+        $crate::console::write_args(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
@@ -0,0 +1,131 @@
+//! 早期启动控制台
+//!
+//! 在 DRAM、.bss 清零和分配器就绪之前使用的最小串口输出。
+//!
+//! # 特点
+//! - 不使用任何全局可变状态，.data/.bss 尚未初始化时也能调用
+//! - 不重新配置波特率，沿用 BootROM/U-Boot 已经设置好的串口
+//!   (RK3588 默认调试串口 UART2, 1500000 8N1)
+//! - 只需要很少的栈空间
+//!
+//! [`EarlyCon`] 可以用 `const fn` 绑定到任意一个已经初始化的串口；
+//! `early_print!`/`early_println!` 使用绑定到 [`EARLY_CONSOLE_BASE`] 的 [`DEFAULT_EARLYCON`]。
+//!
+//! # 切换到正式控制台
+//! 1. .bss 清零之前：直接用 [`EarlyCon`] 或 `early_println!` 输出
+//! 2. .bss 清零之后：用 [`Console::set_early`](crate::console::Console::set_early)
+//!    把 [`EarlyCon`] 设为全局控制台的临时输出端，`println!` 和日志开始可用
+//! 3. CRU 时钟树就绪后：[`init_console`](crate::init_console) 按实际时钟重新配置串口
+//!    并注册为输出端，之后的输出只走已注册的输出端
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::early::EarlyCon;
+//! use uart::{console, init_console, println, UART2_BASE};
+//!
+//! static EARLYCON: EarlyCon = EarlyCon::new(UART2_BASE);
+//!
+//! // kmain 的第一行，MMU 还没有打开
+//! let _ = writeln!(EARLYCON, "kmain: el = {}", 2);
+//! uart::early_println!("early: same UART via the default earlycon");
+//!
+//! // .bss 已清零
+//! console().set_early(&EARLYCON);
+//! println!("mmu: enabling");
+//!
+//! // 时钟和分配器就绪
+//! init_console(UART2_BASE, 1_500_000).unwrap();
+//! println!("console ready");
+//! ```
+
+use core::fmt;
+
+use mmio::{read_volatile, write_volatile};
+
+use crate::{LSR_THRE, UART2_BASE, UART_LSR, UART_THR};
+
+/// 早期控制台使用的 UART 基址
+pub const EARLY_CONSOLE_BASE: usize = UART2_BASE;
+
+/// 绑定到已初始化串口的早期控制台
+///
+/// 只保存基址，不配置串口，可以放在只读静态变量中。
+/// 只走轮询发送路径，不读写 [`Uart`](crate::Uart) 的接收/发送缓冲区。
+pub struct EarlyCon {
+    base: usize,
+}
+
+impl EarlyCon {
+    pub const fn new(base: usize) -> Self {
+        Self { base }
+    }
+
+    /// UART 基址
+    pub const fn base(&self) -> usize {
+        self.base
+    }
+
+    /// 发送一个字节 (轮询等待 THR 空)
+    pub fn putc(&self, byte: u8) {
+        unsafe {
+            let lsr_addr = (self.base + UART_LSR) as *const u32;
+            while (read_volatile(lsr_addr) & LSR_THRE) == 0 {}
+            let thr_addr = (self.base + UART_THR) as *mut u32;
+            write_volatile(thr_addr, byte as u32);
+        }
+    }
+
+    /// 发送字符串 (`\n` 转换为 `\r\n`)
+    pub fn puts(&self, s: &str) {
+        for byte in s.bytes() {
+            if byte == b'\n' {
+                self.putc(b'\r');
+            }
+            self.putc(byte);
+        }
+    }
+
+    /// 格式化输出，使 `write!`/`writeln!` 可以直接用于共享引用和静态变量
+    pub fn write_fmt(&self, args: fmt::Arguments) -> fmt::Result {
+        fmt::Write::write_fmt(&mut &*self, args)
+    }
+}
+
+impl fmt::Write for &EarlyCon {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.puts(s);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "console")]
+impl crate::console::ConsoleSink for EarlyCon {
+    fn write_str(&self, s: &str) {
+        self.puts(s);
+    }
+
+    fn uart_base(&self) -> Option<usize> {
+        Some(self.base)
+    }
+}
+
+/// 绑定到 [`EARLY_CONSOLE_BASE`] 的早期控制台，`early_print!`/`early_println!` 的输出目标
+pub const DEFAULT_EARLYCON: EarlyCon = EarlyCon::new(EARLY_CONSOLE_BASE);
+
+/// 早期控制台格式化输出
+#[macro_export]
+macro_rules! early_print {
+    ($($arg:tt)*) => {{
+        let _ = $crate::early::DEFAULT_EARLYCON.write_fmt(format_args!($($arg)*));
+    }};
+}
+
+/// 早期控制台格式化输出 (带换行)
+#[macro_export]
+macro_rules! early_println {
+    () => ($crate::early_print!("\n"));
+    ($($arg:tt)*) => {{
+        $crate::early_print!($($arg)*);
+        $crate::early_print!("\n");
+    }};
+}
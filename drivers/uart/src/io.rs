@@ -0,0 +1,82 @@
+//! embedded-io 接口实现
+//!
+//! 让使用 `embedded_io` 读写流的通用 no_std 代码可以直接使用 [`Uart`]。
+//!
+//! - 二进制透传，不做 `\n` → `\r\n` 转换
+//! - `read`/`write` 至少传输一个字节后返回 (`buf` 为空时立即返回 0)
+//! - 线路错误不通过 `Error` 返回 (出错的字节被丢弃)，用 [`Uart::take_errors`] 查询
+//!
+//! # 使用示例
+//! ```no_run
+//! use embedded_io::{Read, Write};
+//! use uart::{Uart, UART3_BASE};
+//!
+//! let mut uart = Uart::new(UART3_BASE);
+//! uart.init(115200).unwrap();
+//! uart.write_all(b"\x02ping\x03").unwrap();
+//!
+//! let mut buf = [0u8; 16];
+//! // Uart 自身也有 read 方法 (中断接收缓冲区)，这里需要指明 trait
+//! let n = Read::read(&mut uart, &mut buf).unwrap();
+//! ```
+
+use core::convert::Infallible;
+use core::sync::atomic::Ordering;
+
+use embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
+use mmio::read_volatile;
+
+use crate::{Uart, UART_USR, LSR_DR, USR_TFNF};
+
+impl ErrorType for Uart {
+    type Error = Infallible;
+}
+
+impl Read for Uart {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.getc_blocking();
+        Ok(1 + self.read_bytes(&mut buf[1..]))
+    }
+}
+
+impl ReadReady for Uart {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let fifo_ready = self.read_lsr() & LSR_DR != 0;
+        Ok(!self.rx_buf.is_empty() || fifo_ready)
+    }
+}
+
+impl Write for Uart {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let count = self.write_bytes(buf);
+            if count > 0 {
+                return Ok(count);
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// 等待发送缓冲区和发送器全部清空
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        while self.tx_pending() > 0 || !self.is_tx_idle() {
+            core::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+impl WriteReady for Uart {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        if self.tx_buffered.load(Ordering::Acquire) {
+            return Ok(!self.tx_buf.is_full());
+        }
+        Ok(unsafe { read_volatile((self.base + UART_USR) as *const u32) & USR_TFNF != 0 })
+    }
+}
@@ -17,24 +17,173 @@
 //! use core::fmt::Write;
 //! 
 //! let mut uart = Uart::new(UART2_BASE);
-//! uart.init(115200);
+//! uart.init(115200).unwrap();
 //! writeln!(uart, "Hello, World!").unwrap();
 //! ```
 
 #![no_std]
 
 use core::fmt;
-use core::ptr::{read_volatile, write_volatile};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+
+use mmio::{read_volatile, write_volatile};
+use spinlock::SpinLock;
+
+pub mod autobaud;
+pub mod clock;
+#[cfg(feature = "console")]
+pub mod console;
+#[cfg(feature = "early")]
+pub mod early;
+#[cfg(feature = "embedded-io")]
+mod io;
+pub mod line;
+#[cfg(feature = "mux")]
+pub mod mux;
+pub mod pm;
+#[cfg(feature = "poll")]
+pub mod poll;
+#[cfg(feature = "regset")]
+pub mod regs;
+pub mod ring;
+#[cfg(feature = "rs485")]
+pub mod rs485;
+#[cfg(feature = "xmodem")]
+pub mod xmodem;
+pub mod xonxoff;
+
+pub use clock::UartClock;
+pub use pm::UartContext;
+#[cfg(feature = "console")]
+pub use console::{console, init_console};
+
+/// 故障注入：接收溢出，所有串口共用
+#[cfg(feature = "fault-inject")]
+static RX_OVERRUN_FAULT: fault::FaultPoint = fault::FaultPoint::new("uart.rx_overrun");
 
 /// UART 控制器基址
 /// 
-/// RK3588 有 10 个 UART 控制器 (UART0-UART9)
-/// 这里列出常用的几个
+/// RK3588 有 10 个 UART 控制器 (UART0-UART9)，也可以通过 [`UartId::base`] 获取
 pub const UART0_BASE: usize = 0xFD890000;  // BT/Debug
 pub const UART1_BASE: usize = 0xFEB40000;  // 通用
 pub const UART2_BASE: usize = 0xFEB50000;  // **调试串口 (推荐)**
 pub const UART3_BASE: usize = 0xFEB60000;  // 通用
 pub const UART4_BASE: usize = 0xFEB70000;  // 通用
+pub const UART5_BASE: usize = 0xFEB80000;  // 通用
+pub const UART6_BASE: usize = 0xFEB90000;  // 通用
+pub const UART7_BASE: usize = 0xFEBA0000;  // 通用
+pub const UART8_BASE: usize = 0xFEBB0000;  // 通用
+pub const UART9_BASE: usize = 0xFEBC0000;  // 通用
+
+/// UART 控制器编号
+/// 
+/// # 示例
+/// ```no_run
+/// use uart::{Uart, UartId};
+/// 
+/// let uart = Uart::from_id(UartId::Uart7);
+/// uart.init(115200).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UartId {
+    Uart0 = 0,
+    Uart1 = 1,
+    Uart2 = 2,
+    Uart3 = 3,
+    Uart4 = 4,
+    Uart5 = 5,
+    Uart6 = 6,
+    Uart7 = 7,
+    Uart8 = 8,
+    Uart9 = 9,
+}
+
+impl UartId {
+    /// 全部控制器，按编号排列
+    pub const ALL: [UartId; 10] = [
+        UartId::Uart0,
+        UartId::Uart1,
+        UartId::Uart2,
+        UartId::Uart3,
+        UartId::Uart4,
+        UartId::Uart5,
+        UartId::Uart6,
+        UartId::Uart7,
+        UartId::Uart8,
+        UartId::Uart9,
+    ];
+    
+    /// 由编号 (0-9) 得到控制器
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < Self::ALL.len() {
+            Some(Self::ALL[index])
+        } else {
+            None
+        }
+    }
+    
+    /// 由基址得到控制器
+    pub fn from_base(base: usize) -> Option<Self> {
+        Self::ALL.into_iter().find(|id| id.base() == base)
+    }
+    
+    /// 控制器基址
+    pub const fn base(self) -> usize {
+        match self {
+            UartId::Uart0 => UART0_BASE,
+            UartId::Uart1 => UART1_BASE,
+            UartId::Uart2 => UART2_BASE,
+            UartId::Uart3 => UART3_BASE,
+            UartId::Uart4 => UART4_BASE,
+            UartId::Uart5 => UART5_BASE,
+            UartId::Uart6 => UART6_BASE,
+            UartId::Uart7 => UART7_BASE,
+            UartId::Uart8 => UART8_BASE,
+            UartId::Uart9 => UART9_BASE,
+        }
+    }
+    
+    /// GIC 中断号 (INTID)
+    /// 
+    /// UART0-UART9 依次为 SPI 331-340，INTID = SPI + 32
+    pub const fn irq(self) -> u32 {
+        363 + self as u32
+    }
+}
+
+/// 编译期确定的 UART 控制器
+/// 
+/// 控制器编号作为 const 泛型参数，编号越界 (大于 9) 在编译时报错。
+/// 运行时选择控制器时使用 [`UartId`]。
+/// 
+/// # 示例
+/// ```no_run
+/// use uart::{UartConfig, UartPort};
+/// 
+/// // 启动代码中唯一的 UART2 实例
+/// let uart = unsafe { UartPort::<2>::uart() };
+/// uart.init_with_config(&UartConfig::new(1_500_000)).unwrap();
+/// ```
+/// 
+/// 需要所有权保证时通过 `peripherals::Peripherals` 的 UART 令牌取得实例。
+pub struct UartPort<const N: usize>;
+
+impl<const N: usize> UartPort<N> {
+    /// 控制器基址
+    pub const BASE: usize = match UartId::from_index(N) {
+        Some(id) => id.base(),
+        None => panic!("UART port must be 0-9"),
+    };
+    
+    /// 创建该控制器的驱动实例
+    /// 
+    /// # Safety
+    /// 同一控制器同时只能有一个实例在使用：两个实例各自的缓冲区和锁互不相知，
+    /// 同时收发会互相破坏寄存器状态和缓冲区内容
+    pub const unsafe fn uart() -> Uart {
+        Uart::new(Self::BASE)
+    }
+}
 
 /// UART 寄存器偏移
 /// 
@@ -51,6 +200,7 @@ const UART_MCR: usize = 0x10;   // Modem 控制寄存器
 const UART_LSR: usize = 0x14;   // 线状态寄存器
 const UART_MSR: usize = 0x18;   // Modem 状态寄存器
 const UART_USR: usize = 0x7C;   // UART 状态寄存器 (Designware 扩展)
+const UART_DLF: usize = 0xC0;   // 小数分频寄存器 (Designware 扩展，部分 IP 版本没有)
 
 /// 线状态寄存器 (LSR) 位定义
 const LSR_DR: u32 = 1 << 0;     // 数据就绪
@@ -60,7 +210,11 @@ const LSR_FE: u32 = 1 << 3;     // 帧错误
 const LSR_BI: u32 = 1 << 4;     // Break 中断
 const LSR_THRE: u32 = 1 << 5;   // 发送保持寄存器空
 const LSR_TEMT: u32 = 1 << 6;   // 发送器空
-const LSR_ERR: u32 = 1 << 7;    // FIFO 错误
+/// 读 LSR 时被清除的接收错误位
+/// 
+/// LSR[7] (FIFO 中有出错的字节) 不使用：错误位随字节到达 FIFO 头部时由
+/// [`Uart::read_lsr`] 逐个记录，不需要提前知道 FIFO 中是否有错误
+const LSR_RX_ERRORS: u32 = LSR_OE | LSR_PE | LSR_FE | LSR_BI;
 
 /// 线控制寄存器 (LCR) 位定义
 const LCR_WLS_5: u32 = 0x00;    // 5 位数据位
@@ -70,16 +224,274 @@ const LCR_WLS_8: u32 = 0x03;    // 8 位数据位
 const LCR_STB: u32 = 1 << 2;    // 停止位 (0=1位, 1=1.5/2位)
 const LCR_PEN: u32 = 1 << 3;    // 奇偶校验使能
 const LCR_EPS: u32 = 1 << 4;    // 偶校验选择
+const LCR_SP: u32 = 1 << 5;     // 固定校验位 (Stick Parity)
+const LCR_BC: u32 = 1 << 6;     // Break 控制 (TX 强制为低电平)
 const LCR_DLAB: u32 = 1 << 7;   // 分频器锁存访问位
 
+/// 中断使能寄存器 (IER) 位定义
+const IER_ERBFI: u32 = 1 << 0;  // 接收数据可用中断
+const IER_ETBEI: u32 = 1 << 1;  // 发送保持寄存器空中断
+const IER_ELSI: u32 = 1 << 2;   // 接收线状态中断
+
+/// 中断识别寄存器 (IIR) 中断 ID
+const IIR_IID_MASK: u32 = 0x0F;
+const IIR_MODEM_STATUS: u32 = 0x00; // Modem 状态变化
+const IIR_NO_INT: u32 = 0x01;       // 无中断
+const IIR_THR_EMPTY: u32 = 0x02;    // 发送保持寄存器空
+const IIR_RX_DATA: u32 = 0x04;      // 接收数据可用
+const IIR_RX_LINE: u32 = 0x06;      // 接收线状态
+const IIR_BUSY_DETECT: u32 = 0x07;  // 忙检测 (Designware 扩展)
+const IIR_RX_TIMEOUT: u32 = 0x0C;   // 字符超时
+
+/// Modem 控制寄存器 (MCR) 位定义
+const MCR_DTR: u32 = 1 << 0;    // 数据终端就绪
+const MCR_RTS: u32 = 1 << 1;    // 请求发送
+const MCR_AFCE: u32 = 1 << 5;   // 自动流控使能
+
+/// Modem 状态寄存器 (MSR) 位定义
+const MSR_CTS: u32 = 1 << 4;    // 清除发送 (CTS 引脚有效)
+
+/// UART 状态寄存器 (USR) 位定义
+const USR_BUSY: u32 = 1 << 0;   // 正在收发 (此时 LCR 写入被忽略)
+const USR_TFNF: u32 = 1 << 1;   // TX FIFO 未满
+
+/// 等待 USR.BUSY 清除的最大轮询次数
+const BUSY_POLLS: u32 = 100_000;
+
+/// LCR 写入失败时的最大重试次数
+const LCR_WRITE_RETRIES: u32 = 1000;
+
+/// 发送 Break 前等待已排队数据发完的最长时间 (微秒)
+/// 
+/// 发送缓冲区满 (512 字节) 时 9600 波特率约需 530ms
+const BREAK_DRAIN_TIMEOUT_US: u32 = 1_000_000;
+
 /// FIFO 控制寄存器 (FCR) 位定义
 const FCR_FIFO_EN: u32 = 1 << 0;    // FIFO 使能
 const FCR_RX_FIFO_RST: u32 = 1 << 1; // 复位 RX FIFO
 const FCR_TX_FIFO_RST: u32 = 1 << 2; // 复位 TX FIFO
 
+/// 数据位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataBits {
+    /// 5 位
+    Five,
+    /// 6 位
+    Six,
+    /// 7 位
+    Seven,
+    /// 8 位
+    Eight,
+}
+
+/// 停止位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    /// 1 位
+    One,
+    /// 2 位 (5 位数据时为 1.5 位)
+    Two,
+}
+
+/// 校验方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    /// 无校验
+    None,
+    /// 奇校验
+    Odd,
+    /// 偶校验
+    Even,
+    /// 校验位固定为 1
+    Mark,
+    /// 校验位固定为 0
+    Space,
+}
+
+/// 流控方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowControl {
+    /// 无流控
+    None,
+    /// 硬件 RTS/CTS 自动流控
+    /// 
+    /// - CTS 无效时发送器自动暂停
+    /// - RX FIFO 达到触发水位时自动撤销 RTS
+    RtsCts,
+    /// XON/XOFF 软件流控，见 [`xonxoff`]
+    XonXoff,
+}
+
+/// UART 操作错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UartError {
+    /// 在指定的轮询次数内没有完成
+    Timeout,
+    /// 控制器持续忙 (USR.BUSY)，复位 FIFO 重试后 LCR 写入仍未生效
+    LcrBusy,
+    /// 基址不是 UART 控制器
+    UnknownBase,
+    /// 控制台输出端已满，由 `init_console` 返回
+    SinksFull,
+}
+
+/// 非阻塞操作当前无法完成，由 [`Uart::try_putc`] 返回
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WouldBlock;
+
+/// 接收线路错误
+/// 
+/// 按优先级从高到低为 Break、帧错误、校验错误、溢出
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RxError {
+    /// RX FIFO 溢出，之前的数据已丢失 (当前字节仍然有效)
+    Overrun = 0,
+    /// 奇偶校验错误
+    Parity = 1,
+    /// 帧错误 (停止位无效，通常是波特率不匹配)
+    Framing = 2,
+    /// 线路保持低电平超过一个字符时间
+    Break = 3,
+}
+
+/// 累计的接收线路错误次数，由 [`Uart::take_errors`] 返回
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineErrors {
+    pub overrun: u32,
+    pub parity: u32,
+    pub framing: u32,
+    pub breaks: u32,
+}
+
+impl LineErrors {
+    /// 错误总数
+    pub fn total(&self) -> u32 {
+        self.overrun + self.parity + self.framing + self.breaks
+    }
+}
+
+/// UART 线路配置
+/// 
+/// # 示例
+/// ```no_run
+/// use uart::{DataBits, Parity, StopBits, Uart, UartConfig, UART3_BASE};
+/// 
+/// // 9600 7E1
+/// let config = UartConfig {
+///     data_bits: DataBits::Seven,
+///     stop_bits: StopBits::One,
+///     parity: Parity::Even,
+///     ..UartConfig::new(9600)
+/// };
+/// let uart = Uart::new(UART3_BASE);
+/// uart.init_with_config(&config).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UartConfig {
+    /// 波特率
+    pub baud: u32,
+    /// 数据位
+    pub data_bits: DataBits,
+    /// 停止位
+    pub stop_bits: StopBits,
+    /// 校验方式
+    pub parity: Parity,
+    /// 流控方式
+    pub flow_control: FlowControl,
+    /// 时钟来源，用于计算波特率分频
+    pub clock: UartClock,
+}
+
+impl UartConfig {
+    /// 指定波特率的 8N1 配置
+    pub const fn new(baud: u32) -> Self {
+        Self {
+            baud,
+            data_bits: DataBits::Eight,
+            stop_bits: StopBits::One,
+            parity: Parity::None,
+            flow_control: FlowControl::None,
+            clock: UartClock::Cru,
+        }
+    }
+    
+    /// 对应的 LCR 值 (不含 DLAB)
+    fn lcr(&self) -> u32 {
+        let wls = match self.data_bits {
+            DataBits::Five => LCR_WLS_5,
+            DataBits::Six => LCR_WLS_6,
+            DataBits::Seven => LCR_WLS_7,
+            DataBits::Eight => LCR_WLS_8,
+        };
+        let stb = match self.stop_bits {
+            StopBits::One => 0,
+            StopBits::Two => LCR_STB,
+        };
+        let parity = match self.parity {
+            Parity::None => 0,
+            Parity::Odd => LCR_PEN,
+            Parity::Even => LCR_PEN | LCR_EPS,
+            Parity::Mark => LCR_PEN | LCR_SP,
+            Parity::Space => LCR_PEN | LCR_EPS | LCR_SP,
+        };
+        wls | stb | parity
+    }
+}
+
+impl Default for UartConfig {
+    /// 115200 8N1
+    fn default() -> Self {
+        Self::new(115200)
+    }
+}
+
+/// 中断接收环形缓冲区大小 (字节, 必须为 2 的幂)
+pub const RX_BUFFER_SIZE: usize = 256;
+
+/// 中断发送环形缓冲区大小 (字节, 必须为 2 的幂)
+pub const TX_BUFFER_SIZE: usize = 512;
+
+/// 硬件 FIFO 深度
+const UART_FIFO_DEPTH: usize = 64;
+
 /// UART 控制器结构体
 pub struct Uart {
     base: usize,
+    /// 中断接收缓冲区，由 `on_irq()` 填充
+    rx_buf: ring::RingBuffer<RX_BUFFER_SIZE>,
+    /// 接收缓冲区生产者一侧的锁 (`on_irq()` 与等待 XON 时的轮询接收)
+    rx_push: SpinLock<()>,
+    /// 接收缓冲区消费者一侧的锁 (共用同一串口的各个读取者)
+    rx_pop: SpinLock<()>,
+    /// 因接收缓冲区满而丢弃的字节数
+    rx_dropped: AtomicU32,
+    /// 中断发送缓冲区，由 `on_irq()` 搬到 TX FIFO
+    tx_buf: ring::RingBuffer<TX_BUFFER_SIZE>,
+    /// 发送缓冲区生产者一侧的锁 (共用同一串口的各个发送者)
+    tx_push: SpinLock<()>,
+    /// 发送缓冲区消费者一侧的锁 (`on_irq()` 与 `flush()`)，持有期间取出的字节按顺序写入 THR
+    tx_pop: SpinLock<()>,
+    /// 是否处于中断发送模式
+    tx_buffered: AtomicBool,
+    /// 接收线路错误计数，按 [`RxError`] 取值索引
+    line_errors: [AtomicU32; 4],
+    /// 收到 Break 后置位，由 `take_break()` 清除
+    break_received: AtomicBool,
+    /// 读 LSR 时发现、尚未交给接收路径的错误 (`RxError as u8 + 1`，0 表示无)
+    rx_error: AtomicU8,
+    /// 是否使能 XON/XOFF 软件流控
+    xonxoff: AtomicBool,
+    /// 收到 XOFF 后置位，收到 XON 后清除
+    tx_paused: AtomicBool,
+    /// 是否按接收缓冲区水位发送 XOFF/XON
+    rx_throttle: AtomicBool,
+    /// 已发送 XOFF，等待缓冲区水位降低
+    xoff_sent: AtomicBool,
+    /// 上一次 `read_line` 以 `\r` 结束，下一次忽略紧随的 `\n`
+    line_cr: AtomicBool,
+    /// 多路复用发送时整帧持有，不同通道的帧不会交错
+    #[cfg(feature = "mux")]
+    mux_tx: SpinLock<()>,
 }
 
 impl Uart {
@@ -94,7 +506,32 @@ impl Uart {
     /// let uart = Uart::new(UART2_BASE);
     /// ```
     pub const fn new(base: usize) -> Self {
-        Self { base }
+        Self {
+            base,
+            rx_buf: ring::RingBuffer::new(),
+            rx_push: SpinLock::new(()),
+            rx_pop: SpinLock::new(()),
+            rx_dropped: AtomicU32::new(0),
+            tx_buf: ring::RingBuffer::new(),
+            tx_push: SpinLock::new(()),
+            tx_pop: SpinLock::new(()),
+            tx_buffered: AtomicBool::new(false),
+            line_errors: [const { AtomicU32::new(0) }; 4],
+            break_received: AtomicBool::new(false),
+            rx_error: AtomicU8::new(0),
+            xonxoff: AtomicBool::new(false),
+            tx_paused: AtomicBool::new(false),
+            rx_throttle: AtomicBool::new(false),
+            xoff_sent: AtomicBool::new(false),
+            line_cr: AtomicBool::new(false),
+            #[cfg(feature = "mux")]
+            mux_tx: SpinLock::new(()),
+        }
+    }
+    
+    /// 由控制器编号创建 UART 实例
+    pub const fn from_id(id: UartId) -> Self {
+        Self::new(id.base())
     }
     
     /// 初始化 UART 控制器
@@ -108,47 +545,141 @@ impl Uart {
     /// - 校验位: 无
     /// - 流控: 无
     /// 
+    /// 需要其他线路配置时使用 [`init_with_config`](Self::init_with_config)
+    /// 
+    /// # 返回值
+    /// 同 [`init_with_config`](Self::init_with_config)
+    /// 
+    /// # 示例
+    /// ```no_run
+    /// use uart::{Uart, UART2_BASE};
+    /// let uart = Uart::new(UART2_BASE);
+    /// uart.init(115200).unwrap();  // 初始化为 115200 8N1
+    /// ```
+    pub fn init(&self, baudrate: u32) -> Result<(), UartError> {
+        self.init_with_config(&UartConfig::new(baudrate))
+    }
+    
+    /// 按指定线路配置初始化 UART 控制器
+    /// 
+    /// # 参数
+    /// - `config`: 波特率、数据位、停止位、校验方式和流控
+    /// 
     /// # 波特率计算
     /// ```
-    /// divisor = clock / (16 * baudrate)
+    /// divisor = round(clock / (16 * baudrate))
     /// ```
-    /// 假设 UART 时钟 24MHz，波特率 115200:
+    /// `clock` 由 `config.clock` 决定，默认从 CRU 读取实际的 sclk_uart 频率。
+    /// 例如 UART 时钟 24MHz，波特率 115200:
     /// ```
     /// divisor = 24,000,000 / (16 * 115200) = 13 (0x0D)
     /// ```
+    /// 控制器带小数分频寄存器 (DLF) 时，分频系数精确到 1/2^n (n 为 DLF 位宽)，
+    /// 见 [`clock::Divisor`]；没有 DLF 的 IP 版本只使用整数部分。
     /// 
-    /// # 示例
-    /// ```no_run
-    /// use uart::{Uart, UART2_BASE};
-    /// let uart = Uart::new(UART2_BASE);
-    /// uart.init(115200);  // 初始化为 115200 8N1
-    /// ```
-    pub fn init(&self, baudrate: u32) {
+    /// # 返回值
+    /// - `Ok(())`: 初始化完成
+    /// - `Err(UartError::LcrBusy)`: 控制器持续忙，LCR 写入没有生效，
+    ///   线路配置 (波特率、数据格式) 可能仍是旧值
+    /// 
+    /// # 注意
+    /// Designware UART 忙 (USR.BUSY) 时 LCR 和分频器的写入会被忽略，
+    /// 因此先等待当前收发结束，写 LCR 后回读校验，必要时复位 FIFO 后重试。
+    /// 正在收发的数据可能因此丢失。
+    pub fn init_with_config(&self, config: &UartConfig) -> Result<(), UartError> {
+        #[cfg(feature = "fault-inject")]
+        let _ = fault::register(&RX_OVERRUN_FAULT);
+        
         unsafe {
             // 1. 禁用中断
             let ier_addr = (self.base + UART_IER) as *mut u32;
             write_volatile(ier_addr, 0);
             
-            // 2. 设置 DLAB=1 以访问分频器
-            let lcr_addr = (self.base + UART_LCR) as *mut u32;
-            write_volatile(lcr_addr, LCR_DLAB);
+            // 2. 等待当前收发结束，设置 DLAB=1 以访问分频器
+            self.wait_not_busy();
+            self.write_lcr(LCR_DLAB)?;
             
-            // 3. 计算并设置分频器
-            // 假设 UART 时钟源为 24MHz
-            let clock = 24_000_000;
-            let divisor = clock / (16 * baudrate);
+            // 3. 计算并设置分频器 (四舍五入，支持时使用小数分频)
+            let clock = config.clock.resolve(self.base);
+            let divisor = clock::Divisor::new(clock, config.baud, self.dlf_bits());
             
             let dll_addr = (self.base + UART_DLL) as *mut u32;
             let dlh_addr = (self.base + UART_DLH) as *mut u32;
-            write_volatile(dll_addr, (divisor & 0xFF) as u32);
-            write_volatile(dlh_addr, ((divisor >> 8) & 0xFF) as u32);
+            write_volatile(dll_addr, divisor.integer & 0xFF);
+            write_volatile(dlh_addr, (divisor.integer >> 8) & 0xFF);
+            if divisor.fraction_bits > 0 {
+                let dlf_addr = (self.base + UART_DLF) as *mut u32;
+                write_volatile(dlf_addr, divisor.fraction);
+            }
             
-            // 4. 清除 DLAB, 设置 8N1 (8位数据, 无校验, 1位停止)
-            write_volatile(lcr_addr, LCR_WLS_8);
+            // 4. 清除 DLAB, 设置数据位/停止位/校验
+            self.write_lcr(config.lcr())?;
             
             // 5. 使能并复位 FIFO
             let fcr_addr = (self.base + UART_FCR) as *mut u32;
             write_volatile(fcr_addr, FCR_FIFO_EN | FCR_RX_FIFO_RST | FCR_TX_FIFO_RST);
+            
+            // 6. 配置流控 (自动 RTS 需要同时置位 RTS 和 AFCE)
+            let mcr_addr = (self.base + UART_MCR) as *mut u32;
+            let mut mcr = read_volatile(mcr_addr);
+            match config.flow_control {
+                FlowControl::None | FlowControl::XonXoff => mcr &= !MCR_AFCE,
+                FlowControl::RtsCts => mcr |= MCR_AFCE | MCR_RTS | MCR_DTR,
+            }
+            write_volatile(mcr_addr, mcr);
+            
+            // 7. 软件流控状态
+            self.xonxoff.store(config.flow_control == FlowControl::XonXoff, Ordering::Release);
+            self.tx_paused.store(false, Ordering::Release);
+            self.xoff_sent.store(false, Ordering::Release);
+        }
+        Ok(())
+    }
+    
+    /// 小数分频寄存器 (DLF) 的位宽，控制器没有 DLF 时返回 0
+    /// 
+    /// 写入全 1 后回读，未实现的位读出为 0 (与 Linux 8250_dw 驱动的探测方法相同)。
+    /// 没有 DLF 的 IP 版本上该地址为保留寄存器，写入被忽略。
+    pub fn dlf_bits(&self) -> u32 {
+        unsafe {
+            let dlf_addr = (self.base + UART_DLF) as *mut u32;
+            let saved = read_volatile(dlf_addr);
+            write_volatile(dlf_addr, u32::MAX);
+            let bits = 32 - read_volatile(dlf_addr).leading_zeros();
+            write_volatile(dlf_addr, saved);
+            bits
+        }
+    }
+    
+    /// 手动设置 RTS 引脚
+    /// 
+    /// # 参数
+    /// - `active`: `true` 表示有效 (允许对端发送)
+    /// 
+    /// # 注意
+    /// 使能自动流控时，撤销 RTS 会同时关闭自动 RTS，只保留自动 CTS
+    pub fn set_rts(&self, active: bool) {
+        unsafe {
+            let mcr_addr = (self.base + UART_MCR) as *mut u32;
+            let mut mcr = read_volatile(mcr_addr);
+            if active {
+                mcr |= MCR_RTS;
+            } else {
+                mcr &= !MCR_RTS;
+            }
+            write_volatile(mcr_addr, mcr);
+        }
+    }
+    
+    /// 读取 CTS 引脚状态
+    /// 
+    /// # 返回值
+    /// - `true`: CTS 有效 (对端允许发送)
+    /// - `false`: CTS 无效
+    pub fn cts(&self) -> bool {
+        unsafe {
+            let msr_addr = (self.base + UART_MSR) as *const u32;
+            (read_volatile(msr_addr) & MSR_CTS) != 0
         }
     }
     
@@ -158,15 +689,76 @@ impl Uart {
     /// - `byte`: 要发送的字节
     /// 
     /// # 阻塞
-    /// 此函数会等待发送缓冲区空闲
+    /// - 轮询模式: 等待发送保持寄存器空闲 (软件流控暂停时等待 XON)
+    /// - 中断发送模式: 写入发送缓冲区后立即返回，缓冲区满时等待中断腾出空间
     pub fn putc(&self, byte: u8) {
+        if self.tx_buffered.load(Ordering::Acquire) {
+            while !self.push_tx(byte) {
+                core::hint::spin_loop();
+            }
+            if !self.is_tx_paused() {
+                self.modify_ier(IER_ETBEI, true);
+            }
+        } else {
+            self.wait_tx_resume();
+            self.putc_polled(byte);
+        }
+    }
+    
+    /// 发送一个字节 (非阻塞)
+    /// 
+    /// TX FIFO (中断发送模式下为发送缓冲区) 已满或软件流控暂停时立即返回，
+    /// 不做 `\n` → `\r\n` 转换。
+    /// 适合在空闲循环中尽力输出日志，而不是在 [`putc`](Self::putc) 中自旋等待。
+    /// 
+    /// # 返回值
+    /// - `Ok(())`: 字节已写入
+    /// - `Err(WouldBlock)`: 没有空间，稍后重试
+    /// 
+    /// # 示例
+    /// ```no_run
+    /// use uart::{Uart, UART2_BASE};
+    /// 
+    /// let uart = Uart::new(UART2_BASE);
+    /// let pending = b"idle\r\n";
+    /// let mut sent = 0;
+    /// while sent < pending.len() && uart.try_putc(pending[sent]).is_ok() {
+    ///     sent += 1;
+    /// }
+    /// ```
+    pub fn try_putc(&self, byte: u8) -> Result<(), WouldBlock> {
+        if self.tx_buffered.load(Ordering::Acquire) {
+            if !self.push_tx(byte) {
+                return Err(WouldBlock);
+            }
+            if !self.is_tx_paused() {
+                self.modify_ier(IER_ETBEI, true);
+            }
+            return Ok(());
+        }
+        if self.is_tx_paused() {
+            return Err(WouldBlock);
+        }
+        
         unsafe {
-            // 等待发送保持寄存器空 (LSR[5] = 1)
-            let lsr_addr = (self.base + UART_LSR) as *const u32;
-            while (read_volatile(lsr_addr) & LSR_THRE) == 0 {
-                // 自旋等待
+            let usr_addr = (self.base + UART_USR) as *const u32;
+            if read_volatile(usr_addr) & USR_TFNF == 0 {
+                return Err(WouldBlock);
             }
-            
+            let thr_addr = (self.base + UART_THR) as *mut u32;
+            write_volatile(thr_addr, byte as u32);
+        }
+        Ok(())
+    }
+    
+    /// 轮询方式发送一个字节
+    pub(crate) fn putc_polled(&self, byte: u8) {
+        // 等待发送保持寄存器空 (LSR[5] = 1)
+        while (self.read_lsr() & LSR_THRE) == 0 {
+            // 自旋等待
+        }
+        
+        unsafe {
             // 写入数据到发送保持寄存器
             let thr_addr = (self.base + UART_THR) as *mut u32;
             write_volatile(thr_addr, byte as u32);
@@ -175,21 +767,220 @@ impl Uart {
     
     /// 接收一个字节 (非阻塞)
     /// 
+    /// 优先读取中断接收缓冲区，其次读取 RX FIFO。
+    /// 出错的字节被丢弃，错误计入 [`take_errors`](Self::take_errors)。
+    /// 需要逐字节区分错误时使用 [`try_getc`](Self::try_getc)。
+    /// 
     /// # 返回值
     /// - `Some(byte)`: 收到数据
-    /// - `None`: 接收缓冲区为空
+    /// - `None`: 接收缓冲区为空或当前字节有错误
     pub fn getc(&self) -> Option<u8> {
-        unsafe {
-            let lsr_addr = (self.base + UART_LSR) as *const u32;
-            
-            // 检查数据就绪位 (LSR[0])
-            if (read_volatile(lsr_addr) & LSR_DR) != 0 {
-                let rbr_addr = (self.base + UART_RBR) as *const u32;
-                Some(read_volatile(rbr_addr) as u8)
-            } else {
-                None
+        self.try_getc().ok().flatten()
+    }
+    
+    /// 接收一个字节并报告线路错误 (非阻塞)
+    /// 
+    /// # 返回值
+    /// - `Ok(Some(byte))`: 收到数据
+    /// - `Ok(None)`: 接收缓冲区为空，或收到的是 XON/XOFF 流控字符
+    /// - `Err(RxError::Overrun)`: 发生溢出，之前的数据已丢失；当前字节仍在 FIFO 中，
+    ///   下次调用返回
+    /// - `Err(..)`: 其他错误，出错的字节已被丢弃
+    pub fn try_getc(&self) -> Result<Option<u8>, RxError> {
+        // 先取接收缓冲区中的数据 (中断接收或等待 XON 期间读出)
+        if let Some(byte) = self.pop_rx() {
+            return Ok(Some(byte));
+        }
+        
+        let lsr = self.read_lsr();
+        #[cfg(feature = "fault-inject")]
+        if RX_OVERRUN_FAULT.should_fail() {
+            self.note_line_errors(LSR_OE);
+        }
+        let error = self.take_rx_error();
+        if error == Some(RxError::Overrun) {
+            return Err(RxError::Overrun);
+        }
+        
+        // 检查数据就绪位 (LSR[0])
+        if (lsr & LSR_DR) == 0 {
+            return Ok(None);
+        }
+        let byte = unsafe { read_volatile((self.base + UART_RBR) as *const u32) as u8 };
+        match error {
+            Some(err) => Err(err),
+            None if self.filter_rx(byte) => Ok(None),
+            None => Ok(Some(byte)),
+        }
+    }
+    
+    /// 读取 LSR
+    /// 
+    /// 读 LSR 会清除 RX FIFO 头部字节的错误位，所以所有 LSR 读取都经过这里：
+    /// 错误先计数，再留给接收路径 ([`take_rx_error`](Self::take_rx_error))
+    /// 丢弃对应的字节。发送等待中读到的错误也不会丢失。
+    pub(crate) fn read_lsr(&self) -> u32 {
+        let lsr = unsafe { read_volatile((self.base + UART_LSR) as *const u32) };
+        if lsr & LSR_RX_ERRORS != 0 {
+            self.note_line_errors(lsr);
+        }
+        lsr
+    }
+    
+    /// 统计错误并保存给接收路径，已有未处理的错误时保留先发生的
+    fn note_line_errors(&self, lsr: u32) {
+        if let Some(err) = self.record_line_errors(lsr) {
+            let _ = self.rx_error.compare_exchange(0, err as u8 + 1, Ordering::Relaxed, Ordering::Relaxed);
+        }
+    }
+    
+    /// 取出 RX FIFO 头部字节的错误 (已计数)
+    fn take_rx_error(&self) -> Option<RxError> {
+        match self.rx_error.swap(0, Ordering::Relaxed) {
+            1 => Some(RxError::Overrun),
+            2 => Some(RxError::Parity),
+            3 => Some(RxError::Framing),
+            4 => Some(RxError::Break),
+            _ => None,
+        }
+    }
+    
+    /// 统计 LSR 中的错误位
+    /// 
+    /// # 返回值
+    /// 优先级最高的错误。Break 同时会置位帧错误/校验错误，只计为 Break。
+    fn record_line_errors(&self, lsr: u32) -> Option<RxError> {
+        let lsr = if lsr & LSR_BI != 0 {
+            self.break_received.store(true, Ordering::Release);
+            lsr & !(LSR_FE | LSR_PE)
+        } else {
+            lsr
+        };
+        let mut first = None;
+        for (bit, err) in [
+            (LSR_BI, RxError::Break),
+            (LSR_FE, RxError::Framing),
+            (LSR_PE, RxError::Parity),
+            (LSR_OE, RxError::Overrun),
+        ] {
+            if lsr & bit != 0 {
+                self.line_errors[err as usize].fetch_add(1, Ordering::Relaxed);
+                first = first.or(Some(err));
             }
         }
+        first
+    }
+    
+    /// 取出并清零累计的接收线路错误次数
+    /// 
+    /// 中断接收模式下出错的字节不会进入接收缓冲区，只能通过此函数发现线路问题
+    pub fn take_errors(&self) -> LineErrors {
+        let take = |err: RxError| self.line_errors[err as usize].swap(0, Ordering::Relaxed);
+        LineErrors {
+            overrun: take(RxError::Overrun),
+            parity: take(RxError::Parity),
+            framing: take(RxError::Framing),
+            breaks: take(RxError::Break),
+        }
+    }
+    
+    /// 发送 Break (TX 线保持低电平)
+    /// 
+    /// 先等待已排队的数据发送完 (最多 [`BREAK_DRAIN_TIMEOUT_US`])，然后置位 LCR.BC
+    /// 保持 `duration_us` 微秒后清除。所有等待都用 [`timer::Timeout`] 计时，
+    /// 对端拉低 CTS 或 XOFF 暂停导致发送器停住时不会一直等下去。
+    /// 对端能识别的 Break 至少为 2 个字符时间 (115200 8N1 下约 174µs)。
+    /// 
+    /// # 参数
+    /// - `duration_us`: Break 持续时间 (微秒)
+    /// 
+    /// # 返回值
+    /// - `Ok(())`: Break 已发送，LCR 已恢复
+    /// - `Err(UartError::Timeout)`: 已排队的数据没能发完，没有发送 Break
+    /// - `Err(UartError::LcrBusy)`: 置位或清除 LCR.BC 失败
+    /// 
+    /// # 注意
+    /// Break 期间写入的数据不会出现在线路上
+    /// 
+    /// # 示例
+    /// ```no_run
+    /// use uart::{Uart, UART2_BASE};
+    /// 
+    /// let uart = Uart::new(UART2_BASE);
+    /// uart.send_break(100_000).unwrap();  // 100ms
+    /// ```
+    pub fn send_break(&self, duration_us: u32) -> Result<(), UartError> {
+        self.flush(BREAK_DRAIN_TIMEOUT_US)?;
+        
+        let lcr = unsafe { read_volatile((self.base + UART_LCR) as *const u32) };
+        self.write_lcr(lcr | LCR_BC)?;
+        
+        let mut hold = timer::Timeout::new(duration_us as u64);
+        while !hold.expired() {
+            core::hint::spin_loop();
+        }
+        
+        self.write_lcr(lcr & !LCR_BC)
+    }
+    
+    /// 查询并清除 "收到 Break" 事件
+    /// 
+    /// 接收路径 (`getc`/`try_getc` 或中断接收) 检测到 LSR.BI 时置位。
+    /// 一次 Break 无论持续多长只产生一次事件，可用作主机请求进入恢复 Shell 的信号。
+    /// 
+    /// # 返回值
+    /// 上次调用以来是否收到过 Break
+    pub fn take_break(&self) -> bool {
+        self.break_received.swap(false, Ordering::AcqRel)
+    }
+    
+    /// 接收一个字节 (阻塞)
+    /// 
+    /// 一直等待直到收到数据。已调用 [`enable_rx_interrupt`](Self::enable_rx_interrupt)
+    /// 时从接收缓冲区读取，否则直接读取 RX FIFO。
+    pub fn getc_blocking(&self) -> u8 {
+        loop {
+            if let Some(byte) = self.getc() {
+                return byte;
+            }
+            core::hint::spin_loop();
+        }
+    }
+    
+    /// 接收一个字节 (带超时)
+    /// 
+    /// 用 ARM 通用定时器计时 ([`timer::Timeout`])，固件未设置 CNTFRQ_EL0 时退化为
+    /// 按估计的轮询次数计时。
+    /// 
+    /// # 参数
+    /// - `timeout_us`: 最长等待时间 (微秒)
+    /// 
+    /// # 返回值
+    /// - `Ok(byte)`: 收到数据
+    /// - `Err(UartError::Timeout)`: `timeout_us` 内没有数据
+    /// 
+    /// # 示例
+    /// ```no_run
+    /// use uart::{Uart, UART2_BASE};
+    /// 
+    /// let uart = Uart::new(UART2_BASE);
+    /// // 等待 1 秒
+    /// match uart.getc_timeout(1_000_000) {
+    ///     Ok(b) => uart.putc(b),
+    ///     Err(_) => uart.puts("no input\n"),
+    /// }
+    /// ```
+    pub fn getc_timeout(&self, timeout_us: u32) -> Result<u8, UartError> {
+        let mut timeout = timer::Timeout::new(timeout_us as u64);
+        loop {
+            if let Some(byte) = self.getc() {
+                return Ok(byte);
+            }
+            if timeout.expired() {
+                return Err(UartError::Timeout);
+            }
+            core::hint::spin_loop();
+        }
     }
     
     /// 发送字符串
@@ -208,16 +999,376 @@ impl Uart {
         }
     }
     
+    /// 批量发送 (非阻塞)
+    /// 
+    /// 写入 TX FIFO 当前能容纳的数据后立即返回，不做 `\n` → `\r\n` 转换，
+    /// 适用于 XMODEM 等二进制协议。中断发送模式下写入发送缓冲区。
+    /// 软件流控暂停时轮询模式下不写入任何数据。
+    /// 
+    /// # 返回值
+    /// 实际写入的字节数，可能小于 `data.len()`，调用者需要重试剩余部分
+    pub fn write_bytes(&self, data: &[u8]) -> usize {
+        if self.tx_buffered.load(Ordering::Acquire) {
+            let count = {
+                let _producer = self.tx_push.lock_irqsave();
+                // 持有 tx_push，是唯一的生产者；整段数据在缓冲区中连续
+                data.iter().take_while(|&&byte| unsafe { self.tx_buf.push(byte) }).count()
+            };
+            if count > 0 && !self.is_tx_paused() {
+                self.modify_ier(IER_ETBEI, true);
+            }
+            return count;
+        }
+        if self.is_tx_paused() {
+            return 0;
+        }
+        
+        let usr_addr = (self.base + UART_USR) as *const u32;
+        let thr_addr = (self.base + UART_THR) as *mut u32;
+        let mut count = 0;
+        unsafe {
+            while count < data.len() && read_volatile(usr_addr) & USR_TFNF != 0 {
+                write_volatile(thr_addr, data[count] as u32);
+                count += 1;
+            }
+        }
+        count
+    }
+    
+    /// 批量接收 (非阻塞)
+    /// 
+    /// 先读取中断接收缓冲区，再读取 RX FIFO 中已有的数据，没有数据时立即返回。
+    /// 
+    /// # 返回值
+    /// 实际读取的字节数，最多 `buf.len()` 字节
+    pub fn read_bytes(&self, buf: &mut [u8]) -> usize {
+        let mut count = 0;
+        while count < buf.len() {
+            match self.getc() {
+                Some(byte) => {
+                    buf[count] = byte;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        count
+    }
+    
     /// 检查发送器是否空闲
     /// 
     /// # 返回值
     /// - `true`: 发送器空闲
     /// - `false`: 仍在发送数据
     pub fn is_tx_idle(&self) -> bool {
+        (self.read_lsr() & LSR_TEMT) != 0
+    }
+    
+    /// 等待发送完成 (发送缓冲区已空且 LSR.TEMT 置位)，最多等待 `timeout_us` 微秒
+    /// 
+    /// 发送缓冲区中的数据直接以轮询方式写入 FIFO，不依赖 THRE 中断，
+    /// 屏蔽中断时 (panic 处理、复位前) 也能完成。
+    /// 与 [`getc_timeout`](Self::getc_timeout) 相同，用 [`timer::Timeout`] 计时。
+    /// 
+    /// # 参数
+    /// - `timeout_us`: 最长等待时间 (微秒)，对端一直拉低 CTS 等导致线路卡住时在此之后放弃
+    /// 
+    /// # 返回值
+    /// - `Ok(())`: 所有数据 (含移位寄存器) 已发出
+    /// - `Err(UartError::Timeout)`: 线路卡住，剩余数据留在 FIFO/发送缓冲区中
+    /// 
+    /// # 示例
+    /// ```no_run
+    /// use uart::{Uart, UART2_BASE};
+    /// 
+    /// let uart = Uart::new(UART2_BASE);
+    /// uart.puts("rebooting\n");
+    /// // 复位前保证提示已发出，但最多等 100ms，不会因为线路卡住而无法复位
+    /// let _ = uart.flush(100_000);
+    /// ```
+    pub fn flush(&self, timeout_us: u32) -> Result<(), UartError> {
+        let thr_addr = (self.base + UART_THR) as *mut u32;
+        let mut timeout = timer::Timeout::new(timeout_us as u64);
+        while !timeout.expired() {
+            let lsr = self.read_lsr();
+            if (lsr & LSR_THRE) != 0 {
+                let _consumer = self.tx_pop.lock_irqsave();
+                // 持有 tx_pop，是唯一的消费者
+                match unsafe { self.tx_buf.pop() } {
+                    Some(byte) => unsafe { write_volatile(thr_addr, byte as u32) },
+                    None if (lsr & LSR_TEMT) != 0 => return Ok(()),
+                    None => {}
+                }
+            }
+        }
+        Err(UartError::Timeout)
+    }
+    
+    /// 检查控制器是否正在收发 (USR.BUSY)
+    /// 
+    /// 忙时 LCR 和分频器的写入会被忽略
+    pub fn is_busy(&self) -> bool {
+        unsafe {
+            let usr_addr = (self.base + UART_USR) as *const u32;
+            (read_volatile(usr_addr) & USR_BUSY) != 0
+        }
+    }
+    
+    /// 写 LCR 前等待 USR.BUSY 清除
+    /// 
+    /// [`BUSY_POLLS`] 次轮询后仍忙 (例如线路上持续有数据进入) 时复位 FIFO 强制空闲，
+    /// 写入是否生效由 [`write_lcr`](Self::write_lcr) 回读确认
+    fn wait_not_busy(&self) {
+        for _ in 0..BUSY_POLLS {
+            if !self.is_busy() {
+                return;
+            }
+            core::hint::spin_loop();
+        }
+        self.force_idle();
+    }
+    
+    /// 复位 FIFO 并丢弃接收数据，使 USR.BUSY 尽快清除
+    fn force_idle(&self) {
+        unsafe {
+            let fcr_addr = (self.base + UART_FCR) as *mut u32;
+            write_volatile(fcr_addr, FCR_FIFO_EN | FCR_RX_FIFO_RST | FCR_TX_FIFO_RST);
+            
+            if self.read_lsr() & LSR_DR != 0 {
+                read_volatile((self.base + UART_RBR) as *const u32);
+            }
+        }
+        // 保存的错误属于已丢弃的数据
+        self.rx_error.store(0, Ordering::Relaxed);
+    }
+    
+    /// 写 LCR 并回读校验
+    /// 
+    /// 控制器忙时写入会被静默忽略，此时复位 FIFO 后重试，
+    /// 与 Linux 的 `dw8250_check_lcr` 相同。固定校验位 (SP) 在部分版本上读回不可靠，不参与比较。
+    /// 
+    /// # 返回值
+    /// - `Ok(())`: 写入生效
+    /// - `Err(UartError::LcrBusy)`: 重试 [`LCR_WRITE_RETRIES`] 次仍失败
+    fn write_lcr(&self, value: u32) -> Result<(), UartError> {
+        let lcr_addr = (self.base + UART_LCR) as *mut u32;
+        for _ in 0..LCR_WRITE_RETRIES {
+            unsafe {
+                write_volatile(lcr_addr, value);
+                if (read_volatile(lcr_addr) ^ value) & 0xFF & !LCR_SP == 0 {
+                    return Ok(());
+                }
+            }
+            self.force_idle();
+        }
+        Err(UartError::LcrBusy)
+    }
+    
+    /// 使能接收中断
+    /// 
+    /// 打开接收数据可用、字符超时和接收线状态中断。
+    /// 之后需要在 UART 中断处理函数中调用 [`on_irq`](Self::on_irq)，
+    /// 并通过 [`read`](Self::read)/[`try_read`](Self::try_read) 读取数据。
+    /// 
+    /// # 注意
+    /// 中断控制器 (GIC) 中对应的中断号需要由调用者另外使能
+    pub fn enable_rx_interrupt(&self) {
+        self.modify_ier(IER_ERBFI | IER_ELSI, true);
+    }
+    
+    /// 关闭接收中断，回到轮询模式
+    pub fn disable_rx_interrupt(&self) {
+        self.modify_ier(IER_ERBFI | IER_ELSI, false);
+    }
+    
+    /// 修改 IER 中的部分位
+    fn modify_ier(&self, bits: u32, enable: bool) {
+        unsafe {
+            let ier_addr = (self.base + UART_IER) as *mut u32;
+            let mut ier = read_volatile(ier_addr);
+            if enable {
+                ier |= bits;
+            } else {
+                ier &= !bits;
+            }
+            write_volatile(ier_addr, ier);
+        }
+    }
+    
+    /// UART 中断处理
+    /// 
+    /// 应在 UART 中断处理函数中调用。循环读取 IIR 直到没有待处理的中断：
+    /// - 接收数据/字符超时/线状态: 把 RX FIFO 中的数据全部搬到接收缓冲区
+    /// - 发送保持寄存器空: 从发送缓冲区取数据填满 TX FIFO，缓冲区空时关闭该中断
+    /// - 忙检测: 读取 USR 清除中断
+    /// - Modem 状态: 读取 MSR 清除中断
+    /// 
+    /// 接收缓冲区满时新数据被丢弃，丢弃数量可通过 [`rx_dropped`](Self::rx_dropped) 查询
+    pub fn on_irq(&self) {
         unsafe {
-            let lsr_addr = (self.base + UART_LSR) as *const u32;
-            (read_volatile(lsr_addr) & LSR_TEMT) != 0
+            let iir_addr = (self.base + UART_IIR) as *const u32;
+            loop {
+                match read_volatile(iir_addr) & IIR_IID_MASK {
+                    IIR_NO_INT => break,
+                    IIR_RX_DATA | IIR_RX_TIMEOUT | IIR_RX_LINE => self.drain_rx_fifo(),
+                    IIR_BUSY_DETECT => {
+                        read_volatile((self.base + UART_USR) as *const u32);
+                    }
+                    IIR_MODEM_STATUS => {
+                        read_volatile((self.base + UART_MSR) as *const u32);
+                    }
+                    IIR_THR_EMPTY => self.refill_tx_fifo(),
+                    _ => break,
+                }
+            }
+        }
+    }
+    
+    /// 用发送缓冲区中的数据填充 TX FIFO
+    /// 
+    /// THRE 中断表示 FIFO 已空，可以一次写入 FIFO 深度的数据
+    fn refill_tx_fifo(&self) {
+        if self.is_tx_paused() {
+            // 收到 XON 后重新打开
+            self.modify_ier(IER_ETBEI, false);
+            return;
+        }
+        let thr_addr = (self.base + UART_THR) as *mut u32;
+        let _consumer = self.tx_pop.lock_irqsave();
+        for _ in 0..UART_FIFO_DEPTH {
+            // 持有 tx_pop，是唯一的消费者
+            match unsafe { self.tx_buf.pop() } {
+                Some(byte) => unsafe { write_volatile(thr_addr, byte as u32) },
+                None => break,
+            }
+        }
+        if self.tx_buf.is_empty() {
+            self.modify_ier(IER_ETBEI, false);
+        }
+    }
+    
+    /// 把 RX FIFO 中的数据搬到接收缓冲区
+    pub(crate) fn drain_rx_fifo(&self) {
+        let rbr_addr = (self.base + UART_RBR) as *const u32;
+        // 读 RBR 和写入缓冲区在同一把锁内，多个搬运者之间不会打乱字节顺序
+        let _producer = self.rx_push.lock_irqsave();
+        loop {
+            let lsr = self.read_lsr();
+            let error = self.take_rx_error();
+            if (lsr & LSR_DR) == 0 {
+                break;
+            }
+            let byte = unsafe { read_volatile(rbr_addr) as u8 };
+            // 溢出时当前字节有效，其他错误的字节丢弃
+            if matches!(error, Some(err) if err != RxError::Overrun) || self.filter_rx(byte) {
+                continue;
+            }
+            // 持有 rx_push，是唯一的生产者
+            if !unsafe { self.rx_buf.push(byte) } {
+                self.rx_dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            self.throttle_rx();
+        }
+    }
+    
+    /// 切换到中断发送模式
+    /// 
+    /// 之后 `putc`/`puts`/`write_str` 只把数据写入发送缓冲区，
+    /// 由 THRE 中断在 [`on_irq`](Self::on_irq) 中搬到 TX FIFO，
+    /// 仅在缓冲区满时阻塞。
+    /// 
+    /// # 注意
+    /// 中断控制器 (GIC) 中对应的中断号需要由调用者另外使能
+    pub fn enable_tx_interrupt(&self) {
+        self.tx_buffered.store(true, Ordering::Release);
+    }
+    
+    /// 回到轮询发送模式
+    /// 
+    /// 关闭 THRE 中断，并以轮询方式发送完缓冲区中剩余的数据。
+    /// 中断不可用时 (例如 panic 处理) 应先调用此函数再输出。
+    pub fn disable_tx_interrupt(&self) {
+        self.modify_ier(IER_ETBEI, false);
+        self.tx_buffered.store(false, Ordering::Release);
+        while let Some(byte) = self.pop_tx() {
+            self.putc_polled(byte);
+        }
+    }
+    
+    /// 写入发送缓冲区，多个发送者之间互斥
+    /// 
+    /// # 返回值
+    /// 缓冲区已满时返回 `false`
+    fn push_tx(&self, byte: u8) -> bool {
+        let _producer = self.tx_push.lock_irqsave();
+        // 持有 tx_push，是唯一的生产者
+        unsafe { self.tx_buf.push(byte) }
+    }
+    
+    /// 从发送缓冲区取一个字节，与 `on_irq()`/`flush()` 互斥
+    fn pop_tx(&self) -> Option<u8> {
+        let _consumer = self.tx_pop.lock_irqsave();
+        // 持有 tx_pop，是唯一的消费者
+        unsafe { self.tx_buf.pop() }
+    }
+    
+    /// 发送缓冲区中尚未写入 FIFO 的字节数
+    pub fn tx_pending(&self) -> usize {
+        self.tx_buf.len()
+    }
+    
+    /// 从接收缓冲区取一个字节 (非阻塞)
+    /// 
+    /// 仅在 [`enable_rx_interrupt`](Self::enable_rx_interrupt) 之后，
+    /// 或串口已加入 `poll::add_rx_drain` (`poll` feature) 时有效
+    /// 
+    /// # 返回值
+    /// - `Some(byte)`: 缓冲区中有数据
+    /// - `None`: 缓冲区为空
+    pub fn try_read(&self) -> Option<u8> {
+        self.pop_rx()
+    }
+    
+    /// 从接收缓冲区读取数据 (阻塞)
+    /// 
+    /// 等待至少一个字节到达，然后读取缓冲区中已有的数据，最多 `buf.len()` 字节
+    /// 
+    /// # 返回值
+    /// 实际读取的字节数，`buf` 为空时立即返回 0
+    pub fn read(&self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+        
+        let first = loop {
+            if let Some(byte) = self.pop_rx() {
+                break byte;
+            }
+            core::hint::spin_loop();
+        };
+        buf[0] = first;
+        
+        let mut count = 1;
+        while count < buf.len() {
+            match self.pop_rx() {
+                Some(byte) => {
+                    buf[count] = byte;
+                    count += 1;
+                }
+                None => break,
+            }
         }
+        count
+    }
+    
+    /// 接收缓冲区中待读取的字节数
+    pub fn rx_available(&self) -> usize {
+        self.rx_buf.len()
+    }
+    
+    /// 因接收缓冲区满而丢弃的字节数
+    pub fn rx_dropped(&self) -> u32 {
+        self.rx_dropped.load(Ordering::Relaxed)
     }
 }
 
@@ -228,47 +1379,3 @@ impl fmt::Write for Uart {
         Ok(())
     }
 }
-
-/// 全局控制台 UART 实例（可选）
-/// 
-/// 用于实现 print! 和 println! 宏
-static mut CONSOLE: Option<Uart> = None;
-
-/// 初始化全局控制台
-/// 
-/// # 参数
-/// - `base`: UART 基址
-/// - `baudrate`: 波特率
-/// 
-/// # 安全性
-/// 此函数使用 unsafe，应在系统启动时调用一次
-pub fn init_console(base: usize, baudrate: u32) {
-    unsafe {
-        let uart = Uart::new(base);
-        uart.init(baudrate);
-        CONSOLE = Some(uart);
-    }
-}
-
-/// print! 宏实现
-#[macro_export]
-macro_rules! print {
-    ($($arg:tt)*) => {{
-        use core::fmt::Write;
-        unsafe {
-            if let Some(ref mut uart) = $crate::CONSOLE {
-                let _ = write!(uart, $($arg)*);
-            }
-        }
-    }};
-}
-
-/// println! 宏实现
-#[macro_export]
-macro_rules! println {
-    () => ($crate::print!("\n"));
-    ($($arg:tt)*) => {{
-        $crate::print!($($arg)*);
-        $crate::print!("\n");
-    }};
-}
\ No newline at end of file
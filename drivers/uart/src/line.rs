@@ -0,0 +1,537 @@
+//! 规范输入模式 (行编辑)
+//!
+//! 在接收路径之上按行读取输入：回显字符，处理退格/删除、Ctrl-U 删除整行、
+//! Ctrl-C 放弃当前行，回车或换行结束一行 (CRLF 只算一次)。
+//! 只接受可打印 ASCII 字符，其他控制字符和非 ASCII 字节被忽略。
+//!
+//! # 历史与补全
+//! - `LineEditor<B, H>` 的 `H` 为历史缓冲区字节数 (默认 0，不记录历史)。
+//!   上/下方向键 (`ESC [ A`/`ESC [ B`) 在历史中切换，向下越过最新一条时清空当前行
+//! - Ctrl-R 增量搜索历史：输入的字符组成关键字，再按 Ctrl-R 找更早的匹配，
+//!   回车执行匹配行，其他控制键取出匹配行继续编辑，Ctrl-G 放弃搜索
+//! - Tab 补全第一个词：候选由 [`Complete`] 提供 (Shell 提供命令名)。
+//!   唯一匹配时补全并加空格，多个匹配时补全公共前缀，无法再补全时列出候选并返回
+//!   [`LineEvent::Redraw`]。参数 (文件路径等) 暂不补全
+//!
+//! [`LineEditor`] 只处理编辑逻辑，输入来源和回显目标由调用者决定 (Shell 从所有控制台读取)；
+//! [`Uart::read_line`] 是在单个串口上阻塞读取一行的简单用法。
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::{Uart, UART2_BASE};
+//!
+//! let uart = Uart::new(UART2_BASE);
+//! uart.init(1_500_000).unwrap();
+//!
+//! let mut buf = [0u8; 64];
+//! uart.puts("name: ");
+//! if let Ok(name) = uart.read_line(&mut buf) {
+//!     uart.puts(name);
+//! }
+//! ```
+
+use core::fmt;
+use core::sync::atomic::Ordering;
+
+use crate::Uart;
+
+/// 退格 (Ctrl-H)
+const BS: u8 = 0x08;
+/// 删除 (大多数终端的退格键)
+const DEL: u8 = 0x7F;
+/// 放弃当前行
+const CTRL_C: u8 = 0x03;
+/// 放弃历史搜索
+const CTRL_G: u8 = 0x07;
+/// 增量搜索历史
+const CTRL_R: u8 = 0x12;
+/// 删除整行
+const CTRL_U: u8 = 0x15;
+/// 补全
+const TAB: u8 = 0x09;
+/// 转义序列开始 (方向键)
+const ESC: u8 = 0x1B;
+/// 行满时响铃
+const BEL: char = '\x07';
+
+/// 回显时擦除一个字符
+const ERASE: &str = "\x08 \x08";
+
+/// 历史搜索关键字的最大长度
+const SEARCH_MAX: usize = 32;
+/// 搜索时在关键字前后显示的字符数: `(search)'` 和 `': `
+const SEARCH_DECOR: usize = 12;
+
+/// 输入一个字节后的编辑状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEvent {
+    /// 行尚未结束
+    Pending,
+    /// 收到回车或换行，可以用 [`LineEditor::line`] 取出这一行
+    Done,
+    /// 收到 Ctrl-C，当前行已清空
+    Interrupted,
+    /// Tab 列出了补全候选，调用者应重新输出提示符和 [`LineEditor::line`]
+    Redraw,
+}
+
+/// 读取被 Ctrl-C 中断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupted;
+
+/// Tab 补全的候选来源
+pub trait Complete {
+    /// 对每个候选调用一次 `candidate`，不需要预先按前缀过滤
+    fn candidates(&self, candidate: &mut dyn FnMut(&str));
+}
+
+/// 没有补全候选
+pub struct NoCompletion;
+
+impl Complete for NoCompletion {
+    fn candidates(&self, _candidate: &mut dyn FnMut(&str)) {}
+}
+
+/// 方向键转义序列的解析状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// 收到 ESC
+    Esc,
+    /// 收到 `ESC [` 或 `ESC O`，等待结束字节
+    Csi,
+}
+
+/// 命令历史
+///
+/// 按输入顺序存放，每行以 0 结尾 (行中只有可打印字符)；空间不足时丢弃最旧的行
+struct History<const N: usize> {
+    data: [u8; N],
+    used: usize,
+}
+
+impl<const N: usize> History<N> {
+    const fn new() -> Self {
+        Self { data: [0; N], used: 0 }
+    }
+
+    /// 记录一行，空行和与最近一条相同的行不记录
+    fn push(&mut self, line: &[u8]) {
+        let need = line.len() + 1;
+        if line.is_empty() || need > N || self.get(1) == Some(line) {
+            return;
+        }
+        while N - self.used < need {
+            let oldest = self.data[..self.used].iter().position(|&b| b == 0).map_or(self.used, |i| i + 1);
+            self.data.copy_within(oldest..self.used, 0);
+            self.used -= oldest;
+        }
+        self.data[self.used..self.used + line.len()].copy_from_slice(line);
+        self.data[self.used + line.len()] = 0;
+        self.used += need;
+    }
+
+    /// 倒数第 `n` 条 (1 为最近一条)
+    fn get(&self, n: usize) -> Option<&[u8]> {
+        let mut end = self.used;
+        for _ in 1..n {
+            end = self.start_of(end)?;
+        }
+        let start = self.start_of(end)?;
+        Some(&self.data[start..end - 1])
+    }
+
+    /// 以 `end` (结尾 0 之后) 结束的那一行的起始位置
+    fn start_of(&self, end: usize) -> Option<usize> {
+        if end == 0 {
+            return None;
+        }
+        Some(self.data[..end - 1].iter().rposition(|&b| b == 0).map_or(0, |i| i + 1))
+    }
+
+    /// 从倒数第 `from` 条开始向更早查找包含 `query` 的行
+    fn find(&self, query: &[u8], from: usize) -> Option<usize> {
+        (from.max(1)..)
+            .map_while(|n| self.get(n).map(|line| (n, line)))
+            .find(|(_, line)| query.is_empty() || line.windows(query.len()).any(|w| w == query))
+            .map(|(n, _)| n)
+    }
+}
+
+/// 增量搜索状态
+struct Search {
+    query: [u8; SEARCH_MAX],
+    len: usize,
+    /// 当前匹配的历史条目 (倒数第几条)，0 表示没有匹配
+    hit: usize,
+    /// 搜索提示占用的字符数 (用于擦除)
+    shown: usize,
+}
+
+/// 行编辑器
+///
+/// `B` 为行缓冲区 (`[u8; N]` 或 `&mut [u8]`)，行长度不超过缓冲区长度；
+/// `H` 为历史缓冲区字节数，0 表示不记录历史
+pub struct LineEditor<B, const H: usize = 0> {
+    buf: B,
+    len: usize,
+    /// 上一个字节是 `\r`，用于忽略 CRLF 中的 `\n`
+    last_cr: bool,
+    escape: Escape,
+    history: History<H>,
+    /// 正在浏览的历史条目 (倒数第几条)，0 表示在编辑新行
+    recall: usize,
+    search: Option<Search>,
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>> LineEditor<B> {
+    /// 不记录历史的行编辑器
+    pub const fn new(buf: B) -> Self {
+        Self::with_history(buf)
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, const H: usize> LineEditor<B, H> {
+    /// 带 `H` 字节历史缓冲区的行编辑器
+    pub const fn with_history(buf: B) -> Self {
+        Self {
+            buf,
+            len: 0,
+            last_cr: false,
+            escape: Escape::None,
+            history: History::new(),
+            recall: 0,
+            search: None,
+        }
+    }
+
+    /// 处理一个输入字节，回显写到 `echo` (回显失败不影响编辑)，Tab 不补全
+    ///
+    /// # 示例
+    /// ```
+    /// use uart::line::{LineEditor, LineEvent};
+    ///
+    /// let mut editor = LineEditor::new([0u8; 16]);
+    /// let mut echo = String::new();
+    /// for &b in b"ab\x7fc" {
+    ///     assert_eq!(editor.feed(b, &mut echo), LineEvent::Pending);
+    /// }
+    /// assert_eq!(editor.feed(b'\r', &mut echo), LineEvent::Done);
+    /// assert_eq!(editor.line(), "ac");
+    /// assert_eq!(echo, "ab\x08 \x08c\n");
+    /// ```
+    pub fn feed(&mut self, byte: u8, echo: &mut dyn fmt::Write) -> LineEvent {
+        self.feed_with(byte, echo, &NoCompletion)
+    }
+
+    /// 处理一个输入字节，Tab 从 `complete` 中补全第一个词
+    ///
+    /// # 示例
+    /// ```
+    /// use uart::line::{Complete, LineEditor, LineEvent};
+    ///
+    /// struct Commands;
+    ///
+    /// impl Complete for Commands {
+    ///     fn candidates(&self, candidate: &mut dyn FnMut(&str)) {
+    ///         ["help", "history", "reboot"].into_iter().for_each(candidate);
+    ///     }
+    /// }
+    ///
+    /// let mut editor = LineEditor::<_, 64>::with_history([0u8; 16]);
+    /// let mut echo = String::new();
+    /// for &b in b"re\t" {
+    ///     editor.feed_with(b, &mut echo, &Commands);
+    /// }
+    /// assert_eq!(editor.line(), "reboot ");
+    /// ```
+    pub fn feed_with(&mut self, byte: u8, echo: &mut dyn fmt::Write, complete: &dyn Complete) -> LineEvent {
+        let after_cr = core::mem::replace(&mut self.last_cr, byte == b'\r');
+        if self.escape != Escape::None {
+            self.feed_escape(byte, echo);
+            return LineEvent::Pending;
+        }
+        if self.search.is_some() && self.feed_search(byte, echo) {
+            return LineEvent::Pending;
+        }
+
+        match byte {
+            b'\n' if after_cr => {}
+            b'\r' | b'\n' => {
+                let _ = echo.write_char('\n');
+                self.history.push(&self.buf.as_ref()[..self.len]);
+                self.recall = 0;
+                return LineEvent::Done;
+            }
+            BS | DEL if self.len > 0 => {
+                self.len -= 1;
+                let _ = echo.write_str(ERASE);
+            }
+            CTRL_U => self.set_line(&[], echo),
+            CTRL_C => {
+                self.len = 0;
+                self.recall = 0;
+                let _ = echo.write_str("^C\n");
+                return LineEvent::Interrupted;
+            }
+            ESC => self.escape = Escape::Esc,
+            TAB => return self.complete(echo, complete),
+            CTRL_R if H > 0 => {
+                self.set_line(&[], echo);
+                self.search = Some(Search {
+                    query: [0; SEARCH_MAX],
+                    len: 0,
+                    hit: 0,
+                    shown: 0,
+                });
+                self.search_update(self.recall + 1, echo);
+            }
+            0x20..=0x7E if self.len < self.buf.as_ref().len() => {
+                self.buf.as_mut()[self.len] = byte;
+                self.len += 1;
+                let _ = echo.write_char(byte as char);
+            }
+            0x20..=0x7E => {
+                let _ = echo.write_char(BEL);
+            }
+            _ => {}
+        }
+        LineEvent::Pending
+    }
+
+    /// 当前行内容 (不含换行)
+    pub fn line(&self) -> &str {
+        // 只存入了可打印 ASCII 字符
+        core::str::from_utf8(&self.buf.as_ref()[..self.len]).unwrap_or("")
+    }
+
+    /// 当前行长度
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 当前行是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 清空当前行，开始下一行 (历史保留)
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.recall = 0;
+    }
+
+    /// 擦除回显中的当前行，换成 `line` (超出缓冲区的部分截断)
+    fn set_line(&mut self, line: &[u8], echo: &mut dyn fmt::Write) {
+        erase(self.len, echo);
+        self.len = load(self.buf.as_mut(), line, echo);
+    }
+
+    /// 方向键：上/下在历史中切换，其他忽略
+    fn feed_escape(&mut self, byte: u8, echo: &mut dyn fmt::Write) {
+        self.escape = match (self.escape, byte) {
+            (Escape::Esc, b'[' | b'O') => Escape::Csi,
+            // 参数字节 (例如 `ESC [ 1 ; 5 A`)
+            (Escape::Csi, 0x20..=0x3F) => Escape::Csi,
+            (Escape::Csi, b'A') => {
+                self.recall(self.recall + 1, echo);
+                Escape::None
+            }
+            (Escape::Csi, b'B') => {
+                self.recall(self.recall.saturating_sub(1), echo);
+                Escape::None
+            }
+            _ => Escape::None,
+        };
+    }
+
+    /// 切换到倒数第 `n` 条历史，0 为空的新行；没有这一条时响铃
+    fn recall(&mut self, n: usize, echo: &mut dyn fmt::Write) {
+        let entry = if n == 0 { Some(&[][..]) } else { self.history.get(n) };
+        let Some(entry) = entry.filter(|_| n != self.recall) else {
+            let _ = echo.write_char(BEL);
+            return;
+        };
+        erase(self.len, echo);
+        self.len = load(self.buf.as_mut(), entry, echo);
+        self.recall = n;
+    }
+
+    /// 处理搜索中的一个字节，返回 `false` 表示搜索已结束、字节按普通输入处理
+    fn feed_search(&mut self, byte: u8, echo: &mut dyn fmt::Write) -> bool {
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        match byte {
+            0x20..=0x7E if search.len < SEARCH_MAX => {
+                search.query[search.len] = byte;
+                search.len += 1;
+                let from = search.hit;
+                self.search_update(from, echo);
+            }
+            0x20..=0x7E => {
+                let _ = echo.write_char(BEL);
+            }
+            BS | DEL => {
+                search.len = search.len.saturating_sub(1);
+                self.search_update(1, echo);
+            }
+            CTRL_R => {
+                let from = search.hit + 1;
+                self.search_update(from, echo);
+            }
+            CTRL_G => {
+                let shown = search.shown;
+                self.search = None;
+                erase(shown, echo);
+            }
+            _ => {
+                // 取出匹配行继续处理这个字节 (回车执行、方向键切换等)
+                let (shown, hit) = (search.shown, search.hit);
+                self.search = None;
+                erase(shown, echo);
+                self.recall = hit;
+                self.len = load(self.buf.as_mut(), self.history.get(hit).unwrap_or(&[]), echo);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 从倒数第 `from` 条开始查找关键字并重新显示搜索提示
+    ///
+    /// 找不到时响铃；Ctrl-R 找不到更早的匹配时保留当前匹配
+    fn search_update(&mut self, from: usize, echo: &mut dyn fmt::Write) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let query = &search.query[..search.len];
+        match self.history.find(query, from) {
+            Some(hit) => search.hit = hit,
+            None => {
+                let _ = echo.write_char(BEL);
+                if from != search.hit + 1 {
+                    search.hit = 0;
+                }
+            }
+        }
+
+        erase(search.shown, echo);
+        let entry = self.history.get(search.hit).unwrap_or(&[]);
+        let query = core::str::from_utf8(query).unwrap_or("");
+        let entry_str = core::str::from_utf8(entry).unwrap_or("");
+        let _ = write!(echo, "(search)'{}': {}", query, entry_str);
+        search.shown = SEARCH_DECOR + query.len() + entry.len();
+    }
+
+    /// Tab 补全第一个词
+    fn complete(&mut self, echo: &mut dyn fmt::Write, complete: &dyn Complete) -> LineEvent {
+        let len = self.len;
+        let buf = self.buf.as_mut();
+        if buf[..len].contains(&b' ') {
+            // 参数暂不补全
+            let _ = echo.write_char(BEL);
+            return LineEvent::Pending;
+        }
+
+        // 第一个匹配复制到行尾之后，再逐个缩短到公共前缀
+        let mut matches = 0;
+        let mut common = len;
+        complete.candidates(&mut |candidate| {
+            let candidate = candidate.as_bytes();
+            if !candidate.starts_with(&buf[..len]) {
+                return;
+            }
+            let candidate = &candidate[..candidate.len().min(buf.len())];
+            matches += 1;
+            if matches == 1 {
+                buf[len..candidate.len()].copy_from_slice(&candidate[len..]);
+                common = candidate.len();
+            } else {
+                common = len + buf[len..common].iter().zip(&candidate[len..]).take_while(|(a, b)| a == b).count();
+            }
+        });
+
+        if matches == 0 {
+            let _ = echo.write_char(BEL);
+            return LineEvent::Pending;
+        }
+        if common > len || matches == 1 {
+            let _ = echo.write_str(core::str::from_utf8(&buf[len..common]).unwrap_or(""));
+            self.len = common;
+            if matches == 1 && self.len < buf.len() {
+                buf[self.len] = b' ';
+                self.len += 1;
+                let _ = echo.write_char(' ');
+            }
+            return LineEvent::Pending;
+        }
+
+        // 无法再补全：列出候选
+        let _ = echo.write_char('\n');
+        let prefix = &buf[..len];
+        complete.candidates(&mut |candidate| {
+            if candidate.as_bytes().starts_with(prefix) {
+                let _ = write!(echo, "{}  ", candidate);
+            }
+        });
+        let _ = echo.write_char('\n');
+        LineEvent::Redraw
+    }
+}
+
+/// 把 `line` 复制到行缓冲区并回显，返回复制的长度 (超出缓冲区的部分截断)
+fn load(buf: &mut [u8], line: &[u8], echo: &mut dyn fmt::Write) -> usize {
+    let len = line.len().min(buf.len());
+    buf[..len].copy_from_slice(&line[..len]);
+    let _ = echo.write_str(core::str::from_utf8(&buf[..len]).unwrap_or(""));
+    len
+}
+
+/// 擦除 `count` 个回显字符
+fn erase(count: usize, echo: &mut dyn fmt::Write) {
+    for _ in 0..count {
+        let _ = echo.write_str(ERASE);
+    }
+}
+
+/// 回显到串口
+struct Echo<'a>(&'a Uart);
+
+impl fmt::Write for Echo<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.puts(s);
+        Ok(())
+    }
+}
+
+impl Uart {
+    /// 阻塞读取一行，带回显和行编辑
+    ///
+    /// 以 CRLF 结束的行，`\n` 留到下一次调用时丢弃
+    ///
+    /// # 参数
+    /// - `buf`: 行缓冲区，行满后的输入被忽略 (响铃)
+    ///
+    /// # 返回值
+    /// - `Ok(line)`: 读到的一行 (不含换行)
+    /// - `Err(Interrupted)`: 收到 Ctrl-C
+    pub fn read_line<'a>(&self, buf: &'a mut [u8]) -> Result<&'a str, Interrupted> {
+        let mut editor = LineEditor::new(&mut *buf);
+        editor.last_cr = self.line_cr.load(Ordering::Relaxed);
+        let mut echo = Echo(self);
+        let event = loop {
+            match editor.feed(self.getc_blocking(), &mut echo) {
+                // 没有补全，不会要求重绘
+                LineEvent::Pending | LineEvent::Redraw => {}
+                event => break event,
+            }
+        };
+        self.line_cr.store(editor.last_cr, Ordering::Relaxed);
+        if event == LineEvent::Interrupted {
+            return Err(Interrupted);
+        }
+        let len = editor.len();
+        Ok(core::str::from_utf8(&buf[..len]).unwrap_or(""))
+    }
+}
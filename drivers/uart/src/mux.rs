@@ -0,0 +1,296 @@
+//! UART 多路复用调试通道
+//!
+//! 在一个串口上同时承载交互式 Shell、二进制跟踪数据和文件传输等多个逻辑流。
+//! 每个逻辑流是一个通道 (channel)，数据按帧发送，帧之间用 `0x00` 分隔。
+//!
+//! # 帧格式（主机端协议说明）
+//! ```text
+//! 编码前:  | channel (1B) | payload (0..N B) |
+//! 线路上:  | COBS(编码前的数据) | 0x00 |
+//! ```
+//! - 使用 COBS (Consistent Overhead Byte Stuffing) 编码，编码后的数据中不含 `0x00`，
+//!   因此 `0x00` 可作为唯一的帧分隔符，接收端可在任意位置重新同步
+//! - 每 254 字节最多增加 1 字节开销
+//! - `channel` 取值见 [`Channel`]，`0x10` 以上留给应用自定义
+//! - 帧内不带校验，链路错误会表现为 COBS 解码失败或通道号无效，接收端直接丢弃该帧
+//! - 同一串口上的帧整帧发送 (持有该串口的多路复用锁)，不同通道同时发送时帧不会交错；
+//!   一次 `write!`/`writeln!` 是一帧
+//!
+//! 主机端解码流程：
+//! 1. 按 `0x00` 切分字节流
+//! 2. 对每段做 COBS 解码，失败则丢弃
+//! 3. 第 1 字节为通道号，其余为负载
+//!
+//! # 参考资料
+//! - S. Cheshire, M. Baker, "Consistent Overhead Byte Stuffing", IEEE/ACM ToN 1999
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::{Uart, UART2_BASE};
+//! use uart::mux::{Channel, Mux};
+//! use core::fmt::Write;
+//!
+//! let uart = Uart::new(UART2_BASE);
+//! uart.init(1_500_000).unwrap();
+//!
+//! let mux = Mux::new(&uart);
+//! writeln!(mux.channel(Channel::Log), "boot ok").unwrap();
+//! mux.send(Channel::Data, &[0x00, 0x01, 0x02]);
+//! ```
+//!
+//! # 注意
+//! - 发送期间持有的是普通自旋锁，不要在中断处理函数中发送 (可能与被打断的发送者死锁)
+//! - 同一串口上不经过多路复用直接输出的数据 (例如 `println!`) 仍会插进帧中间，
+//!   使用多路复用时所有输出都应经过 [`Mux`]
+
+use core::fmt;
+
+use crate::Uart;
+
+/// 帧分隔符
+pub const FRAME_DELIMITER: u8 = 0x00;
+
+/// 预定义的逻辑通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// 交互式控制台 (Shell 输入输出)
+    Console,
+    /// 文本日志
+    Log,
+    /// 二进制数据 (跟踪、文件传输)
+    Data,
+    /// 应用自定义通道 (建议使用 0x10 以上)
+    Custom(u8),
+}
+
+impl Channel {
+    /// 通道号
+    pub const fn id(self) -> u8 {
+        match self {
+            Channel::Console => 0x00,
+            Channel::Log => 0x01,
+            Channel::Data => 0x02,
+            Channel::Custom(id) => id,
+        }
+    }
+
+    /// 由通道号得到通道
+    pub const fn from_id(id: u8) -> Self {
+        match id {
+            0x00 => Channel::Console,
+            0x01 => Channel::Log,
+            0x02 => Channel::Data,
+            id => Channel::Custom(id),
+        }
+    }
+}
+
+/// 流式 COBS 编码器
+///
+/// 按 254 字节为一块缓存数据，块满或遇到 `0x00` 时输出，
+/// 因此发送任意长度的负载只需要 254 字节栈空间。
+struct CobsEncoder<'a> {
+    uart: &'a Uart,
+    block: [u8; 254],
+    len: usize,
+}
+
+impl<'a> CobsEncoder<'a> {
+    fn new(uart: &'a Uart) -> Self {
+        Self { uart, block: [0; 254], len: 0 }
+    }
+
+    fn flush_block(&mut self) {
+        self.uart.putc(self.len as u8 + 1);
+        for &b in &self.block[..self.len] {
+            self.uart.putc(b);
+        }
+        self.len = 0;
+    }
+
+    fn push(&mut self, byte: u8) {
+        if byte == 0 {
+            self.flush_block();
+            return;
+        }
+        self.block[self.len] = byte;
+        self.len += 1;
+        if self.len == self.block.len() {
+            // 满块的长度码为 0xFF，表示后面没有隐含的 0x00
+            self.flush_block();
+        }
+    }
+
+    fn finish(mut self) {
+        self.flush_block();
+        self.uart.putc(FRAME_DELIMITER);
+    }
+}
+
+impl fmt::Write for CobsEncoder<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for b in s.bytes() {
+            self.push(b);
+        }
+        Ok(())
+    }
+}
+
+/// 多路复用发送端
+pub struct Mux<'a> {
+    uart: &'a Uart,
+}
+
+impl<'a> Mux<'a> {
+    /// 在指定 UART 上创建多路复用器
+    ///
+    /// UART 需要已经初始化，建议使用较高波特率 (例如 1500000)
+    pub const fn new(uart: &'a Uart) -> Self {
+        Self { uart }
+    }
+
+    /// 在指定通道上发送一帧
+    pub fn send(&self, channel: Channel, payload: &[u8]) {
+        let _frame = self.uart.mux_tx.lock();
+        let mut enc = CobsEncoder::new(self.uart);
+        enc.push(channel.id());
+        for &b in payload {
+            enc.push(b);
+        }
+        enc.finish();
+    }
+
+    /// 把格式化输出作为一帧发送
+    ///
+    /// 边格式化边编码，不需要整帧大小的缓冲区。格式化出错时已输出的部分仍作为一帧结束。
+    pub fn send_fmt(&self, channel: Channel, args: fmt::Arguments) -> fmt::Result {
+        let _frame = self.uart.mux_tx.lock();
+        let mut enc = CobsEncoder::new(self.uart);
+        enc.push(channel.id());
+        let result = fmt::write(&mut enc, args);
+        enc.finish();
+        result
+    }
+
+    /// 获取某个通道的文本写入器
+    ///
+    /// 每次 `write!`/`writeln!` 发送一帧 (见 [`send_fmt`](Self::send_fmt))，
+    /// 直接调用 `write_str` 时每次调用一帧
+    pub fn channel(&self, channel: Channel) -> ChannelWriter<'a> {
+        ChannelWriter { uart: self.uart, channel }
+    }
+}
+
+/// 单个通道的文本写入器
+pub struct ChannelWriter<'a> {
+    uart: &'a Uart,
+    channel: Channel,
+}
+
+impl fmt::Write for ChannelWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Mux::new(self.uart).send(self.channel, s.as_bytes());
+        Ok(())
+    }
+
+    /// 整个 `write!`/`writeln!` 作为一帧，而不是每个格式化片段一帧
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        Mux::new(self.uart).send_fmt(self.channel, args)
+    }
+}
+
+/// 帧解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// 帧长度超出接收缓冲区，已丢弃
+    Overflow,
+    /// COBS 编码无效
+    InvalidEncoding,
+    /// 空帧 (缺少通道号)
+    Empty,
+}
+
+/// 多路复用接收端帧解码器
+///
+/// 逐字节喂入串口收到的数据，遇到帧分隔符时返回解码后的帧。
+/// `N` 为单帧编码后的最大长度。
+pub struct FrameDecoder<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    overflow: bool,
+}
+
+impl<const N: usize> FrameDecoder<N> {
+    /// 创建解码器
+    pub const fn new() -> Self {
+        Self { buf: [0; N], len: 0, overflow: false }
+    }
+
+    /// 喂入一个字节
+    ///
+    /// # 返回值
+    /// - `None`: 帧尚未结束
+    /// - `Some(Ok((channel, payload)))`: 收到完整的帧
+    /// - `Some(Err(e))`: 帧无效，已丢弃
+    pub fn feed(&mut self, byte: u8) -> Option<Result<(Channel, &[u8]), FrameError>> {
+        if byte != FRAME_DELIMITER {
+            if self.len < N {
+                self.buf[self.len] = byte;
+                self.len += 1;
+            } else {
+                self.overflow = true;
+            }
+            return None;
+        }
+
+        let len = self.len;
+        let overflow = self.overflow;
+        self.len = 0;
+        self.overflow = false;
+
+        if overflow {
+            return Some(Err(FrameError::Overflow));
+        }
+        Some(match cobs_decode_in_place(&mut self.buf[..len]) {
+            Some(0) => Err(FrameError::Empty),
+            Some(n) => Ok((Channel::from_id(self.buf[0]), &self.buf[1..n])),
+            None => Err(FrameError::InvalidEncoding),
+        })
+    }
+}
+
+impl<const N: usize> Default for FrameDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 原地 COBS 解码 (不含帧分隔符)
+///
+/// 解码后的数据不会比编码数据长，因此可以原地进行。
+/// 返回解码后的长度，编码无效时返回 `None`。
+fn cobs_decode_in_place(buf: &mut [u8]) -> Option<usize> {
+    let mut read = 0;
+    let mut write = 0;
+
+    while read < buf.len() {
+        let code = buf[read] as usize;
+        if code == 0 || read + code > buf.len() {
+            return None;
+        }
+        read += 1;
+
+        for _ in 1..code {
+            buf[write] = buf[read];
+            read += 1;
+            write += 1;
+        }
+
+        if code != 0xFF && read < buf.len() {
+            buf[write] = 0;
+            write += 1;
+        }
+    }
+
+    Some(write)
+}
@@ -0,0 +1,105 @@
+//! 挂起/恢复时的寄存器保存与恢复
+//!
+//! 挂起到内存 (suspend-to-RAM) 时 UART 所在的电源域断电，寄存器全部复位。
+//! 挂起前调用 [`Uart::save_context`] 保存线路配置，唤醒后调用
+//! [`Uart::restore_context`] 重新写入，不需要重新计算波特率。
+//!
+//! # 注意
+//! - FCR 只写，FIFO 使能状态从 IIR[7:6] 读出；FIFO 中的数据不保存，恢复时 FIFO 被复位
+//! - 接收/发送缓冲区在内存中，不受断电影响；恢复 IER 后中断收发继续进行
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::{Uart, UART3_BASE};
+//!
+//! let uart = Uart::new(UART3_BASE);
+//! uart.init(115200).unwrap();
+//!
+//! let ctx = uart.save_context().unwrap();
+//! // ... 挂起，电源域断电 ...
+//! uart.restore_context(&ctx).unwrap();
+//! ```
+
+use mmio::{read_volatile, write_volatile};
+
+use crate::{
+    Uart, UartError, FCR_FIFO_EN, FCR_RX_FIFO_RST, FCR_TX_FIFO_RST, LCR_DLAB, UART_DLF,
+    UART_DLH, UART_DLL, UART_FCR, UART_IER, UART_IIR, UART_LCR, UART_MCR,
+};
+
+/// IIR[7:6]: FIFO 已使能
+const IIR_FIFO_ENABLED: u32 = 0xC0;
+
+/// 保存的 UART 寄存器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UartContext {
+    dll: u32,
+    dlh: u32,
+    /// 小数分频，控制器没有 DLF 时为 0
+    dlf: u32,
+    lcr: u32,
+    mcr: u32,
+    fcr: u32,
+    ier: u32,
+}
+
+impl Uart {
+    /// 保存 DLL/DLH/DLF/LCR/MCR/FCR/IER
+    ///
+    /// 读取分频器需要短暂置位 DLAB，应在停止收发后调用
+    ///
+    /// # 返回值
+    /// - `Ok(UartContext)`: 保存的寄存器
+    /// - `Err(UartError::LcrBusy)`: 控制器持续忙，无法置位或清除 DLAB，
+    ///   此时 LCR 可能停留在 DLAB=1
+    pub fn save_context(&self) -> Result<UartContext, UartError> {
+        unsafe {
+            let lcr = read_volatile((self.base + UART_LCR) as *const u32) & 0xFF;
+            let mcr = read_volatile((self.base + UART_MCR) as *const u32);
+            let ier = read_volatile((self.base + UART_IER) as *const u32);
+            let fcr = if read_volatile((self.base + UART_IIR) as *const u32) & IIR_FIFO_ENABLED != 0 {
+                FCR_FIFO_EN
+            } else {
+                0
+            };
+
+            self.wait_not_busy();
+            self.write_lcr(lcr | LCR_DLAB)?;
+            let dll = read_volatile((self.base + UART_DLL) as *const u32) & 0xFF;
+            let dlh = read_volatile((self.base + UART_DLH) as *const u32) & 0xFF;
+            self.write_lcr(lcr)?;
+            let dlf = read_volatile((self.base + UART_DLF) as *const u32);
+
+            Ok(UartContext { dll, dlh, dlf, lcr, mcr, fcr, ier })
+        }
+    }
+
+    /// 按 [`save_context`](Self::save_context) 的结果重新配置控制器
+    ///
+    /// 顺序: 关中断 → 分频器 → LCR → FCR (复位 FIFO) → MCR → IER
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 恢复完成
+    /// - `Err(UartError::LcrBusy)`: 控制器持续忙，LCR 写入没有生效，
+    ///   此时中断保持关闭，不写 FCR/MCR/IER
+    pub fn restore_context(&self, ctx: &UartContext) -> Result<(), UartError> {
+        unsafe {
+            write_volatile((self.base + UART_IER) as *mut u32, 0);
+
+            self.wait_not_busy();
+            self.write_lcr(ctx.lcr | LCR_DLAB)?;
+            write_volatile((self.base + UART_DLL) as *mut u32, ctx.dll);
+            write_volatile((self.base + UART_DLH) as *mut u32, ctx.dlh);
+            write_volatile((self.base + UART_DLF) as *mut u32, ctx.dlf);
+            self.write_lcr(ctx.lcr)?;
+
+            write_volatile(
+                (self.base + UART_FCR) as *mut u32,
+                ctx.fcr | FCR_RX_FIFO_RST | FCR_TX_FIFO_RST,
+            );
+            write_volatile((self.base + UART_MCR) as *mut u32, ctx.mcr);
+            write_volatile((self.base + UART_IER) as *mut u32, ctx.ier);
+        }
+        Ok(())
+    }
+}
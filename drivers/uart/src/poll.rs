@@ -0,0 +1,118 @@
+//! poll_loop 适配：轮询搬运 RX FIFO
+//!
+//! 没有接入中断的串口注册到这里后，由 [`poll_loop::run_once`] 把 RX FIFO 中的数据
+//! 搬到接收缓冲区 (与 [`Uart::on_irq`] 的接收路径相同)。主循环忙于其他工作时，
+//! 只要空闲时调用 `run_once`，FIFO 就不会溢出；应用照常用
+//! [`Uart::try_read`]/[`Uart::read`] 读取。
+//!
+//! 所有串口共用一个名为 [`POLLER_NAME`] 的轮询回调。每次调用至少搬运一个串口，
+//! 时间片用完时返回 [`PollStatus::Busy`]，下次从没轮到的串口继续。
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::{Uart, UART3_BASE};
+//!
+//! static GPS: Uart = Uart::new(UART3_BASE);
+//!
+//! GPS.init(9600).unwrap();
+//! uart::poll::add_rx_drain(&GPS).unwrap();
+//! loop {
+//!     poll_loop::run_once();
+//!     while let Some(byte) = GPS.try_read() {
+//!         // 解析 NMEA 语句
+//!         let _ = byte;
+//!     }
+//! }
+//! ```
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use poll_loop::{Budget, PollStatus, RegisterError};
+use spinlock::SpinLock;
+
+use crate::Uart;
+
+/// 在 poll_loop 中注册的回调名称
+pub const POLLER_NAME: &str = "uart-rx";
+
+/// 回调的时间片 (微秒)，115200 波特率下约一个字符时间
+pub const RX_DRAIN_SLICE_US: u32 = 100;
+
+/// 最多可注册的串口数量 (RK3588 共 10 个 UART)
+pub const MAX_RX_DRAIN: usize = 10;
+
+/// 已注册的串口
+static UARTS: SpinLock<[Option<&'static Uart>; MAX_RX_DRAIN]> = SpinLock::new([None; MAX_RX_DRAIN]);
+
+/// 下次调用从哪个槽位开始
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// 让 poll_loop 轮询搬运 `uart` 的 RX FIFO
+///
+/// 第一次调用时注册 [`POLLER_NAME`] 回调
+///
+/// # 返回值
+/// - `Ok(())`: 已加入
+/// - `Err(RegisterError::Duplicate)`: 同一基址的串口已经加入
+/// - `Err(RegisterError::Full)`: 串口表或 poll_loop 回调表已满
+pub fn add_rx_drain(uart: &'static Uart) -> Result<(), RegisterError> {
+    {
+        let mut uarts = UARTS.lock();
+        if uarts.iter().flatten().any(|u| u.base == uart.base) {
+            return Err(RegisterError::Duplicate);
+        }
+        let slot = uarts
+            .iter_mut()
+            .find(|u| u.is_none())
+            .ok_or(RegisterError::Full)?;
+        *slot = Some(uart);
+    }
+
+    match poll_loop::register(POLLER_NAME, RX_DRAIN_SLICE_US, drain) {
+        Ok(()) | Err(RegisterError::Duplicate) => Ok(()),
+        Err(e) => {
+            remove_rx_drain(uart);
+            Err(e)
+        }
+    }
+}
+
+/// 停止轮询搬运 `uart`
+///
+/// 回调保持注册，没有串口时直接返回 [`PollStatus::Idle`]
+///
+/// # 返回值
+/// 串口在表中并已移除时返回 `true`
+pub fn remove_rx_drain(uart: &Uart) -> bool {
+    let mut uarts = UARTS.lock();
+    match uarts
+        .iter_mut()
+        .find(|u| u.is_some_and(|u| u.base == uart.base))
+    {
+        Some(slot) => {
+            *slot = None;
+            true
+        }
+        None => false,
+    }
+}
+
+/// poll_loop 回调：从上次停下的槽位开始逐个搬运
+fn drain(budget: &Budget) -> PollStatus {
+    let uarts = *UARTS.lock();
+    let start = NEXT.load(Ordering::Relaxed);
+    let mut drained = false;
+    for k in 0..MAX_RX_DRAIN {
+        let i = (start + k) % MAX_RX_DRAIN;
+        let Some(uart) = uarts[i] else {
+            continue;
+        };
+        if drained && budget.expired() {
+            NEXT.store(i, Ordering::Relaxed);
+            return PollStatus::Busy;
+        }
+        uart.drain_rx_fifo();
+        drained = true;
+    }
+    PollStatus::Idle
+}
@@ -0,0 +1,62 @@
+//! UART 寄存器集合
+//!
+//! 供 `regdump` 调试输出使用，见 [`regset`]。
+//!
+//! # 注意
+//! - RBR/IIR/LSR/MSR 读取有副作用 (取走数据、清除中断或错误位)，不在集合中
+//! - 挂起/恢复不使用这个集合：分频器需要 DLAB=1 才能访问，FCR 只写，
+//!   恢复还要求先关中断、最后写 IER，见 [`pm`](crate::pm)
+
+use regset::{Access, Field, Register, RegisterSet};
+
+use crate::{UART_IER, UART_LCR, UART_MCR, UART_USR};
+
+/// 发送 FIFO 水位寄存器 (Designware 扩展)
+const UART_TFL: usize = 0x80;
+/// 接收 FIFO 水位寄存器 (Designware 扩展)
+const UART_RFL: usize = 0x84;
+
+const LCR_FIELDS: &[Field] = &[
+    Field::new("DLS", 0, 2),
+    Field::new("STOP", 2, 1),
+    Field::new("PEN", 3, 1),
+    Field::new("EPS", 4, 1),
+    Field::new("SP", 5, 1),
+    Field::new("BC", 6, 1),
+    Field::new("DLAB", 7, 1),
+];
+
+const MCR_FIELDS: &[Field] = &[
+    Field::new("DTR", 0, 1),
+    Field::new("RTS", 1, 1),
+    Field::new("OUT1", 2, 1),
+    Field::new("OUT2", 3, 1),
+    Field::new("LOOP", 4, 1),
+    Field::new("AFCE", 5, 1),
+];
+
+const IER_FIELDS: &[Field] = &[
+    Field::new("ERBFI", 0, 1),
+    Field::new("ETBEI", 1, 1),
+    Field::new("ELSI", 2, 1),
+    Field::new("EDSSI", 3, 1),
+    Field::new("PTIME", 7, 1),
+];
+
+const USR_FIELDS: &[Field] = &[
+    Field::new("BUSY", 0, 1),
+    Field::new("TFNF", 1, 1),
+    Field::new("TFE", 2, 1),
+    Field::new("RFNE", 3, 1),
+    Field::new("RFF", 4, 1),
+];
+
+/// UART 关键寄存器
+pub const UART_REGS: RegisterSet = RegisterSet::new("uart", &[
+    Register::new("LCR", UART_LCR, Access::ReadWrite, LCR_FIELDS),
+    Register::new("MCR", UART_MCR, Access::ReadWrite, MCR_FIELDS),
+    Register::new("IER", UART_IER, Access::ReadWrite, IER_FIELDS),
+    Register::new("USR", UART_USR, Access::ReadOnly, USR_FIELDS),
+    Register::new("TFL", UART_TFL, Access::ReadOnly, &[]),
+    Register::new("RFL", UART_RFL, Access::ReadOnly, &[]),
+]);
@@ -0,0 +1,108 @@
+//! 单生产者单消费者无锁环形缓冲区
+//!
+//! 用于中断处理函数与普通代码之间传递数据：
+//! 一端只调用 `push`，另一端只调用 `pop`，生产者和消费者之间不需要关中断或加锁。
+//!
+//! `push`/`pop` 是 `unsafe fn`：同一时刻最多一个生产者、一个消费者由调用者保证。
+//! 可能有多个生产者 (或消费者) 时，调用者需要用各自一侧的锁把它们串行化，
+//! 例如 [`Uart`](crate::Uart) 用 `tx_push`/`tx_pop` 等锁保护发送和接收缓冲区的两端。
+//!
+//! # 实现
+//! - `head` 只由生产者写，`tail` 只由消费者写
+//! - 容量 `N` 必须是 2 的幂，索引自由递增，通过掩码取模
+//! - 实际可存放 `N` 个字节
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// SPSC 字节环形缓冲区
+pub struct RingBuffer<const N: usize> {
+    buf: UnsafeCell<[u8; N]>,
+    /// 下一个写入位置 (生产者)
+    head: AtomicUsize,
+    /// 下一个读取位置 (消费者)
+    tail: AtomicUsize,
+}
+
+// push/pop 的调用约定保证最多一个生产者和一个消费者，二者各自只访问自己拥有的槽位，
+// 由 head/tail 的 Acquire/Release 同步
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    const MASK: usize = {
+        assert!(N.is_power_of_two(), "Ring buffer size must be a power of two");
+        N - 1
+    };
+
+    /// 创建空缓冲区
+    pub const fn new() -> Self {
+        Self {
+            buf: UnsafeCell::new([0; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// 写入一个字节 (仅生产者调用)
+    ///
+    /// # 返回值
+    /// 缓冲区已满时返回 `false`，字节被丢弃
+    ///
+    /// # Safety
+    /// 不能与另一个 `push` 同时执行 (包括其他核和中断处理函数中的调用)
+    pub unsafe fn push(&self, byte: u8) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head.wrapping_sub(tail) >= N {
+            return false;
+        }
+        unsafe {
+            (*self.buf.get())[head & Self::MASK] = byte;
+        }
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// 取出一个字节 (仅消费者调用)
+    ///
+    /// # Safety
+    /// 不能与另一个 `pop` 同时执行 (包括其他核和中断处理函数中的调用)
+    pub unsafe fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let byte = unsafe { (*self.buf.get())[tail & Self::MASK] };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Some(byte)
+    }
+
+    /// 当前缓存的字节数
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        head.wrapping_sub(tail)
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 是否已满
+    pub fn is_full(&self) -> bool {
+        self.len() >= N
+    }
+
+    /// 容量
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,127 @@
+//! RS-485 半双工收发
+//!
+//! 用一个 GPIO 控制收发器的 DE/RE 方向引脚：
+//! 发送前置为发送方向，等待发送器完全空闲 (LSR.TEMT，最后一个停止位已移出) 后切回接收。
+//! 过早切回会截断最后一个字节，这是 RS-485 通信中最常见的问题。
+//!
+//! 收发器的 RE 没有单独控制时，本机发送的数据会被自己接收到 (回显)，
+//! 可以用 [`Rs485::discard_echo`] 在发送后丢弃这些字节。
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::{GpioBank, GpioPin};
+//! use uart::{Uart, UART3_BASE};
+//! use uart::rs485::Rs485;
+//!
+//! let uart = Uart::new(UART3_BASE);
+//! uart.init(9600).unwrap();
+//!
+//! let bus = Rs485::new(&uart, GpioPin::new(GpioBank::Gpio3, 12))
+//!     .unwrap()
+//!     .discard_echo(true);
+//! bus.write(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A]);
+//! let reply = bus.uart().getc_timeout(1_000_000);
+//! ```
+
+use gpio::claim::{self, PinClaim, PinConflict};
+use gpio::{GpioDirection, GpioLevel, GpioPin};
+
+use crate::Uart;
+
+/// 丢弃回显时每个字节的最长等待时间 (微秒，覆盖 1200 波特率下的一个字符)
+const ECHO_TIMEOUT_US: u32 = 10_000;
+
+/// RS-485 半双工端口
+pub struct Rs485<'a> {
+    uart: &'a Uart,
+    /// DE/RE 方向控制引脚
+    de: GpioPin,
+    /// DE 低电平有效
+    active_low: bool,
+    /// 发送后丢弃回显
+    discard_echo: bool,
+    _claim: PinClaim,
+}
+
+impl<'a> Rs485<'a> {
+    /// 创建 RS-485 端口，方向引脚设为输出并处于接收方向
+    ///
+    /// # 参数
+    /// - `uart`: 已初始化的 UART
+    /// - `de`: 收发器 DE/RE 引脚 (默认高电平为发送)，登记为 `rs485` 占用
+    ///
+    /// # 返回值
+    /// - `Ok(Rs485)`: 方向引脚已登记，实例丢弃时释放
+    /// - `Err(PinConflict)`: 方向引脚已被占用 (包括另一个 `Rs485`)，引脚配置不变
+    pub fn new(uart: &'a Uart, de: GpioPin) -> Result<Self, PinConflict> {
+        let claim = claim::claim_pin(&de, "rs485")?;
+        de.set_direction(GpioDirection::Output);
+        let port = Self {
+            uart,
+            de,
+            active_low: false,
+            discard_echo: false,
+            _claim: claim,
+        };
+        port.set_transmit(false);
+        Ok(port)
+    }
+
+    /// 设置 DE 为低电平有效
+    pub fn active_low(mut self) -> Self {
+        self.active_low = true;
+        self.set_transmit(false);
+        self
+    }
+
+    /// 设置发送后是否丢弃回显字节
+    pub fn discard_echo(mut self, enable: bool) -> Self {
+        self.discard_echo = enable;
+        self
+    }
+
+    /// 底层 UART，用于接收
+    pub fn uart(&self) -> &'a Uart {
+        self.uart
+    }
+
+    fn set_transmit(&self, transmit: bool) {
+        let high = transmit != self.active_low;
+        self.de.set_level(if high { GpioLevel::High } else { GpioLevel::Low });
+    }
+
+    /// 发送数据 (阻塞直到最后一个字节发送完成)
+    ///
+    /// 二进制透传，不做 `\n` → `\r\n` 转换
+    pub fn write(&self, data: &[u8]) {
+        self.transmit(data.len(), || {
+            for &byte in data {
+                self.uart.putc(byte);
+            }
+        });
+    }
+
+    /// 发送字符串 (`\n` 转换为 `\r\n`)
+    pub fn puts(&self, s: &str) {
+        let len = s.len() + s.bytes().filter(|&b| b == b'\n').count();
+        self.transmit(len, || self.uart.puts(s));
+    }
+
+    /// 切换到发送方向执行 `send`，等待发送完成后切回接收并丢弃 `len` 字节回显
+    fn transmit(&self, len: usize, send: impl FnOnce()) {
+        self.set_transmit(true);
+        send();
+        while self.uart.tx_pending() > 0 || !self.uart.is_tx_idle() {
+            core::hint::spin_loop();
+        }
+        self.set_transmit(false);
+
+        if self.discard_echo {
+            for _ in 0..len {
+                if self.uart.getc_timeout(ECHO_TIMEOUT_US).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! XMODEM 接收 (CRC16，128/1K 数据块)
+//!
+//! 通过串口接收二进制文件，用于在主机没有 SD 读卡器时更新内核镜像。
+//! 主机端可以用 `sx -k` (lrzsz)、minicom、Tera Term 等发送。
+//!
+//! # 协议
+//! ```text
+//! 接收方                     发送方
+//!   'C' (请求 CRC 模式)  →
+//!                       ←  SOH/STX | blk | 255-blk | 128/1024 字节 | CRC16 (高字节在前)
+//!   ACK / NAK           →
+//!   ...
+//!                       ←  EOT
+//!   ACK                 →
+//! ```
+//! - 数据块编号从 1 开始，模 256 递增；重复收到上一块 (ACK 丢失) 时再次 ACK 并丢弃
+//! - CRC 为 CRC-16/XMODEM (多项式 0x1021，初值 0)
+//! - 最后一块由发送方用 0x1A (SUB) 填充，接收长度是块大小的整数倍
+//! - 收到两个 CAN 表示发送方取消；接收方出错退出时同样发送 CAN
+//!
+//! # 注意
+//! - 只支持 CRC 模式，不支持校验和模式和 YMODEM 批量传输
+//! - 不能与 XON/XOFF 软件流控同时使用
+//! - 收发使用轮询方式，传输期间不要在该串口上输出日志
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::{Uart, UART2_BASE};
+//!
+//! let uart = Uart::new(UART2_BASE);
+//! uart.init(115200).unwrap();
+//!
+//! // 接收到内存
+//! let image = unsafe { core::slice::from_raw_parts_mut(0x0040_0000 as *mut u8, 32 << 20) };
+//! match uart::xmodem::receive(&uart, image) {
+//!     Ok(_len) => uart.puts("received\n"),
+//!     Err(_) => uart.puts("xmodem failed\n"),
+//! }
+//!
+//! // 逐块交给其他存储 (例如 SD 卡)
+//! let result = uart::xmodem::receive_with(&uart, |_block| {
+//!     // 写入失败时返回 false，传输被取消
+//!     true
+//! });
+//! ```
+
+use core::fmt;
+
+use crate::Uart;
+
+/// 128 字节数据块头
+const SOH: u8 = 0x01;
+/// 1024 字节数据块头
+const STX: u8 = 0x02;
+/// 传输结束
+const EOT: u8 = 0x04;
+/// 确认
+const ACK: u8 = 0x06;
+/// 否认，请求重发
+const NAK: u8 = 0x15;
+/// 取消
+const CAN: u8 = 0x18;
+/// 请求 CRC 模式
+const CRC_MODE: u8 = b'C';
+
+/// 数据块最大长度
+const BLOCK_1K: usize = 1024;
+
+/// 等待发送方开始时发送 'C' 的次数
+pub const START_RETRIES: u32 = 60;
+/// 每次发送 'C' 后等待的时间 (微秒)
+pub const START_TIMEOUT_US: u32 = 3_000_000;
+/// 块内两个字节之间的最长间隔 (微秒)
+pub const BYTE_TIMEOUT_US: u32 = 1_000_000;
+/// 等待下一个块头的时间 (微秒)
+pub const BLOCK_TIMEOUT_US: u32 = 10_000_000;
+/// 同一数据块连续出错的最大次数
+pub const MAX_ERRORS: u32 = 10;
+
+/// XMODEM 接收错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmodemError {
+    /// 发送方没有开始传输
+    Timeout,
+    /// 发送方取消了传输
+    Cancelled,
+    /// 数据块编号不连续，数据已丢失
+    Sequence,
+    /// 同一数据块连续出错 [`MAX_ERRORS`] 次
+    TooManyErrors,
+    /// 输出端拒绝数据 (缓冲区已满或写入失败)
+    Aborted,
+}
+
+impl fmt::Display for XmodemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmodemError::Timeout => f.write_str("sender did not start"),
+            XmodemError::Cancelled => f.write_str("cancelled by sender"),
+            XmodemError::Sequence => f.write_str("block sequence error"),
+            XmodemError::TooManyErrors => f.write_str("too many errors"),
+            XmodemError::Aborted => f.write_str("aborted by receiver"),
+        }
+    }
+}
+
+/// CRC-16/XMODEM (即 [`crc::crc16_xmodem`])
+///
+/// # 示例
+/// ```
+/// assert_eq!(uart::xmodem::crc16(b"123456789"), 0x31C3);
+/// ```
+pub use crc::crc16_xmodem as crc16;
+
+/// 接收文件到 `buf`
+///
+/// # 返回值
+/// - `Ok(len)`: 接收的字节数 (含最后一块的填充)
+/// - `Err(XmodemError)`: 接收失败；`buf` 放不下时为 [`XmodemError::Aborted`]
+pub fn receive(uart: &Uart, buf: &mut [u8]) -> Result<usize, XmodemError> {
+    let mut offset = 0;
+    receive_with(uart, |block| match buf.get_mut(offset..offset + block.len()) {
+        Some(dst) => {
+            dst.copy_from_slice(block);
+            offset += block.len();
+            true
+        }
+        None => false,
+    })
+}
+
+/// 接收文件，每个数据块按顺序交给 `sink`
+///
+/// `sink` 返回 `false` 时取消传输。重发的数据块不会重复交给 `sink`。
+///
+/// # 返回值
+/// - `Ok(len)`: 接收的字节数 (含最后一块的填充)
+/// - `Err(XmodemError)`: 接收失败
+pub fn receive_with(
+    uart: &Uart,
+    mut sink: impl FnMut(&[u8]) -> bool,
+) -> Result<usize, XmodemError> {
+    let mut block = [0u8; BLOCK_1K];
+    let mut expected: u8 = 1;
+    let mut total = 0;
+    let mut errors = 0;
+    let mut next = Some(start(uart)?);
+
+    loop {
+        let header = match next.take() {
+            Some(byte) => byte,
+            None => match uart.getc_timeout(BLOCK_TIMEOUT_US) {
+                Ok(byte) => byte,
+                Err(_) => {
+                    nak(uart, &mut errors)?;
+                    continue;
+                }
+            },
+        };
+
+        let len = match header {
+            SOH => 128,
+            STX => BLOCK_1K,
+            EOT => {
+                uart.putc(ACK);
+                return Ok(total);
+            }
+            CAN if uart.getc_timeout(BYTE_TIMEOUT_US) == Ok(CAN) => {
+                return Err(XmodemError::Cancelled);
+            }
+            _ => {
+                nak(uart, &mut errors)?;
+                continue;
+            }
+        };
+
+        match read_block(uart, &mut block[..len]) {
+            Some(seq) if seq == expected => {
+                if !sink(&block[..len]) {
+                    cancel(uart);
+                    return Err(XmodemError::Aborted);
+                }
+                total += len;
+                expected = expected.wrapping_add(1);
+                errors = 0;
+                uart.putc(ACK);
+            }
+            // 上一块的 ACK 丢失，发送方重发
+            Some(seq) if seq == expected.wrapping_sub(1) => uart.putc(ACK),
+            Some(_) => {
+                cancel(uart);
+                return Err(XmodemError::Sequence);
+            }
+            None => nak(uart, &mut errors)?,
+        }
+    }
+}
+
+/// 发送 'C' 直到发送方开始，返回收到的第一个块头
+fn start(uart: &Uart) -> Result<u8, XmodemError> {
+    for _ in 0..START_RETRIES {
+        uart.putc(CRC_MODE);
+        match uart.getc_timeout(START_TIMEOUT_US) {
+            Ok(byte @ (SOH | STX | EOT)) => return Ok(byte),
+            Ok(CAN) => return Err(XmodemError::Cancelled),
+            _ => {}
+        }
+    }
+    Err(XmodemError::Timeout)
+}
+
+/// 读取块号、数据和 CRC
+///
+/// # 返回值
+/// 校验通过时返回块号，超时或校验失败返回 `None`
+fn read_block(uart: &Uart, data: &mut [u8]) -> Option<u8> {
+    let next = || uart.getc_timeout(BYTE_TIMEOUT_US).ok();
+    let seq = next()?;
+    let seq_inv = next()?;
+    for byte in data.iter_mut() {
+        *byte = next()?;
+    }
+    let crc = u16::from_be_bytes([next()?, next()?]);
+    (seq == !seq_inv && crc == crc16(data)).then_some(seq)
+}
+
+/// 丢弃线路上剩余的数据后请求重发
+fn nak(uart: &Uart, errors: &mut u32) -> Result<(), XmodemError> {
+    *errors += 1;
+    if *errors >= MAX_ERRORS {
+        cancel(uart);
+        return Err(XmodemError::TooManyErrors);
+    }
+    while uart.getc_timeout(BYTE_TIMEOUT_US).is_ok() {}
+    uart.putc(NAK);
+    Ok(())
+}
+
+/// 通知发送方取消传输
+fn cancel(uart: &Uart) {
+    uart.putc(CAN);
+    uart.putc(CAN);
+}
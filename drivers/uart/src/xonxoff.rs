@@ -0,0 +1,118 @@
+//! XON/XOFF 软件流控
+//!
+//! 用 [`FlowControl::XonXoff`](crate::FlowControl::XonXoff) 初始化后：
+//! - 收到 XOFF 暂停发送，收到 XON 恢复；这两个字符被驱动吃掉，不会出现在接收数据中
+//! - 暂停期间 `putc` 阻塞，`try_putc`/`write_bytes` 立即返回，中断发送模式下数据留在发送缓冲区
+//! - 可选: [`Uart::set_rx_throttle`] 开启后，接收缓冲区达到 3/4 时发送 XOFF，
+//!   降到 1/4 以下时发送 XON (需要中断接收)
+//!
+//! # 注意
+//! 二进制数据中可能出现 0x11/0x13，XMODEM 等二进制协议不能与软件流控同时使用。
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::{FlowControl, Uart, UartConfig, UART3_BASE};
+//!
+//! let uart = Uart::new(UART3_BASE);
+//! uart.init_with_config(&UartConfig {
+//!     flow_control: FlowControl::XonXoff,
+//!     ..UartConfig::new(115200)
+//! }).unwrap();
+//! uart.enable_rx_interrupt();
+//! uart.set_rx_throttle(true);
+//! ```
+
+use core::sync::atomic::Ordering;
+
+use mmio::read_volatile;
+
+use crate::{Uart, IER_ERBFI, IER_ETBEI, RX_BUFFER_SIZE, UART_IER};
+
+/// 恢复发送 (DC1)
+pub const XON: u8 = 0x11;
+/// 暂停发送 (DC3)
+pub const XOFF: u8 = 0x13;
+
+/// 接收缓冲区达到该字节数时发送 XOFF
+const RX_XOFF_LEVEL: usize = RX_BUFFER_SIZE * 3 / 4;
+/// 发送 XOFF 后接收缓冲区降到该字节数时发送 XON
+const RX_XON_LEVEL: usize = RX_BUFFER_SIZE / 4;
+
+impl Uart {
+    /// 按接收缓冲区水位发送 XOFF/XON
+    ///
+    /// 只在 [`FlowControl::XonXoff`](crate::FlowControl::XonXoff) 模式且
+    /// 使能中断接收时有效
+    pub fn set_rx_throttle(&self, enable: bool) {
+        self.rx_throttle.store(enable, Ordering::Release);
+        if !enable && self.xoff_sent.swap(false, Ordering::AcqRel) {
+            self.putc_polled(XON);
+        }
+    }
+
+    /// 发送是否被对端的 XOFF 暂停
+    pub fn is_tx_paused(&self) -> bool {
+        self.tx_paused.load(Ordering::Acquire)
+    }
+
+    /// 处理收到的流控字符
+    ///
+    /// # 返回值
+    /// `byte` 是流控字符并已处理时返回 `true`，调用者应丢弃该字节
+    pub(crate) fn filter_rx(&self, byte: u8) -> bool {
+        if !self.xonxoff.load(Ordering::Acquire) {
+            return false;
+        }
+        match byte {
+            XOFF => self.tx_paused.store(true, Ordering::Release),
+            XON => {
+                self.tx_paused.store(false, Ordering::Release);
+                if self.tx_buffered.load(Ordering::Acquire) && !self.tx_buf.is_empty() {
+                    self.modify_ier(IER_ETBEI, true);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// 轮询发送前等待 XON
+    ///
+    /// 没有使能中断接收时由这里读取 RX FIFO，普通数据存入接收缓冲区
+    pub(crate) fn wait_tx_resume(&self) {
+        while self.is_tx_paused() {
+            let ier = unsafe { read_volatile((self.base + UART_IER) as *const u32) };
+            if ier & IER_ERBFI == 0 {
+                self.drain_rx_fifo();
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// 接收缓冲区写入后检查是否需要发送 XOFF
+    pub(crate) fn throttle_rx(&self) {
+        if self.xonxoff.load(Ordering::Acquire)
+            && self.rx_throttle.load(Ordering::Acquire)
+            && self.rx_buf.len() >= RX_XOFF_LEVEL
+            && !self.xoff_sent.swap(true, Ordering::AcqRel)
+        {
+            self.putc_polled(XOFF);
+        }
+    }
+
+    /// 从接收缓冲区取一个字节，水位降低后发送 XON
+    pub(crate) fn pop_rx(&self) -> Option<u8> {
+        let byte = {
+            let _consumer = self.rx_pop.lock_irqsave();
+            // 持有 rx_pop，是唯一的消费者
+            unsafe { self.rx_buf.pop() }
+        };
+        if self.xoff_sent.load(Ordering::Acquire)
+            && self.rx_buf.len() <= RX_XON_LEVEL
+            && self.xoff_sent.swap(false, Ordering::AcqRel)
+        {
+            self.putc_polled(XON);
+        }
+        byte
+    }
+}
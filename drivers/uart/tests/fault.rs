@@ -0,0 +1,28 @@
+//! 故障注入测试，注入点是全局的，与其他测试分开运行
+//!
+//! 运行: `cargo test -p uart --features sim,fault-inject`
+
+#![cfg(all(feature = "sim", feature = "fault-inject", debug_assertions))]
+
+use mmio::sim::{self, UartModel};
+use uart::{RxError, Uart, UartClock, UartConfig, UART2_BASE};
+
+#[test]
+fn injected_overrun_takes_error_path() {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    let uart = Uart::new(UART2_BASE);
+    uart.init_with_config(&UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(115200)
+    })
+    .unwrap();
+    model.borrow_mut().receive(b"ab");
+
+    fault::arm("uart.rx_overrun", 1, 1).unwrap();
+    assert_eq!(uart.try_getc(), Ok(Some(b'a')));
+    // 溢出时不读 RBR，待读字节保留
+    assert_eq!(uart.try_getc(), Err(RxError::Overrun));
+    assert_eq!(uart.try_getc(), Ok(Some(b'b')));
+    assert_eq!(uart.take_errors().overrun, 1);
+    assert!(!fault::find("uart.rx_overrun").unwrap().is_armed());
+}
@@ -0,0 +1,177 @@
+//! 行编辑器：历史、增量搜索与 Tab 补全
+//!
+//! 运行: `cargo test -p uart --test line`
+
+use uart::line::{Complete, LineEditor, LineEvent};
+
+const UP: &[u8] = b"\x1b[A";
+const DOWN: &[u8] = b"\x1b[B";
+const BEL: char = '\x07';
+
+/// 逐字节输入，返回最后一个事件和回显
+fn feed<B: AsRef<[u8]> + AsMut<[u8]>, const H: usize>(
+    editor: &mut LineEditor<B, H>,
+    input: &[u8],
+) -> (LineEvent, String) {
+    let mut echo = String::new();
+    let mut event = LineEvent::Pending;
+    for &b in input {
+        event = editor.feed_with(b, &mut echo, &Commands);
+    }
+    (event, echo)
+}
+
+/// 输入一行并结束，之后清空开始下一行
+fn enter<B: AsRef<[u8]> + AsMut<[u8]>, const H: usize>(editor: &mut LineEditor<B, H>, line: &str) {
+    let (event, _) = feed(editor, line.as_bytes());
+    assert_eq!(event, LineEvent::Pending);
+    assert_eq!(feed(editor, b"\r").0, LineEvent::Done);
+    assert_eq!(editor.line(), line);
+    editor.clear();
+}
+
+struct Commands;
+
+impl Complete for Commands {
+    fn candidates(&self, candidate: &mut dyn FnMut(&str)) {
+        ["help", "history", "halt", "regdump", "reboot"].into_iter().for_each(candidate);
+    }
+}
+
+#[test]
+fn arrows_walk_history() {
+    let mut editor = LineEditor::<_, 64>::with_history([0u8; 32]);
+    enter(&mut editor, "one");
+    enter(&mut editor, "two");
+
+    assert_eq!(feed(&mut editor, UP).1, "two");
+    assert_eq!(feed(&mut editor, UP).1, "\x08 \x08".repeat(3) + "one");
+    // 没有更早的条目
+    assert_eq!(feed(&mut editor, UP).1, BEL.to_string());
+    assert_eq!(editor.line(), "one");
+
+    feed(&mut editor, DOWN);
+    assert_eq!(editor.line(), "two");
+    feed(&mut editor, DOWN);
+    assert_eq!(editor.line(), "");
+    assert_eq!(feed(&mut editor, DOWN).1, BEL.to_string());
+
+    // `ESC O A` 和带参数的序列同样有效，左右键被忽略
+    feed(&mut editor, b"\x1bOA");
+    assert_eq!(editor.line(), "two");
+    feed(&mut editor, b"\x1b[1;5A\x1b[C");
+    assert_eq!(editor.line(), "one");
+}
+
+#[test]
+fn recalled_line_can_be_edited() {
+    let mut editor = LineEditor::<_, 64>::with_history([0u8; 32]);
+    enter(&mut editor, "ls /dev");
+    feed(&mut editor, UP);
+    assert_eq!(feed(&mut editor, b"\x7f\x7f\x7fmnt\r").0, LineEvent::Done);
+    assert_eq!(editor.line(), "ls /mnt");
+    editor.clear();
+
+    feed(&mut editor, UP);
+    assert_eq!(editor.line(), "ls /mnt");
+    feed(&mut editor, UP);
+    assert_eq!(editor.line(), "ls /dev");
+}
+
+#[test]
+fn history_skips_blank_and_repeated_lines_and_drops_oldest() {
+    // 每条 "xxxx" 占 5 字节，16 字节放得下 3 条
+    let mut editor = LineEditor::<_, 16>::with_history([0u8; 32]);
+    for line in ["aaaa", "", "bbbb", "bbbb", "cccc", "dddd"] {
+        enter(&mut editor, line);
+    }
+
+    let mut recalled = Vec::new();
+    for _ in 0..4 {
+        let (_, echo) = feed(&mut editor, UP);
+        if echo.ends_with(BEL) {
+            break;
+        }
+        recalled.push(editor.line().to_string());
+    }
+    assert_eq!(recalled, ["dddd", "cccc", "bbbb"]);
+}
+
+#[test]
+fn editor_without_history_ignores_arrows() {
+    let mut editor = LineEditor::new([0u8; 16]);
+    enter(&mut editor, "help");
+    assert_eq!(feed(&mut editor, UP).1, BEL.to_string());
+    assert!(editor.is_empty());
+}
+
+#[test]
+fn ctrl_r_searches_history_incrementally() {
+    let mut editor = LineEditor::<_, 128>::with_history([0u8; 32]);
+    for line in ["make all", "ls", "make clean"] {
+        enter(&mut editor, line);
+    }
+
+    let (_, echo) = feed(&mut editor, b"\x12ma");
+    assert!(echo.ends_with("(search)'ma': make clean"), "{echo:?}");
+    let (_, echo) = feed(&mut editor, b"\x12");
+    assert!(echo.ends_with("(search)'ma': make all"), "{echo:?}");
+    // 没有更早的匹配，保留当前匹配
+    let (_, echo) = feed(&mut editor, b"\x12");
+    assert!(echo.starts_with(BEL) && echo.ends_with("make all"), "{echo:?}");
+
+    assert_eq!(feed(&mut editor, b"\r").0, LineEvent::Done);
+    assert_eq!(editor.line(), "make all");
+    editor.clear();
+
+    // 其他控制键取出匹配行继续编辑
+    feed(&mut editor, b"\x12ls\x1b[D");
+    assert_eq!(editor.line(), "ls");
+    feed(&mut editor, b" -l");
+    assert_eq!(editor.line(), "ls -l");
+    editor.clear();
+
+    // 没有匹配时响铃并保留上一个匹配，Ctrl-G 放弃搜索
+    let (_, echo) = feed(&mut editor, b"\x12zz");
+    assert!(echo.ends_with("(search)'zz': make all"), "{echo:?}");
+    assert!(echo.contains(BEL));
+    feed(&mut editor, b"\x07");
+    assert!(editor.is_empty());
+    assert_eq!(feed(&mut editor, b"x").1, "x");
+}
+
+#[test]
+fn tab_completes_unique_command() {
+    let mut editor = LineEditor::new([0u8; 32]);
+    let (event, echo) = feed(&mut editor, b"reb\t");
+    assert_eq!(event, LineEvent::Pending);
+    assert_eq!(echo, "reboot ");
+    assert_eq!(editor.line(), "reboot ");
+
+    // 参数不补全
+    let (_, echo) = feed(&mut editor, b"l\t");
+    assert_eq!(echo, format!("l{BEL}"));
+    assert_eq!(editor.line(), "reboot l");
+}
+
+#[test]
+fn tab_extends_common_prefix_then_lists() {
+    let mut editor = LineEditor::new([0u8; 32]);
+    assert_eq!(feed(&mut editor, b"r\t").1, "r\x07".replace('\x07', "e"));
+    assert_eq!(editor.line(), "re");
+
+    let (event, echo) = feed(&mut editor, b"\t");
+    assert_eq!(event, LineEvent::Redraw);
+    assert_eq!(echo, "\nregdump  reboot  \n");
+    assert_eq!(editor.line(), "re");
+
+    editor.clear();
+    assert_eq!(feed(&mut editor, b"x\t").1, format!("x{BEL}"));
+}
+
+#[test]
+fn tab_completion_is_bounded_by_line_buffer() {
+    let mut editor = LineEditor::new([0u8; 4]);
+    feed(&mut editor, b"reg\t");
+    assert_eq!(editor.line(), "regd");
+}
@@ -0,0 +1,709 @@
+//! 基于主机端寄存器模型的 UART 驱动测试
+//!
+//! 运行: `cargo test -p uart --features sim`
+
+#![cfg(feature = "sim")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use mmio::sim::{self, Handle, Ram, UartModel};
+#[cfg(feature = "console")]
+use uart::console::ConsoleSink;
+#[cfg(feature = "early")]
+use uart::early::EarlyCon;
+#[cfg(feature = "mux")]
+use uart::mux::{Channel, FrameDecoder, Mux};
+use uart::line::Interrupted;
+#[cfg(feature = "xmodem")]
+use uart::xmodem::{self, XmodemError};
+use uart::xonxoff::{XOFF, XON};
+#[cfg(feature = "autobaud")]
+use uart::autobaud::AutobaudError;
+use uart::{
+    FlowControl, RxError, Uart, UartClock, UartConfig, UartError, WouldBlock, UART2_BASE,
+};
+
+/// CRU 基址和 UART2 输出选择寄存器 CLKSEL_CON(45)
+const CRU_BASE: usize = 0xFD7C0000;
+const CRU_CLKSEL_CON45: usize = 0x300 + 45 * 4;
+
+fn setup() -> (Uart, Handle<UartModel>) {
+    setup_with(FlowControl::None)
+}
+
+fn setup_with(flow_control: FlowControl) -> (Uart, Handle<UartModel>) {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    let uart = Uart::new(UART2_BASE);
+    uart.init_with_config(&UartConfig {
+        flow_control,
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(115200)
+    })
+    .unwrap();
+    (uart, model)
+}
+
+#[test]
+fn init_programs_divisor_and_line() {
+    let (_uart, model) = setup();
+    let model = model.borrow();
+    assert_eq!(model.divisor(), 13);
+    assert_eq!(model.lcr(), 0x03);
+    assert_eq!(model.ier(), 0);
+}
+
+#[test]
+fn init_resolves_clock_from_cru() {
+    let cru = sim::map(CRU_BASE, 0x1000, Ram::new(0x1000));
+    // sclk_uart2 选择 xin24m
+    cru.borrow_mut().set_word(CRU_CLKSEL_CON45, 2);
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+
+    Uart::new(UART2_BASE).init(115200).unwrap();
+    assert_eq!(model.borrow().divisor(), 13);
+}
+
+#[test]
+fn init_retries_lcr_while_busy() {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    model.borrow_mut().set_busy(true);
+
+    let config = UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(1_500_000)
+    };
+    Uart::new(UART2_BASE).init_with_config(&config).unwrap();
+    let model = model.borrow();
+    assert_eq!(model.divisor(), 1);
+    assert_eq!(model.lcr(), 0x03);
+}
+
+#[test]
+fn init_reports_lcr_stuck_busy() {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    model.borrow_mut().set_stuck_busy(true);
+
+    let uart = Uart::new(UART2_BASE);
+    let config = UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(1_500_000)
+    };
+    assert_eq!(uart.init_with_config(&config), Err(UartError::LcrBusy));
+    assert_eq!(model.borrow().lcr(), 0);
+}
+
+#[test]
+fn init_uses_fractional_divisor_when_available() {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    model.borrow_mut().set_dlf_bits(4);
+    let uart = Uart::new(UART2_BASE);
+    assert_eq!(uart.dlf_bits(), 4);
+
+    uart.init_with_config(&UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(921_600)
+    })
+    .unwrap();
+    let model = model.borrow();
+    assert_eq!(model.divisor(), 1);
+    assert_eq!(model.fraction(), 10);
+}
+
+#[test]
+fn init_without_dlf_uses_integer_divisor() {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    let uart = Uart::new(UART2_BASE);
+    assert_eq!(uart.dlf_bits(), 0);
+
+    uart.init_with_config(&UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(921_600)
+    })
+    .unwrap();
+    assert_eq!(model.borrow().divisor(), 2);
+}
+
+#[test]
+fn puts_translates_newlines() {
+    let (uart, model) = setup();
+    uart.puts("ok\n");
+    assert_eq!(model.borrow().transmitted(), b"ok\r\n");
+}
+
+#[test]
+#[cfg(feature = "early")]
+fn earlycon_writes_without_init() {
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    static EARLYCON: EarlyCon = EarlyCon::new(UART2_BASE);
+
+    writeln!(EARLYCON, "el{}", 2).unwrap();
+    let model = model.borrow();
+    assert_eq!(model.transmitted(), b"el2\r\n");
+    assert_eq!(model.divisor(), 0);
+}
+
+#[test]
+fn getc_reads_fifo() {
+    let (uart, model) = setup();
+    assert_eq!(uart.getc(), None);
+    model.borrow_mut().receive(b"hi");
+    assert_eq!(uart.getc(), Some(b'h'));
+    assert_eq!(uart.getc(), Some(b'i'));
+    assert_eq!(uart.getc(), None);
+}
+
+#[test]
+fn getc_timeout_waits_for_data_or_expires() {
+    let (uart, model) = setup();
+    // 主机上没有通用定时器，按估计的轮询次数计时
+    assert_eq!(uart.getc_timeout(100), Err(UartError::Timeout));
+    model.borrow_mut().receive(b"x");
+    assert_eq!(uart.getc_timeout(100), Ok(b'x'));
+}
+
+#[test]
+fn probe_baud_gives_up_after_each_candidate_times_out() {
+    let (uart, model) = setup();
+    let config = UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(115200)
+    };
+    assert_eq!(uart.probe_baud(&config, &[115_200, 9_600], b'\r', 100), None);
+    // 失败时保持在最后一个候选波特率: 24 MHz / (16 × 9600) ≈ 156
+    assert_eq!(model.borrow().divisor(), 156);
+}
+
+/// 模拟通用定时器频率，与 RK3588 的 CNTFRQ_EL0 相同
+#[cfg(feature = "autobaud")]
+const TIMER_HZ: u64 = 24_000_000;
+
+/// 按模拟时钟回放 RX 线路电平的 GPIO Bank，`edges` 为 (计数, 电平) 并按计数排序
+#[cfg(feature = "autobaud")]
+struct RxLine {
+    pin: u8,
+    edges: Vec<(u64, bool)>,
+}
+
+#[cfg(feature = "autobaud")]
+impl mmio::sim::Device for RxLine {
+    fn read(&mut self, offset: usize) -> u32 {
+        // GPIO_EXT_PORT
+        if offset != 0x70 {
+            return 0;
+        }
+        let now = timer::ticks().unwrap();
+        let high = self.edges.iter().rev().find(|&&(at, _)| at <= now).is_none_or(|&(_, level)| level);
+        (high as u32) << self.pin
+    }
+
+    fn write(&mut self, _offset: usize, _value: u32) {}
+}
+
+/// 从计数 `at` 开始按 `baud` 发送的 8N1 字符 `byte` 的电平跳变
+#[cfg(feature = "autobaud")]
+fn frame(byte: u8, baud: u32, at: u64) -> Vec<(u64, bool)> {
+    let bit = TIMER_HZ as f64 / baud as f64;
+    let levels = std::iter::once(false)
+        .chain((0..8).map(|i| byte >> i & 1 != 0))
+        .chain(std::iter::once(true));
+    levels.enumerate().map(|(i, level)| (at + (i as f64 * bit) as u64, level)).collect()
+}
+
+/// 在 GPIO4 的 `pin` 上回放 `edges`，开启模拟时钟 (每次读取约 125ns)
+#[cfg(feature = "autobaud")]
+fn rx_line(pin: u8, edges: Vec<(u64, bool)>) -> gpio::GpioPin {
+    sim::map(gpio::GPIO4_BASE, 0x100, RxLine { pin, edges });
+    timer::sim::start(TIMER_HZ);
+    timer::sim::set_auto_advance(3);
+    gpio::GpioPin::new(gpio::GpioBank::Gpio4, pin)
+}
+
+#[test]
+#[cfg(feature = "autobaud")]
+fn autobaud_measures_edges_of_expected_char() {
+    let (uart, model) = setup();
+    let config = UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::default()
+    };
+    for (expected, baud) in [(b'\r', 115_200), (b'U', 1_500_000), (b'U', 9_600), (b'\r', 57_600)] {
+        let rx = rx_line(1, frame(expected, baud, 1_000));
+        assert_eq!(uart.autobaud(&config, &rx, expected, 100_000), Ok(baud), "{baud}");
+        let divisor = (24_000_000 + 8 * baud) / (16 * baud);
+        assert_eq!(model.borrow().divisor(), divisor, "{baud}");
+    }
+
+    // 不在常用波特率表中的速率按测量值设置 (DMX512 的 250k)
+    let rx = rx_line(1, frame(b'U', 250_000, 1_000));
+    let baud = uart.autobaud(&config, &rx, b'U', 100_000).unwrap();
+    assert!(baud.abs_diff(250_000) < 2_500, "{baud}");
+    assert_eq!(model.borrow().divisor(), 6);
+}
+
+#[test]
+#[cfg(feature = "autobaud")]
+fn autobaud_skips_frames_that_are_not_expected_char() {
+    let (uart, model) = setup();
+    let config = UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(9_600)
+    };
+    let bit = TIMER_HZ / 57_600;
+    let mut edges = frame(b'a', 57_600, 1_000);
+    edges.extend(frame(b'\r', 57_600, 1_000 + 20 * bit));
+    let rx = rx_line(2, edges);
+
+    assert_eq!(uart.autobaud(&config, &rx, b'\r', 100_000), Ok(57_600));
+    assert_eq!(model.borrow().divisor(), 26);
+}
+
+#[test]
+#[cfg(feature = "autobaud")]
+fn autobaud_times_out_on_idle_line_without_touching_uart() {
+    let (uart, model) = setup();
+    let rx = rx_line(3, Vec::new());
+    let config = UartConfig {
+        clock: UartClock::Fixed(24_000_000),
+        ..UartConfig::new(9_600)
+    };
+    assert_eq!(uart.autobaud(&config, &rx, b'U', 100), Err(AutobaudError::Timeout));
+    assert_eq!(model.borrow().divisor(), 13);
+
+    // 引脚在返回后释放
+    assert!(gpio::claim::claim_pin(&rx, "test").is_ok());
+}
+
+#[test]
+#[cfg(feature = "autobaud")]
+fn autobaud_reports_setup_errors() {
+    let (uart, _model) = setup();
+    let config = UartConfig::default();
+    let rx = rx_line(4, frame(b'U', 115_200, 1_000));
+
+    assert_eq!(uart.autobaud(&config, &rx, 0x00, 100), Err(AutobaudError::NoEdges));
+
+    let claim = gpio::claim::claim_pin(&rx, "test").unwrap();
+    let Err(AutobaudError::PinConflict(conflict)) = uart.autobaud(&config, &rx, b'U', 100) else {
+        panic!("RX pin claimed twice");
+    };
+    assert_eq!((conflict.owner, conflict.requester), ("test", "autobaud"));
+    drop(claim);
+
+    timer::sim::stop();
+    assert_eq!(uart.autobaud(&config, &rx, b'U', 100), Err(AutobaudError::NoTimer));
+}
+
+#[test]
+fn line_errors_are_reported_and_counted() {
+    let (uart, model) = setup();
+    model.borrow_mut().receive_with_error(b'x', UartModel::PARITY);
+    model.borrow_mut().receive(b"y");
+
+    assert_eq!(uart.try_getc(), Err(RxError::Parity));
+    assert_eq!(uart.try_getc(), Ok(Some(b'y')));
+    assert_eq!(uart.take_errors().parity, 1);
+}
+
+#[test]
+fn line_errors_survive_lsr_reads_on_the_tx_path() {
+    let (uart, model) = setup();
+    model.borrow_mut().receive_with_error(b'x', UartModel::FRAMING);
+    model.borrow_mut().receive(b"y");
+
+    // 发送等待 THRE 时读 LSR，清除了 FIFO 头部字节的错误位
+    uart.putc(b'!');
+    assert!(uart.is_tx_idle());
+    assert_eq!(uart.try_getc(), Err(RxError::Framing));
+    assert_eq!(uart.try_getc(), Ok(Some(b'y')));
+    assert_eq!(uart.take_errors().framing, 1);
+}
+
+#[test]
+fn overrun_keeps_pending_byte() {
+    let (uart, model) = setup();
+    model.borrow_mut().receive(&[0x55; 65]);
+
+    assert_eq!(uart.try_getc(), Err(RxError::Overrun));
+    assert_eq!(uart.try_getc(), Ok(Some(0x55)));
+    assert_eq!(model.borrow().rx_level(), 63);
+}
+
+#[test]
+fn break_is_detected_once() {
+    let (uart, model) = setup();
+    model.borrow_mut().receive_with_error(0, UartModel::BREAK | UartModel::FRAMING);
+
+    assert_eq!(uart.try_getc(), Err(RxError::Break));
+    assert!(uart.take_break());
+    assert!(!uart.take_break());
+}
+
+#[test]
+fn send_break_restores_lcr() {
+    let (uart, model) = setup();
+    uart.send_break(200).unwrap();
+    let model = model.borrow();
+    assert_eq!(model.breaks_sent(), 1);
+    assert_eq!(model.lcr(), 0x03);
+    assert!(model.transmitted().is_empty());
+}
+
+#[test]
+fn send_break_gives_up_when_transmitter_stalls() {
+    let (uart, model) = setup();
+    model.borrow_mut().set_tx_stalled(true);
+    assert_eq!(uart.send_break(200), Err(UartError::Timeout));
+    assert_eq!(model.borrow().breaks_sent(), 0);
+    assert_eq!(model.borrow().lcr(), 0x03);
+}
+
+#[test]
+fn interrupt_driven_tx_drains_ring() {
+    let (uart, model) = setup();
+    uart.enable_tx_interrupt();
+    let data = [0xA5; 100];
+    assert_eq!(uart.write_bytes(&data), data.len());
+    assert_eq!(uart.tx_pending(), data.len());
+
+    uart.on_irq();
+    assert_eq!(uart.tx_pending(), 0);
+    assert_eq!(model.borrow().transmitted(), &data[..]);
+    // 发送缓冲区空后关闭 THRE 中断
+    assert_eq!(model.borrow().ier() & 0x02, 0);
+}
+
+#[test]
+fn interrupt_driven_rx_fills_buffer() {
+    let (uart, model) = setup();
+    uart.enable_rx_interrupt();
+    model.borrow_mut().receive(b"abc");
+
+    uart.on_irq();
+    assert_eq!(model.borrow().rx_level(), 0);
+    let mut buf = [0; 8];
+    assert_eq!(uart.read(&mut buf), 3);
+    assert_eq!(&buf[..3], b"abc");
+}
+
+#[test]
+fn try_putc_reports_full_tx_buffer() {
+    let (uart, model) = setup();
+    assert_eq!(uart.try_putc(b'a'), Ok(()));
+    assert_eq!(model.borrow().transmitted(), b"a");
+
+    uart.enable_tx_interrupt();
+    let mut queued = 0;
+    while uart.try_putc(b'b').is_ok() {
+        queued += 1;
+    }
+    assert_eq!(uart.try_putc(b'b'), Err(WouldBlock));
+    assert_eq!(queued, uart.tx_pending());
+}
+
+#[test]
+fn flush_drains_tx_buffer_without_interrupts() {
+    let (uart, model) = setup();
+    uart.enable_tx_interrupt();
+    uart.puts("bye");
+    assert_eq!(uart.tx_pending(), 3);
+
+    assert_eq!(uart.flush(100), Ok(()));
+    assert_eq!(uart.tx_pending(), 0);
+    assert_eq!(model.borrow().transmitted(), b"bye");
+}
+
+#[test]
+fn flush_times_out_when_deadline_passes() {
+    let (uart, _model) = setup();
+    uart.enable_tx_interrupt();
+    uart.puts("x");
+    // 0 微秒的期限在第一次检查时就已经过去
+    assert_eq!(uart.flush(0), Err(UartError::Timeout));
+    assert_eq!(uart.tx_pending(), 1);
+}
+
+#[test]
+fn read_line_echoes_and_edits() {
+    let (uart, model) = setup();
+    model.borrow_mut().queue(b"lx\x7fs -l\x15ls\r\n");
+
+    let mut buf = [0u8; 16];
+    assert_eq!(uart.read_line(&mut buf), Ok("ls"));
+    let echo = model.borrow_mut().take_transmitted();
+    assert_eq!(echo, b"lx\x08 \x08s -l\x08 \x08\x08 \x08\x08 \x08\x08 \x08\x08 \x08ls\r\n");
+
+    // CRLF 中的 \n 不产生空行，Ctrl-C 中断读取
+    model.borrow_mut().queue(b"ab\x03");
+    assert_eq!(uart.read_line(&mut buf), Err(Interrupted));
+    assert_eq!(model.borrow().transmitted(), b"ab^C\r\n");
+}
+
+#[test]
+fn context_survives_power_loss() {
+    let (uart, _model) = setup();
+    uart.set_rts(true);
+    uart.enable_rx_interrupt();
+    let ctx = uart.save_context().unwrap();
+
+    // 电源域断电后寄存器复位
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    uart.restore_context(&ctx).unwrap();
+    let model = model.borrow();
+    assert_eq!(model.divisor(), 13);
+    assert_eq!(model.lcr(), 0x03);
+    assert_eq!(model.mcr() & 0x02, 0x02);
+    assert_eq!(model.ier() & 0x01, 0x01);
+}
+
+#[test]
+fn xoff_pauses_polled_tx() {
+    let (uart, model) = setup_with(FlowControl::XonXoff);
+    model.borrow_mut().receive(&[XOFF, b'a']);
+
+    assert_eq!(uart.getc(), None);
+    assert!(uart.is_tx_paused());
+    assert_eq!(uart.try_putc(b'x'), Err(WouldBlock));
+    assert_eq!(uart.write_bytes(b"xyz"), 0);
+    assert_eq!(uart.getc(), Some(b'a'));
+
+    model.borrow_mut().receive(&[XON]);
+    assert_eq!(uart.getc(), None);
+    assert!(!uart.is_tx_paused());
+    assert_eq!(uart.write_bytes(b"xyz"), 3);
+    assert_eq!(model.borrow().transmitted(), b"xyz");
+}
+
+#[test]
+fn xoff_holds_interrupt_tx_until_xon() {
+    let (uart, model) = setup_with(FlowControl::XonXoff);
+    uart.enable_rx_interrupt();
+    uart.enable_tx_interrupt();
+
+    model.borrow_mut().receive(&[XOFF]);
+    uart.on_irq();
+    assert_eq!(uart.write_bytes(b"held"), 4);
+    uart.on_irq();
+    assert!(model.borrow().transmitted().is_empty());
+    assert_eq!(uart.tx_pending(), 4);
+
+    model.borrow_mut().receive(&[XON]);
+    uart.on_irq();
+    assert_eq!(model.borrow().transmitted(), b"held");
+    assert_eq!(uart.rx_available(), 0);
+}
+
+#[test]
+fn flow_control_chars_are_data_without_xonxoff() {
+    let (uart, model) = setup();
+    model.borrow_mut().receive(&[XOFF]);
+    assert_eq!(uart.getc(), Some(XOFF));
+    assert!(!uart.is_tx_paused());
+}
+
+#[test]
+fn rx_throttle_sends_xoff_and_xon() {
+    let (uart, model) = setup_with(FlowControl::XonXoff);
+    uart.enable_rx_interrupt();
+    uart.set_rx_throttle(true);
+
+    for _ in 0..3 {
+        model.borrow_mut().receive(&[b'.'; 64]);
+        uart.on_irq();
+    }
+    assert_eq!(model.borrow_mut().take_transmitted(), [XOFF]);
+
+    let mut buf = [0; 160];
+    assert_eq!(uart.read(&mut buf), 160);
+    assert_eq!(model.borrow().transmitted(), [XON]);
+}
+
+/// 只有一个待读字节的输入端
+#[cfg(feature = "console")]
+struct OneByteSink(AtomicU32);
+
+#[cfg(feature = "console")]
+impl ConsoleSink for OneByteSink {
+    fn write_str(&self, _s: &str) {}
+
+    fn getc(&self) -> Option<u8> {
+        match self.0.swap(0, Ordering::Relaxed) {
+            0 => None,
+            byte => Some(byte as u8),
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "console")]
+fn console_reads_input_from_all_backends() {
+    static SINK: OneByteSink = OneByteSink(AtomicU32::new(0));
+
+    let cru = sim::map(CRU_BASE, 0x1000, Ram::new(0x1000));
+    cru.borrow_mut().set_word(CRU_CLKSEL_CON45, 2);
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    uart::init_console(UART2_BASE, 115200).unwrap();
+    uart::console().register(&SINK).unwrap();
+
+    model.borrow_mut().receive(b"a");
+    SINK.0.store(b'b' as u32, Ordering::Relaxed);
+    assert_eq!(uart::console().getc(), Some(b'a'));
+    assert_eq!(uart::console().getc(), Some(b'b'));
+    assert_eq!(uart::console().getc(), None);
+
+    uart::console().unregister(&SINK);
+}
+
+#[test]
+#[cfg(feature = "console")]
+fn init_console_rejects_unknown_base() {
+    assert_eq!(uart::init_console(0x1000, 115200), Err(UartError::UnknownBase));
+}
+
+/// 构造一个 XMODEM 数据块 (128 字节用 SOH，1024 字节用 STX)
+#[cfg(feature = "xmodem")]
+fn xmodem_block(seq: u8, data: &[u8]) -> Vec<u8> {
+    let mut block = vec![if data.len() == 128 { 0x01 } else { 0x02 }, seq, !seq];
+    block.extend_from_slice(data);
+    block.extend_from_slice(&xmodem::crc16(data).to_be_bytes());
+    block
+}
+
+#[test]
+#[cfg(feature = "xmodem")]
+fn xmodem_receives_blocks_and_skips_resends() {
+    let (uart, model) = setup();
+    let first = [0xA5; 128];
+    let second: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+
+    let mut wire = xmodem_block(1, &first);
+    // 发送方没收到 ACK，重发第 1 块
+    wire.extend(xmodem_block(1, &first));
+    wire.extend(xmodem_block(2, &second));
+    wire.push(0x04);
+    model.borrow_mut().queue(&wire);
+
+    let mut buf = [0u8; 2048];
+    assert_eq!(xmodem::receive(&uart, &mut buf), Ok(128 + 1024));
+    assert_eq!(&buf[..128], &first);
+    assert_eq!(&buf[128..1152], &second[..]);
+    // 'C'，三个数据块和 EOT 各一个 ACK
+    assert_eq!(model.borrow().transmitted(), b"C\x06\x06\x06\x06");
+}
+
+#[test]
+#[cfg(feature = "xmodem")]
+fn xmodem_cancels_when_buffer_is_full() {
+    let (uart, model) = setup();
+    model.borrow_mut().queue(&xmodem_block(1, &[0; 1024]));
+
+    let mut buf = [0u8; 512];
+    assert_eq!(xmodem::receive(&uart, &mut buf), Err(XmodemError::Aborted));
+    assert_eq!(model.borrow().transmitted(), b"C\x18\x18");
+}
+
+#[test]
+#[cfg(feature = "xmodem")]
+fn xmodem_reports_sender_cancel() {
+    let (uart, model) = setup();
+    model.borrow_mut().queue(&[0x18, 0x18]);
+    assert_eq!(xmodem::receive_with(&uart, |_| true), Err(XmodemError::Cancelled));
+}
+
+/// 按帧解码发送的数据
+#[cfg(feature = "mux")]
+fn decode_frames(wire: &[u8]) -> Vec<(Channel, Vec<u8>)> {
+    let mut decoder = FrameDecoder::<512>::new();
+    wire.iter()
+        .filter_map(|&b| decoder.feed(b).map(|f| f.map(|(ch, p)| (ch, p.to_vec())).unwrap()))
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "mux")]
+fn mux_writeln_is_one_frame() {
+    use core::fmt::Write;
+
+    let (uart, model) = setup();
+    let mux = Mux::new(&uart);
+    writeln!(mux.channel(Channel::Log), "irq {} took {}us", 42, 7).unwrap();
+    mux.send(Channel::Data, &[0x00, 0x01, 0x00]);
+
+    let frames = decode_frames(model.borrow().transmitted());
+    assert_eq!(
+        frames,
+        [
+            (Channel::Log, b"irq 42 took 7us\n".to_vec()),
+            (Channel::Data, vec![0x00, 0x01, 0x00]),
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "mux")]
+fn mux_long_formatted_frame_spans_cobs_blocks() {
+    use core::fmt::Write;
+
+    let (uart, model) = setup();
+    let text = "x".repeat(300);
+    write!(Mux::new(&uart).channel(Channel::Custom(0x10)), "{}{}", text, 1).unwrap();
+
+    let frames = decode_frames(model.borrow().transmitted());
+    assert_eq!(frames, [(Channel::Custom(0x10), format!("{}1", text).into_bytes())]);
+}
+
+#[test]
+#[cfg(feature = "rs485")]
+fn second_rs485_on_same_de_pin_is_rejected() {
+    use gpio::{GpioBank, GpioPin, GPIO3_BASE};
+    use uart::rs485::Rs485;
+
+    let (uart, _model) = setup();
+    let gpio = sim::map(GPIO3_BASE, 0x100, mmio::sim::GpioModel::new());
+    let bus = Rs485::new(&uart, GpioPin::new(GpioBank::Gpio3, 12)).unwrap();
+    assert!(gpio.borrow().is_output(12));
+
+    let Err(conflict) = Rs485::new(&uart, GpioPin::new(GpioBank::Gpio3, 12)) else {
+        panic!("DE pin claimed twice");
+    };
+    assert_eq!((conflict.owner, conflict.requester), ("rs485", "rs485"));
+
+    drop(bus);
+    assert!(Rs485::new(&uart, GpioPin::new(GpioBank::Gpio3, 12)).is_ok());
+}
+
+#[test]
+#[cfg(feature = "poll")]
+fn rx_drain_poller_moves_fifo_into_buffer() {
+    use poll_loop::RegisterError;
+
+    static POLLED: Uart = Uart::new(UART2_BASE);
+
+    let model = sim::map(UART2_BASE, 0x100, UartModel::new());
+    POLLED
+        .init_with_config(&UartConfig {
+            clock: UartClock::Fixed(24_000_000),
+            ..UartConfig::new(115200)
+        })
+        .unwrap();
+    uart::poll::add_rx_drain(&POLLED).unwrap();
+    assert_eq!(uart::poll::add_rx_drain(&POLLED), Err(RegisterError::Duplicate));
+
+    // 超过 FIFO 深度的数据在一次调用中全部搬走，不会溢出
+    let data: Vec<u8> = (0..100).collect();
+    model.borrow_mut().queue(&data);
+    assert!(!poll_loop::run_once());
+    assert_eq!(model.borrow().rx_level(), 0);
+    assert_eq!(POLLED.rx_available(), data.len());
+    let received: Vec<u8> = std::iter::from_fn(|| POLLED.try_read()).collect();
+    assert_eq!(received, data);
+    assert_eq!(POLLED.take_errors().total(), 0);
+
+    let stats = poll_loop::stats(uart::poll::POLLER_NAME).unwrap();
+    assert_eq!((stats.runs, stats.busy), (1, 0));
+
+    assert!(uart::poll::remove_rx_drain(&POLLED));
+    model.borrow_mut().receive(b"x");
+    poll_loop::run_once();
+    assert_eq!(POLLED.rx_available(), 0);
+    assert_eq!(model.borrow().rx_level(), 1);
+}
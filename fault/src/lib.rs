@@ -0,0 +1,296 @@
+//! 故障注入
+//!
+//! 驱动的错误恢复路径 (CRC 重试、溢出计数、超时退出) 在正常硬件上几乎不会执行。
+//! 驱动在这些路径前声明注入点，测试或调试命令按名称启用后，注入点会在接下来的
+//! 若干次检查中报告故障，驱动按真实错误处理。
+//!
+//! # 注意
+//! - 只在调试构建 (`debug_assertions`) 中生效，发布构建中 [`FaultPoint::should_fail`]
+//!   总是返回 `false`，编译器会删除注入分支
+//! - 注入点是全局的：同一驱动的多个实例共用一个注入点，故障落在最先检查的实例上
+//! - 驱动在初始化时用 [`register`] 登记注入点，登记后才能按名称启用
+//!
+//! # 运行时断言
+//! 本 crate 不提供单独的断言宏。驱动用 `assert!` 检查调用者传入的参数 (例如引脚号)，
+//! 用 `debug_assert!` 检查内部不变量：调试构建中断言失败经 panic 处理函数
+//! (`panic_dump`) 输出位置和寄存器现场，发布构建中 `debug_assert!` 被删除，与注入点相同。
+//! 再加一层断言宏只会重复这两者。
+//!
+//! # 命名
+//! `<驱动>.<故障>`，例如 `uart.rx_overrun`、`mmc.cmd_timeout`
+//!
+//! # 使用示例
+//! ```
+//! use fault::FaultPoint;
+//!
+//! static CMD_TIMEOUT: FaultPoint = FaultPoint::new("demo.cmd_timeout");
+//!
+//! fn send_command() -> Result<(), ()> {
+//!     if CMD_TIMEOUT.should_fail() {
+//!         return Err(());
+//!     }
+//!     Ok(())
+//! }
+//!
+//! fault::register(&CMD_TIMEOUT).unwrap();
+//!
+//! // 跳过 1 次后注入 2 次
+//! fault::arm("demo.cmd_timeout", 1, 2).unwrap();
+//! assert_eq!(send_command(), Ok(()));
+//! assert_eq!(send_command(), Err(()));
+//! assert_eq!(send_command(), Err(()));
+//! assert_eq!(send_command(), Ok(()));
+//! assert_eq!(CMD_TIMEOUT.injected(), 2);
+//! ```
+
+#![no_std]
+
+use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use spinlock::SpinLock;
+
+/// 最多可登记的注入点数量
+pub const MAX_POINTS: usize = 32;
+
+/// 注入次数不限，直到 [`FaultPoint::disarm`]
+pub const ALWAYS: u32 = u32::MAX;
+
+/// 当前构建是否支持故障注入
+pub const ENABLED: bool = cfg!(debug_assertions);
+
+/// 故障注入点
+pub struct FaultPoint {
+    name: &'static str,
+    /// 启用后先跳过的检查次数
+    skip: AtomicU32,
+    /// 剩余的注入次数，0 表示未启用
+    remaining: AtomicU32,
+    /// 累计注入次数
+    injected: AtomicU32,
+}
+
+impl FaultPoint {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            skip: AtomicU32::new(0),
+            remaining: AtomicU32::new(0),
+            injected: AtomicU32::new(0),
+        }
+    }
+
+    /// 注入点名称
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 检查是否注入故障，驱动在错误路径前调用
+    ///
+    /// # 返回值
+    /// 返回 `true` 时驱动应按对应的真实错误处理
+    #[inline]
+    pub fn should_fail(&self) -> bool {
+        if !ENABLED || self.remaining.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+        self.trigger()
+    }
+
+    #[cold]
+    fn trigger(&self) -> bool {
+        if self
+            .skip
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |s| s.checked_sub(1))
+            .is_ok()
+        {
+            return false;
+        }
+        let taken = self
+            .remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |r| match r {
+                0 => None,
+                ALWAYS => Some(ALWAYS),
+                r => Some(r - 1),
+            })
+            .is_ok();
+        if taken {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+            klog::debug!("fault injected: {}", self.name);
+        }
+        taken
+    }
+
+    /// 启用注入点
+    ///
+    /// # 参数
+    /// - `skip`: 先跳过的检查次数
+    /// - `times`: 之后注入的次数，[`ALWAYS`] 表示直到禁用
+    pub fn arm(&self, skip: u32, times: u32) {
+        // 先清零 remaining，避免另一个核看到新的 remaining 和旧的 skip
+        self.remaining.store(0, Ordering::Relaxed);
+        self.skip.store(skip, Ordering::Relaxed);
+        self.remaining.store(times, Ordering::Release);
+    }
+
+    /// 禁用注入点
+    pub fn disarm(&self) {
+        self.remaining.store(0, Ordering::Relaxed);
+        self.skip.store(0, Ordering::Relaxed);
+    }
+
+    /// 是否还有待注入的故障
+    pub fn is_armed(&self) -> bool {
+        self.remaining.load(Ordering::Relaxed) != 0
+    }
+
+    /// 累计注入次数
+    pub fn injected(&self) -> u32 {
+        self.injected.load(Ordering::Relaxed)
+    }
+}
+
+/// 注入点操作失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultError {
+    /// 已登记 [`MAX_POINTS`] 个注入点
+    Full,
+    /// 另一个同名注入点已登记
+    Duplicate,
+    /// 没有这个名称的注入点
+    NotFound,
+}
+
+impl fmt::Display for FaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultError::Full => f.write_str("fault point table full"),
+            FaultError::Duplicate => f.write_str("fault point name already used"),
+            FaultError::NotFound => f.write_str("no such fault point"),
+        }
+    }
+}
+
+/// 注入点表
+static REGISTRY: SpinLock<[Option<&'static FaultPoint>; MAX_POINTS]> =
+    SpinLock::new([None; MAX_POINTS]);
+
+/// 登记注入点
+///
+/// 重复登记同一个注入点 (例如驱动多次初始化) 不是错误
+///
+/// # 返回值
+/// - `Ok(())`: 登记成功
+/// - `Err(FaultError)`: 表已满或名称被另一个注入点占用
+pub fn register(point: &'static FaultPoint) -> Result<(), FaultError> {
+    let mut points = REGISTRY.lock();
+    if let Some(p) = points.iter().flatten().find(|p| p.name == point.name) {
+        return if core::ptr::eq(*p, point) {
+            Ok(())
+        } else {
+            Err(FaultError::Duplicate)
+        };
+    }
+    let slot = points
+        .iter_mut()
+        .find(|p| p.is_none())
+        .ok_or(FaultError::Full)?;
+    *slot = Some(point);
+    Ok(())
+}
+
+/// 按名称查找注入点
+pub fn find(name: &str) -> Option<&'static FaultPoint> {
+    REGISTRY.lock().iter().flatten().copied().find(|p| p.name == name)
+}
+
+/// 按名称启用注入点，参数见 [`FaultPoint::arm`]
+pub fn arm(name: &str, skip: u32, times: u32) -> Result<(), FaultError> {
+    find(name).ok_or(FaultError::NotFound)?.arm(skip, times);
+    Ok(())
+}
+
+/// 按名称禁用注入点
+pub fn disarm(name: &str) -> Result<(), FaultError> {
+    find(name).ok_or(FaultError::NotFound)?.disarm();
+    Ok(())
+}
+
+/// 禁用所有注入点
+pub fn disarm_all() {
+    let points = *REGISTRY.lock();
+    for p in points.iter().flatten() {
+        p.disarm();
+    }
+}
+
+/// 输出所有注入点的状态
+pub fn write_points(w: &mut dyn fmt::Write) -> fmt::Result {
+    let points = *REGISTRY.lock();
+    writeln!(
+        w,
+        "{:<24} {:>8} {:>10} {:>10}",
+        "name", "skip", "remaining", "injected"
+    )?;
+    for p in points.iter().flatten() {
+        let remaining = p.remaining.load(Ordering::Relaxed);
+        write!(w, "{:<24} {:>8} ", p.name, p.skip.load(Ordering::Relaxed))?;
+        if remaining == ALWAYS {
+            write!(w, "{:>10}", "always")?;
+        } else {
+            write!(w, "{:>10}", remaining)?;
+        }
+        writeln!(w, " {:>10}", p.injected())?;
+    }
+    Ok(())
+}
+
+/// `fault` 调试命令
+///
+/// 签名与 Shell 命令处理函数相同 (`args[0]` 为命令名)，板级代码用
+/// `shell::register_with_help("fault", "inject driver faults", fault::command)` 注册。
+/// ```text
+/// fault                               列出注入点
+/// fault arm <name> [times] [skip]     启用注入点 (times 默认 1，`always` 表示不限)
+/// fault disarm <name|all>             禁用注入点
+/// ```
+pub fn command(args: &[&str], out: &mut dyn fmt::Write) -> fmt::Result {
+    if !ENABLED {
+        return writeln!(out, "fault injection is disabled in release builds");
+    }
+    // 跳过命令名
+    let args = args.get(1..).unwrap_or_default();
+    let result = match args {
+        [] | ["list"] => return write_points(out),
+        ["arm", name, rest @ ..] if rest.len() <= 2 => {
+            let times = match rest.first() {
+                None => Some(1),
+                Some(&"always") => Some(ALWAYS),
+                Some(s) => s.parse().ok(),
+            };
+            let skip = match rest.get(1) {
+                None => Some(0),
+                Some(s) => s.parse().ok(),
+            };
+            match (times, skip) {
+                (Some(times), Some(skip)) => arm(name, skip, times),
+                _ => return writeln!(out, "fault: invalid number"),
+            }
+        }
+        ["disarm", "all"] => {
+            disarm_all();
+            Ok(())
+        }
+        ["disarm", name] => disarm(name),
+        _ => {
+            return writeln!(
+                out,
+                "usage: fault [list | arm <name> [times] [skip] | disarm <name|all>]"
+            )
+        }
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) => writeln!(out, "fault: {}", e),
+    }
+}
@@ -0,0 +1,133 @@
+//! 调试通信通道 (DCC) 控制台
+//!
+//! 通过 DBGDTRTX_EL0/DBGDTRRX_EL0 与调试器逐字节交换字符，
+//! 在调试器的 DCC 终端 (如 Arm Development Studio 的 Channel 0) 中收发。
+//!
+//! # 注意
+//! - 没有调试器读取时发送寄存器一直为满，每个字符最多等待 [`DCC_TX_POLLS`] 次，
+//!   超时后进入丢弃模式，直到调试器再次取走数据，避免没接调试器时拖慢启动
+//! - 非 aarch64 目标上所有操作为空
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use uart::console::ConsoleSink;
+
+/// 等待发送寄存器空的最大轮询次数
+pub const DCC_TX_POLLS: u32 = 10_000;
+
+/// MDCCSR_EL0.TXfull: DBGDTRTX_EL0 中的数据尚未被调试器取走
+const MDCCSR_TXFULL: u64 = 1 << 29;
+/// MDCCSR_EL0.RXfull: DBGDTRRX_EL0 中有调试器写入的数据
+const MDCCSR_RXFULL: u64 = 1 << 30;
+
+/// DCC 控制台
+///
+/// 可以作为 `static` 注册到全局控制台
+pub struct Dcc {
+    /// 上次发送超时，调试器取走数据前不再等待
+    stalled: AtomicBool,
+}
+
+impl Default for Dcc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dcc {
+    pub const fn new() -> Self {
+        Self {
+            stalled: AtomicBool::new(false),
+        }
+    }
+
+    /// 发送一个字节
+    ///
+    /// # 返回值
+    /// 调试器没有及时取走数据、字节被丢弃时返回 `false`
+    pub fn putc(&self, byte: u8) -> bool {
+        let polls = if self.stalled.load(Ordering::Relaxed) {
+            1
+        } else {
+            DCC_TX_POLLS
+        };
+        for _ in 0..polls {
+            if mdccsr() & MDCCSR_TXFULL == 0 {
+                self.stalled.store(false, Ordering::Relaxed);
+                write_dtr(byte as u32);
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        self.stalled.store(true, Ordering::Relaxed);
+        false
+    }
+
+    /// 发送字符串，`\n` 转换为 `\r\n`
+    pub fn puts(&self, s: &str) {
+        for byte in s.bytes() {
+            if byte == b'\n' {
+                self.putc(b'\r');
+            }
+            self.putc(byte);
+        }
+    }
+
+    /// 读取调试器发来的一个字节 (非阻塞)
+    pub fn getc(&self) -> Option<u8> {
+        if mdccsr() & MDCCSR_RXFULL != 0 {
+            Some(read_dtr() as u8)
+        } else {
+            None
+        }
+    }
+}
+
+impl ConsoleSink for Dcc {
+    fn write_str(&self, s: &str) {
+        self.puts(s);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        Dcc::getc(self)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn mdccsr() -> u64 {
+    let value: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, mdccsr_el0", out(reg) value, options(nomem, nostack));
+    }
+    value
+}
+
+#[cfg(target_arch = "aarch64")]
+fn write_dtr(value: u32) {
+    unsafe {
+        core::arch::asm!("msr dbgdtrtx_el0, {}", in(reg) value as u64, options(nomem, nostack));
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn read_dtr() -> u32 {
+    let value: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, dbgdtrrx_el0", out(reg) value, options(nomem, nostack));
+    }
+    value as u32
+}
+
+/// 非 aarch64: 发送寄存器总是空，没有接收数据
+#[cfg(not(target_arch = "aarch64"))]
+fn mdccsr() -> u64 {
+    0
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn write_dtr(_: u32) {}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn read_dtr() -> u32 {
+    0
+}
@@ -0,0 +1,97 @@
+//! JTAG/SWD 调试辅助
+//!
+//! 用 DSTREAM、J-Link 等调试器做板级调试时：
+//! - [`wait_for_debugger`] 让 CPU 停在已知的循环中，等调试器连接、设置断点后再继续启动
+//! - [`dcc::Dcc`] 通过调试通信通道 (DCC) 输出控制台，不需要串口线
+//!
+//! # 继续启动
+//! CPU 停在 [`wait_for_debugger`] 中时，在调试器中把 `WCOS_DEBUGGER_ATTACHED` 置为 1：
+//! ```text
+//! (gdb) set var WCOS_DEBUGGER_ATTACHED = 1
+//! (gdb) continue
+//! ```
+//!
+//! # 使用示例
+//! ```no_run
+//! use uart::console;
+//!
+//! static DCC: jtag::dcc::Dcc = jtag::dcc::Dcc::new();
+//! console().register(&DCC).unwrap();
+//!
+//! // 开启 `wait-at-boot` feature 时在这里等待调试器
+//! jtag::boot_hook();
+//! ```
+
+#![no_std]
+
+pub mod dcc;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// 调试器已连接标志，由调试器写 1 后 [`wait_for_debugger`] 返回
+///
+/// 使用固定的符号名，方便在调试器中按名称修改
+#[no_mangle]
+pub static WCOS_DEBUGGER_ATTACHED: AtomicBool = AtomicBool::new(false);
+
+/// 调试器是否已通过 `WCOS_DEBUGGER_ATTACHED` 通知继续
+pub fn debugger_attached() -> bool {
+    WCOS_DEBUGGER_ATTACHED.load(Ordering::Acquire)
+}
+
+/// 停在循环中等待调试器
+///
+/// 等待期间屏蔽本核 IRQ/FIQ (调试异常和停机不受影响)，
+/// 调试器把 `WCOS_DEBUGGER_ATTACHED` 置为 1 后恢复中断屏蔽状态并返回。
+/// 标志已置位时立即返回。
+pub fn wait_for_debugger() {
+    if debugger_attached() {
+        return;
+    }
+    klog::info!(
+        "waiting for debugger: set WCOS_DEBUGGER_ATTACHED = 1 at {:p}",
+        &WCOS_DEBUGGER_ATTACHED
+    );
+    let daif = irq_save();
+    while !debugger_attached() {
+        core::hint::spin_loop();
+    }
+    irq_restore(daif);
+    klog::info!("debugger attached");
+}
+
+/// 启动流程中的调试器等待点
+///
+/// 开启 `wait-at-boot` feature 时调用 [`wait_for_debugger`]，否则什么都不做
+#[inline(always)]
+pub fn boot_hook() {
+    if cfg!(feature = "wait-at-boot") {
+        wait_for_debugger();
+    }
+}
+
+/// 屏蔽本核 IRQ 和 FIQ，返回之前的 DAIF
+#[cfg(target_arch = "aarch64")]
+fn irq_save() -> u64 {
+    let daif: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, daif", "msr daifset, #3", out(reg) daif, options(nostack));
+    }
+    daif
+}
+
+/// 恢复 DAIF
+#[cfg(target_arch = "aarch64")]
+fn irq_restore(daif: u64) {
+    unsafe {
+        core::arch::asm!("msr daif, {}", in(reg) daif, options(nostack));
+    }
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn irq_save() -> u64 {
+    0
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn irq_restore(_: u64) {}
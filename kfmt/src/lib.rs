@@ -0,0 +1,157 @@
+//! no_std 格式化辅助工具
+//!
+//! 系统目前没有堆分配器，`alloc::format!` 不可用。本 crate 提供：
+//! - [`FmtBuf`]: 固定容量的字符串缓冲区，实现 `fmt::Write`，超出容量时截断
+//! - [`bformat!`]: 类似 `format!` 的宏，结果写入 `FmtBuf`
+//! - [`hexdump`]: 经典 `偏移 + 十六进制 + ASCII` 布局的内存转储
+//!
+//! # 使用示例
+//! ```no_run
+//! use kfmt::{bformat, hexdump, FmtBuf};
+//!
+//! let msg = bformat!(32, "lsr = {:#x}", 0x60);
+//! assert_eq!(msg.as_str(), "lsr = 0x60");
+//!
+//! let mut out = FmtBuf::<128>::new();
+//! hexdump(&mut out, 0x4000_0000, b"Hello").unwrap();
+//! ```
+
+#![no_std]
+
+use core::fmt;
+
+/// 固定容量的格式化缓冲区
+///
+/// # 截断
+/// 写入超出容量 `N` 时丢弃多余内容（按 UTF-8 字符边界截断），
+/// 并通过 [`FmtBuf::is_truncated`] 报告，写入本身不返回错误，
+/// 避免日志输出因为缓冲区不足而中断。
+pub struct FmtBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> FmtBuf<N> {
+    /// 创建空缓冲区
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// 已写入的内容
+    pub fn as_str(&self) -> &str {
+        // write_str 只按字符边界拷贝，内容始终是合法 UTF-8
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    /// 已写入的字节
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// 已写入的字节数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 缓冲区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 是否发生过截断
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// 清空缓冲区以便复用
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+}
+
+impl<const N: usize> Default for FmtBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for FmtBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = N - self.len;
+        let mut take = s.len().min(room);
+        if take < s.len() {
+            self.truncated = true;
+            while !s.is_char_boundary(take) {
+                take -= 1;
+            }
+        }
+        self.buf[self.len..self.len + take].copy_from_slice(&s.as_bytes()[..take]);
+        self.len += take;
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Display for FmtBuf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 类似 `format!` 的宏，结果写入容量为 `N` 的 [`FmtBuf`]
+///
+/// # 示例
+/// ```no_run
+/// let s = kfmt::bformat!(16, "{}-{}", 1, 2);
+/// assert_eq!(s.as_str(), "1-2");
+/// ```
+#[macro_export]
+macro_rules! bformat {
+    ($cap:expr, $($arg:tt)*) => {{
+        use core::fmt::Write;
+        let mut buf = $crate::FmtBuf::<$cap>::new();
+        let _ = write!(buf, $($arg)*);
+        buf
+    }};
+}
+
+/// 以经典布局转储一段内存
+///
+/// # 参数
+/// - `w`: 输出目标 (例如 `&EarlyCon`、`FmtBuf`)
+/// - `addr`: 第一行显示的起始地址
+/// - `data`: 要转储的数据
+///
+/// # 输出格式
+/// 与 `hexdump -C` 一致，每行 16 字节：
+/// ```text
+/// 40000000  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 0a 00 00  |Hello, World!...|
+/// ```
+pub fn hexdump<W: fmt::Write>(w: &mut W, addr: usize, data: &[u8]) -> fmt::Result {
+    for (i, line) in data.chunks(16).enumerate() {
+        write!(w, "{:08x}  ", addr + i * 16)?;
+
+        for col in 0..16 {
+            match line.get(col) {
+                Some(b) => write!(w, "{:02x} ", b)?,
+                None => w.write_str("   ")?,
+            }
+            if col == 7 {
+                w.write_char(' ')?;
+            }
+        }
+
+        w.write_str(" |")?;
+        for &b in line {
+            let c = if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' };
+            w.write_char(c)?;
+        }
+        w.write_str("|\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+//! FmtBuf 截断与 hexdump 布局
+//!
+//! 运行: `cargo test -p kfmt`
+
+use kfmt::{bformat, hexdump, FmtBuf};
+
+#[test]
+fn fmtbuf_truncates_on_char_boundary() {
+    let s = bformat!(5, "ab{}", "é€");
+    assert_eq!(s.as_str(), "abé");
+    assert!(s.is_truncated());
+
+    let s = bformat!(8, "{}-{}", 1, 2);
+    assert_eq!(s.as_str(), "1-2");
+    assert!(!s.is_truncated());
+}
+
+#[test]
+fn hexdump_pads_short_last_line() {
+    let mut out = FmtBuf::<256>::new();
+    hexdump(&mut out, 0x4000_0000, b"0123456789abcdefXY").unwrap();
+    assert_eq!(
+        out.as_str(),
+        "40000000  30 31 32 33 34 35 36 37  38 39 61 62 63 64 65 66  |0123456789abcdef|\n\
+         40000010  58 59                                             |XY|\n"
+    );
+}
@@ -0,0 +1,337 @@
+//! 内核日志
+//!
+//! `error!`/`warn!`/`info!`/`debug!`/`trace!` 分级日志宏，支持：
+//! - 编译期过滤: 通过 `max-level-*` / `release-max-level-*` feature 设定最高级别，
+//!   更高级别的日志调用 (包括参数格式化) 在编译时被移除
+//! - 运行时过滤: [`set_max_level`] 设置全局级别，[`set_target_level`] 按目标单独设置
+//! - 目标 (target): 默认为调用处的 `module_path!()`，即 crate 名加模块路径，
+//!   也可以用 `target: "..."` 显式指定
+//!
+//! 日志本身不依赖任何驱动，输出目标由 [`set_sink`] 设置 (通常为 UART 控制台)，
+//! 设置之前的日志被丢弃。
+//!
+//! # 输出格式
+//! ```text
+//! [WARN  mmc] command 17 timed out
+//! ```
+//! 调用 [`set_timestamps`] 后每行前加上 ARM 通用定时器的时间戳 (秒.微秒，见 [`timer::timestamp_us`])，
+//! 与 Linux printk 时间戳相同，便于从串口记录中分析启动顺序和耗时：
+//! ```text
+//! [    1.204317] [WARN  mmc] command 17 timed out
+//! ```
+//!
+//! # 使用示例
+//! ```no_run
+//! use klog::{info, debug, LevelFilter};
+//!
+//! // 通常直接使用 UART 控制台: klog::set_sink(uart::console::write_args)
+//! fn sink(line: core::fmt::Arguments) {
+//!     // 输出到调试通道
+//! }
+//!
+//! klog::set_sink(sink);
+//! klog::set_max_level(LevelFilter::Info);
+//! // 只打开 MMC 驱动的调试输出
+//! klog::set_target_level("mmc", LevelFilter::Debug);
+//!
+//! info!("boot: {} MB DRAM", 8192);
+//! debug!(target: "mmc", "clock set to {} Hz", 400_000);
+//!
+//! // 数据块按 hexdump -C 布局输出，每行一条日志
+//! let block = [0u8; 32];
+//! klog::hexdump(klog::Level::Debug, "mmc", 0x200, &block);
+//! ```
+
+#![no_std]
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+use kfmt::FmtBuf;
+use spinlock::SpinLock;
+use timer::timestamp_us;
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl Level {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// 日志级别过滤器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LevelFilter {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl LevelFilter {
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LevelFilter::Off,
+            1 => LevelFilter::Error,
+            2 => LevelFilter::Warn,
+            3 => LevelFilter::Info,
+            4 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    /// `level` 是否通过过滤
+    pub const fn allows(self, level: Level) -> bool {
+        level as u8 <= self as u8
+    }
+}
+
+/// 编译期最高日志级别，由 feature 决定
+pub const STATIC_MAX_LEVEL: LevelFilter = static_max_level();
+
+const fn static_max_level() -> LevelFilter {
+    if !cfg!(debug_assertions) {
+        if cfg!(feature = "release-max-level-off") {
+            return LevelFilter::Off;
+        } else if cfg!(feature = "release-max-level-error") {
+            return LevelFilter::Error;
+        } else if cfg!(feature = "release-max-level-warn") {
+            return LevelFilter::Warn;
+        } else if cfg!(feature = "release-max-level-info") {
+            return LevelFilter::Info;
+        } else if cfg!(feature = "release-max-level-debug") {
+            return LevelFilter::Debug;
+        }
+    }
+    if cfg!(feature = "max-level-off") {
+        LevelFilter::Off
+    } else if cfg!(feature = "max-level-error") {
+        LevelFilter::Error
+    } else if cfg!(feature = "max-level-warn") {
+        LevelFilter::Warn
+    } else if cfg!(feature = "max-level-info") {
+        LevelFilter::Info
+    } else if cfg!(feature = "max-level-debug") {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Trace
+    }
+}
+
+/// 按目标设置级别的最大条目数
+pub const MAX_TARGET_FILTERS: usize = 8;
+
+/// 全局运行时级别
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(LevelFilter::Info as u8);
+
+/// 输出函数，0 表示未设置
+static SINK: AtomicUsize = AtomicUsize::new(0);
+
+/// 是否输出时间戳
+static TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+
+/// 按目标的级别设置
+static TARGET_FILTERS: SpinLock<[Option<(&'static str, LevelFilter)>; MAX_TARGET_FILTERS]> =
+    SpinLock::new([None; MAX_TARGET_FILTERS]);
+
+/// 是否设置过按目标的级别 (未设置时跳过加锁查表)
+static HAS_TARGET_FILTERS: AtomicBool = AtomicBool::new(false);
+
+/// 设置日志输出函数
+///
+/// 输出函数收到的是完整的一行 (含换行)，应当一次性输出以免与其他输出交错
+pub fn set_sink(sink: fn(fmt::Arguments)) {
+    SINK.store(sink as usize, Ordering::Release);
+}
+
+/// 设置是否在每行前输出时间戳 (默认关闭)
+pub fn set_timestamps(enable: bool) {
+    TIMESTAMPS.store(enable, Ordering::Relaxed);
+}
+
+/// 设置全局运行时级别 (默认 `Info`)
+pub fn set_max_level(filter: LevelFilter) {
+    MAX_LEVEL.store(filter as u8, Ordering::Relaxed);
+}
+
+/// 全局运行时级别
+pub fn max_level() -> LevelFilter {
+    LevelFilter::from_u8(MAX_LEVEL.load(Ordering::Relaxed))
+}
+
+/// `target` 与过滤项 `filter` 匹配: 完全相同，或 `target` 是 `filter` 的子模块
+fn target_matches(target: &str, filter: &str) -> bool {
+    match target.strip_prefix(filter) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
+
+/// 为目标单独设置级别，覆盖全局级别
+///
+/// `target` 同时匹配其子模块，例如 `"mmc"` 匹配 `mmc::card`；多项匹配时最长者生效。
+///
+/// # 返回值
+/// 表已满 (超过 [`MAX_TARGET_FILTERS`] 项) 时返回 `false`
+pub fn set_target_level(target: &'static str, filter: LevelFilter) -> bool {
+    let mut entries = TARGET_FILTERS.lock();
+    let slot = entries
+        .iter()
+        .position(|e| matches!(e, Some((t, _)) if *t == target))
+        .or_else(|| entries.iter().position(Option::is_none));
+    match slot {
+        Some(i) => {
+            entries[i] = Some((target, filter));
+            HAS_TARGET_FILTERS.store(true, Ordering::Release);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 清除目标的单独设置，恢复使用全局级别
+pub fn clear_target_level(target: &str) {
+    for entry in TARGET_FILTERS.lock().iter_mut() {
+        if matches!(entry, Some((t, _)) if *t == target) {
+            *entry = None;
+        }
+    }
+}
+
+/// `target` 当前生效的运行时级别
+///
+/// 按目标的设置正在被修改时使用全局级别 (不等待锁，避免中断处理函数
+/// 在本核持锁时输出日志而死锁)
+pub fn target_level(target: &str) -> LevelFilter {
+    if !HAS_TARGET_FILTERS.load(Ordering::Acquire) {
+        return max_level();
+    }
+    TARGET_FILTERS
+        .try_lock()
+        .and_then(|entries| {
+            entries
+                .iter()
+                .flatten()
+                .filter(|(t, _)| target_matches(target, t))
+                .max_by_key(|(t, _)| t.len())
+                .map(|&(_, filter)| filter)
+        })
+        .unwrap_or_else(max_level)
+}
+
+/// 指定级别和目标的日志是否会被输出
+pub fn enabled(level: Level, target: &str) -> bool {
+    STATIC_MAX_LEVEL.allows(level) && target_level(target).allows(level)
+}
+
+#[doc(hidden)]
+pub fn __log(level: Level, target: &str, args: fmt::Arguments) {
+    let sink = SINK.load(Ordering::Acquire);
+    if sink == 0 || !enabled(level, target) {
+        return;
+    }
+    // SINK 中只会存入 set_sink 传入的函数指针
+    let sink: fn(fmt::Arguments) = unsafe { core::mem::transmute(sink) };
+    match TIMESTAMPS.load(Ordering::Relaxed).then(timestamp_us).flatten() {
+        Some(us) => sink(format_args!(
+            "[{:5}.{:06}] [{:<5} {}] {}\n",
+            us / 1_000_000,
+            us % 1_000_000,
+            level,
+            target,
+            args
+        )),
+        None => sink(format_args!("[{:<5} {}] {}\n", level, target, args)),
+    }
+}
+
+/// `hexdump -C` 布局的一行最多占用的字节数
+const HEXDUMP_LINE_MAX: usize = 80;
+
+/// 以 `hexdump -C` 布局输出一段内存，每 16 字节一条日志
+///
+/// # 参数
+/// - `level`/`target`: 与日志宏相同，按同样的规则过滤
+/// - `addr`: 第一行显示的起始地址
+/// - `data`: 要输出的数据
+pub fn hexdump(level: Level, target: &str, addr: usize, data: &[u8]) {
+    if !enabled(level, target) {
+        return;
+    }
+    for (i, chunk) in data.chunks(16).enumerate() {
+        let mut line = FmtBuf::<HEXDUMP_LINE_MAX>::new();
+        let _ = kfmt::hexdump(&mut line, addr + i * 16, chunk);
+        __log(level, target, format_args!("{}", line.as_str().trim_end()));
+    }
+}
+
+/// 输出指定级别的日志
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $lvl:expr, $($arg:tt)+) => {{
+        let lvl: $crate::Level = $lvl;
+        if $crate::STATIC_MAX_LEVEL.allows(lvl) {
+            $crate::__log(lvl, $target, format_args!($($arg)+));
+        }
+    }};
+    ($lvl:expr, $($arg:tt)+) => {
+        $crate::log!(target: module_path!(), $lvl, $($arg)+)
+    };
+}
+
+/// 错误日志
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => ($crate::log!(target: $target, $crate::Level::Error, $($arg)+));
+    ($($arg:tt)+) => ($crate::log!($crate::Level::Error, $($arg)+));
+}
+
+/// 警告日志
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $($arg:tt)+) => ($crate::log!(target: $target, $crate::Level::Warn, $($arg)+));
+    ($($arg:tt)+) => ($crate::log!($crate::Level::Warn, $($arg)+));
+}
+
+/// 信息日志
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => ($crate::log!(target: $target, $crate::Level::Info, $($arg)+));
+    ($($arg:tt)+) => ($crate::log!($crate::Level::Info, $($arg)+));
+}
+
+/// 调试日志
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => ($crate::log!(target: $target, $crate::Level::Debug, $($arg)+));
+    ($($arg:tt)+) => ($crate::log!($crate::Level::Debug, $($arg)+));
+}
+
+/// 跟踪日志
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => ($crate::log!(target: $target, $crate::Level::Trace, $($arg)+));
+    ($($arg:tt)+) => ($crate::log!($crate::Level::Trace, $($arg)+));
+}
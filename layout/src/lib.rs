@@ -0,0 +1,239 @@
+//! RK3588 内存布局与启动镜像头
+//!
+//! # 参考资料
+//! - RK3588 Technical Reference Manual Part1 Chapter 2 - System Overview (Address Mapping)
+//! - U-Boot: include/configs/rk3588_common.h
+//!
+//! # 内容
+//! - 物理内存映射常量 (DRAM、外设区)
+//! - 链接脚本 `link.ld` 导出的符号访问
+//! - 镜像头 [`ImageHeader`] 的定义与校验
+//!
+//! # 镜像头
+//! `link.ld` 在镜像最前面放置 64 字节的镜像头：
+//! ```text
+//! 0x00  u32  跳转到入口的指令 (b _start)
+//! 0x04  u32  魔数 "WCOS"
+//! 0x08  u32  镜像头版本
+//! 0x0C  u32  镜像头大小
+//! 0x10  u64  加载地址
+//! 0x18  u64  镜像大小 (不含 .bss)
+//! 0x20  u64  入口偏移
+//! 0x28  u64  .bss 大小
+//! 0x30  u32  CRC32 (覆盖镜像头之后到镜像结束的数据)
+//! 0x34  u32  保留
+//! 0x38  u64  保留
+//! ```
+//! 加载器 (U-Boot 脚本、二级引导或固件更新) 可以据此检查镜像是否完整、是否加载到了正确地址。
+//!
+//! 链接时 CRC 字段为 0，`scripts/build.sh` 在 `objcopy -O binary` 之后用
+//! `scripts/image-crc.py` 计算并填入。CRC 为 CRC-32/ISO-HDLC (与 zlib `crc32` 相同)。
+
+#![no_std]
+
+/// DRAM 起始地址
+pub const DRAM_BASE: usize = 0x0000_0000;
+
+/// 低 4GB 地址空间中 DRAM 的结束地址，其上为外设寄存器区
+pub const DRAM_LOW_END: usize = 0xF000_0000;
+
+/// 外设寄存器区起始地址
+pub const MMIO_BASE: usize = 0xF000_0000;
+
+/// 外设寄存器区大小
+pub const MMIO_SIZE: usize = 0x1000_0000;
+
+/// 镜像加载地址 (U-Boot kernel_addr_r)，与 link.ld 中的 LOAD_ADDR 一致
+pub const LOAD_ADDR: usize = 0x0040_0000;
+
+/// 启动栈大小，与 link.ld 中的 STACK_SIZE 一致
+pub const STACK_SIZE: usize = 0x1_0000;
+
+/// 镜像头魔数 "WCOS"
+pub const IMAGE_MAGIC: u32 = u32::from_le_bytes(*b"WCOS");
+
+/// 当前镜像头版本
+pub const IMAGE_HEADER_VERSION: u32 = 2;
+
+/// 镜像头大小
+pub const IMAGE_HEADER_SIZE: usize = 64;
+
+/// 启动镜像头
+///
+/// 由 link.ld 生成，字段均为小端
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ImageHeader {
+    /// 跳转到入口的指令
+    pub branch: u32,
+    /// 魔数，必须为 [`IMAGE_MAGIC`]
+    pub magic: u32,
+    /// 镜像头版本
+    pub version: u32,
+    /// 镜像头大小
+    pub header_size: u32,
+    /// 链接时的加载地址
+    pub load_addr: u64,
+    /// 镜像大小 (含镜像头，不含 .bss)
+    pub image_size: u64,
+    /// 入口相对镜像起始的偏移
+    pub entry_offset: u64,
+    /// .bss 大小
+    pub bss_size: u64,
+    /// 镜像头之后到镜像结束 (`image_size`) 的 CRC32
+    pub image_crc: u32,
+    /// 保留
+    pub reserved0: u32,
+    /// 保留
+    pub reserved1: u64,
+}
+
+/// 镜像头校验错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderError {
+    /// 数据不足一个镜像头
+    TooShort,
+    /// 魔数不匹配
+    BadMagic,
+    /// 不支持的镜像头版本
+    UnsupportedVersion,
+    /// 镜像大小或入口偏移不合理
+    InvalidLayout,
+    /// 镜像数据的 CRC 与镜像头不符
+    BadCrc,
+}
+
+impl ImageHeader {
+    /// 解析并校验完整镜像的镜像头
+    ///
+    /// # 参数
+    /// - `image`: 从镜像头开始、至少 `image_size` 字节的镜像数据
+    ///
+    /// # 返回值
+    /// - `Ok(header)`: 镜像头字段合理且 CRC 正确
+    /// - `Err(HeaderError::TooShort)`: 数据不足 `image_size`
+    /// - `Err(HeaderError::BadCrc)`: 镜像数据损坏
+    pub fn parse(image: &[u8]) -> Result<Self, HeaderError> {
+        let header = Self::parse_header(image)?;
+        header.verify(image)?;
+        Ok(header)
+    }
+
+    /// 只解析镜像头，不校验 CRC
+    ///
+    /// 用于先读出镜像头得到镜像大小，读完整个镜像后再调用 [`verify`](Self::verify)
+    pub fn parse_header(data: &[u8]) -> Result<Self, HeaderError> {
+        if data.len() < IMAGE_HEADER_SIZE {
+            return Err(HeaderError::TooShort);
+        }
+
+        let u32_at = |off: usize| u32::from_le_bytes([data[off], data[off + 1], data[off + 2], data[off + 3]]);
+        let u64_at = |off: usize| u32_at(off) as u64 | ((u32_at(off + 4) as u64) << 32);
+
+        let header = Self {
+            branch: u32_at(0x00),
+            magic: u32_at(0x04),
+            version: u32_at(0x08),
+            header_size: u32_at(0x0C),
+            load_addr: u64_at(0x10),
+            image_size: u64_at(0x18),
+            entry_offset: u64_at(0x20),
+            bss_size: u64_at(0x28),
+            image_crc: u32_at(0x30),
+            reserved0: u32_at(0x34),
+            reserved1: u64_at(0x38),
+        };
+        header.validate()?;
+        Ok(header)
+    }
+
+    /// 检查镜像头字段 (不含 CRC)
+    pub fn validate(&self) -> Result<(), HeaderError> {
+        if self.magic != IMAGE_MAGIC {
+            return Err(HeaderError::BadMagic);
+        }
+        if self.version != IMAGE_HEADER_VERSION {
+            return Err(HeaderError::UnsupportedVersion);
+        }
+        if self.header_size as usize != IMAGE_HEADER_SIZE
+            || self.image_size < IMAGE_HEADER_SIZE as u64
+            || self.entry_offset >= self.image_size
+            || self.memory_end().is_none()
+        {
+            return Err(HeaderError::InvalidLayout);
+        }
+        Ok(())
+    }
+
+    /// 校验镜像数据的 CRC
+    ///
+    /// # 参数
+    /// - `image`: 从镜像头开始的镜像数据，超出 `image_size` 的部分被忽略
+    pub fn verify(&self, image: &[u8]) -> Result<(), HeaderError> {
+        let end = usize::try_from(self.image_size).map_err(|_| HeaderError::InvalidLayout)?;
+        let body = image
+            .get(self.header_size as usize..end)
+            .ok_or(HeaderError::TooShort)?;
+        if crc32(body) != self.image_crc {
+            return Err(HeaderError::BadCrc);
+        }
+        Ok(())
+    }
+
+    /// 入口地址
+    ///
+    /// 加载地址加入口偏移溢出时返回 `None` ([`validate`](Self::validate) 通过的镜像头不会溢出)
+    pub fn entry(&self) -> Option<u64> {
+        self.load_addr.checked_add(self.entry_offset)
+    }
+
+    /// 加载后占用的内存范围 (含 .bss)
+    ///
+    /// 范围结束地址溢出时返回 `None` ([`validate`](Self::validate) 通过的镜像头不会溢出)
+    pub fn memory_range(&self) -> Option<core::ops::Range<u64>> {
+        Some(self.load_addr..self.memory_end()?)
+    }
+
+    fn memory_end(&self) -> Option<u64> {
+        self.load_addr
+            .checked_add(self.image_size)?
+            .checked_add(self.bss_size)
+    }
+}
+
+extern "C" {
+    static __image_start: u8;
+    static __image_end: u8;
+    static __bss_start: u8;
+    static __bss_end: u8;
+    static __stack_bottom: u8;
+    static __stack_top: u8;
+}
+
+/// 当前镜像在内存中的范围 (不含 .bss)
+pub fn image_range() -> core::ops::Range<usize> {
+    core::ptr::addr_of!(__image_start) as usize..core::ptr::addr_of!(__image_end) as usize
+}
+
+/// .bss 段范围，启动代码需要在进入 Rust 之前清零
+pub fn bss_range() -> core::ops::Range<usize> {
+    core::ptr::addr_of!(__bss_start) as usize..core::ptr::addr_of!(__bss_end) as usize
+}
+
+/// 启动栈范围
+pub fn stack_range() -> core::ops::Range<usize> {
+    core::ptr::addr_of!(__stack_bottom) as usize..core::ptr::addr_of!(__stack_top) as usize
+}
+
+/// 当前运行镜像的镜像头
+pub fn current_header() -> &'static ImageHeader {
+    unsafe { &*(core::ptr::addr_of!(__image_start) as *const ImageHeader) }
+}
+
+/// CRC-32/ISO-HDLC，镜像头校验使用
+pub use crc::crc32;
+
+/// 地址是否位于外设寄存器区
+pub const fn is_mmio(addr: usize) -> bool {
+    addr >= MMIO_BASE && addr - MMIO_BASE < MMIO_SIZE
+}
@@ -0,0 +1,99 @@
+//! 镜像头解析与 CRC 校验
+//!
+//! 运行: `cargo test -p layout`
+
+use layout::{crc32, HeaderError, ImageHeader, IMAGE_HEADER_SIZE, IMAGE_HEADER_VERSION, IMAGE_MAGIC, LOAD_ADDR};
+
+const BODY_LEN: usize = 192;
+const ENTRY_OFFSET: u64 = IMAGE_HEADER_SIZE as u64;
+
+/// 按 link.ld 的布局生成一个合法镜像：镜像头 + `BODY_LEN` 字节数据
+fn image() -> Vec<u8> {
+    let body: Vec<u8> = (0..BODY_LEN).map(|i| (i * 7) as u8).collect();
+    let mut image = vec![0u8; IMAGE_HEADER_SIZE];
+    put32(&mut image, 0x00, 0x1400_0000 | (ENTRY_OFFSET >> 2) as u32);
+    put32(&mut image, 0x04, IMAGE_MAGIC);
+    put32(&mut image, 0x08, IMAGE_HEADER_VERSION);
+    put32(&mut image, 0x0C, IMAGE_HEADER_SIZE as u32);
+    put64(&mut image, 0x10, LOAD_ADDR as u64);
+    put64(&mut image, 0x18, (IMAGE_HEADER_SIZE + BODY_LEN) as u64);
+    put64(&mut image, 0x20, ENTRY_OFFSET);
+    put64(&mut image, 0x28, 0x1000);
+    put32(&mut image, 0x30, crc32(&body));
+    image.extend_from_slice(&body);
+    image
+}
+
+/// 表驱动用例：名称、对合法镜像的改动、期望的错误
+type Case = (&'static str, fn(&mut Vec<u8>), HeaderError);
+
+fn put32(image: &mut [u8], off: usize, value: u32) {
+    image[off..off + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put64(image: &mut [u8], off: usize, value: u64) {
+    image[off..off + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn valid_image_parses() {
+    let image = image();
+    let header = ImageHeader::parse(&image).unwrap();
+    assert_eq!(header.branch, 0x1400_0010);
+    assert_eq!(header.image_size, (IMAGE_HEADER_SIZE + BODY_LEN) as u64);
+    assert_eq!(header.entry(), Some(LOAD_ADDR as u64 + ENTRY_OFFSET));
+    let end = LOAD_ADDR as u64 + (IMAGE_HEADER_SIZE + BODY_LEN) as u64 + 0x1000;
+    assert_eq!(header.memory_range(), Some(LOAD_ADDR as u64..end));
+}
+
+#[test]
+fn malformed_headers_are_rejected() {
+    let cases: &[Case] = &[
+        ("short header", |img| img.truncate(IMAGE_HEADER_SIZE - 1), HeaderError::TooShort),
+        ("bad magic", |img| put32(img, 0x04, u32::from_le_bytes(*b"WCOT")), HeaderError::BadMagic),
+        ("old version", |img| put32(img, 0x08, IMAGE_HEADER_VERSION - 1), HeaderError::UnsupportedVersion),
+        ("new version", |img| put32(img, 0x08, IMAGE_HEADER_VERSION + 1), HeaderError::UnsupportedVersion),
+        ("header size", |img| put32(img, 0x0C, 32), HeaderError::InvalidLayout),
+        ("image smaller than header", |img| put64(img, 0x18, 32), HeaderError::InvalidLayout),
+        ("entry past image end", |img| put64(img, 0x20, (IMAGE_HEADER_SIZE + BODY_LEN) as u64), HeaderError::InvalidLayout),
+        ("load addr + size overflow", |img| put64(img, 0x10, u64::MAX - 16), HeaderError::InvalidLayout),
+        ("bss size overflow", |img| put64(img, 0x28, u64::MAX), HeaderError::InvalidLayout),
+    ];
+
+    for (name, corrupt, expected) in cases {
+        let mut img = image();
+        corrupt(&mut img);
+        let Err(err) = ImageHeader::parse_header(&img) else {
+            panic!("{name}: accepted");
+        };
+        assert_eq!(err, *expected, "{name}");
+        assert_eq!(ImageHeader::parse(&img).unwrap_err(), *expected, "{name}");
+    }
+}
+
+#[test]
+fn corrupted_images_fail_verification() {
+    let cases: &[Case] = &[
+        ("body byte flipped", |img| img[IMAGE_HEADER_SIZE + 5] ^= 0x01, HeaderError::BadCrc),
+        ("last byte flipped", |img| *img.last_mut().unwrap() ^= 0x80, HeaderError::BadCrc),
+        ("crc field", |img| img[0x30] ^= 0xFF, HeaderError::BadCrc),
+        ("unfilled crc", |img| put32(img, 0x30, 0), HeaderError::BadCrc),
+        ("truncated body", |img| img.truncate(IMAGE_HEADER_SIZE + BODY_LEN - 1), HeaderError::TooShort),
+    ];
+
+    for (name, corrupt, expected) in cases {
+        let mut img = image();
+        corrupt(&mut img);
+        // 镜像头本身没问题，CRC 要读完整个镜像才能检查
+        let header = ImageHeader::parse_header(&img).unwrap();
+        assert_eq!(header.verify(&img), Err(*expected), "{name}");
+        assert_eq!(ImageHeader::parse(&img).unwrap_err(), *expected, "{name}");
+    }
+}
+
+#[test]
+fn verify_ignores_bytes_past_image_size() {
+    let mut img = image();
+    img.extend_from_slice(&[0xFF; 512]);
+    assert!(ImageHeader::parse(&img).is_ok());
+}
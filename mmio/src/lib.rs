@@ -0,0 +1,51 @@
+//! MMIO 寄存器访问
+//!
+//! 驱动通过这里的 [`read_volatile`]/[`write_volatile`] 访问寄存器，
+//! 用法与 `core::ptr` 中的同名函数相同 (只支持 32 位寄存器)。
+//!
+//! - 默认: 直接转发到 `core::ptr::read_volatile`/`write_volatile`，全部内联，没有额外开销
+//! - `sim` feature: 转发到 [`sim`] 中注册的主机端软件设备模型，
+//!   驱动的初始化流程和状态机可以用 `cargo test` 在主机上测试，不需要硬件或 QEMU
+
+#![cfg_attr(not(feature = "sim"), no_std)]
+
+#[cfg(feature = "sim")]
+pub mod sim;
+
+/// 读取 32 位寄存器
+///
+/// # Safety
+/// 与 `core::ptr::read_volatile` 相同，`src` 必须是有效的寄存器地址
+#[cfg(not(feature = "sim"))]
+#[inline(always)]
+pub unsafe fn read_volatile(src: *const u32) -> u32 {
+    core::ptr::read_volatile(src)
+}
+
+/// 写入 32 位寄存器
+///
+/// # Safety
+/// 与 `core::ptr::write_volatile` 相同，`dst` 必须是有效的寄存器地址
+#[cfg(not(feature = "sim"))]
+#[inline(always)]
+pub unsafe fn write_volatile(dst: *mut u32, value: u32) {
+    core::ptr::write_volatile(dst, value)
+}
+
+/// 读取 32 位寄存器 (由设备模型处理)
+///
+/// # Safety
+/// 模拟模式下总是安全的，保留 `unsafe` 以便与硬件模式的签名一致
+#[cfg(feature = "sim")]
+pub unsafe fn read_volatile(src: *const u32) -> u32 {
+    sim::read(src as usize)
+}
+
+/// 写入 32 位寄存器 (由设备模型处理)
+///
+/// # Safety
+/// 模拟模式下总是安全的，保留 `unsafe` 以便与硬件模式的签名一致
+#[cfg(feature = "sim")]
+pub unsafe fn write_volatile(dst: *mut u32, value: u32) {
+    sim::write(dst as usize, value)
+}
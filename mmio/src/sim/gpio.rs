@@ -0,0 +1,79 @@
+//! GPIO 控制器模型
+
+use super::Device;
+
+const GPIO_SWPORT_DR_L: usize = 0x0000;
+const GPIO_SWPORT_DR_H: usize = 0x0004;
+const GPIO_SWPORT_DDR_L: usize = 0x0008;
+const GPIO_SWPORT_DDR_H: usize = 0x000C;
+const GPIO_EXT_PORT: usize = 0x0070;
+
+/// 按写使能 (高 16 位) 更新 `reg` 的 16 位一半，`shift` 为 0 (_L) 或 16 (_H)
+fn write_masked(reg: &mut u32, shift: u32, value: u32) {
+    let mask = (value >> 16) << shift;
+    *reg = (*reg & !mask) | ((value & 0xFFFF) << shift & mask);
+}
+
+/// GPIO Bank 模型
+///
+/// 输出引脚的实际电平等于数据寄存器，输入引脚的电平由测试通过
+/// [`set_input`](Self::set_input) 设置。
+/// 数据和方向寄存器按 RK3588 的 _L/_H 写使能方式访问，高 16 位读出为 0。
+#[derive(Default)]
+pub struct GpioModel {
+    dr: u32,
+    ddr: u32,
+    inputs: u32,
+}
+
+impl GpioModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置外部输入到 `pin` 的电平
+    pub fn set_input(&mut self, pin: u8, high: bool) {
+        if high {
+            self.inputs |= 1 << pin;
+        } else {
+            self.inputs &= !(1 << pin);
+        }
+    }
+
+    /// `pin` 是否配置为输出
+    pub fn is_output(&self, pin: u8) -> bool {
+        self.ddr & (1 << pin) != 0
+    }
+
+    /// `pin` 的实际电平
+    pub fn level(&self, pin: u8) -> bool {
+        self.ext() & (1 << pin) != 0
+    }
+
+    fn ext(&self) -> u32 {
+        (self.dr & self.ddr) | (self.inputs & !self.ddr)
+    }
+}
+
+impl Device for GpioModel {
+    fn read(&mut self, offset: usize) -> u32 {
+        match offset {
+            GPIO_SWPORT_DR_L => self.dr & 0xFFFF,
+            GPIO_SWPORT_DR_H => self.dr >> 16,
+            GPIO_SWPORT_DDR_L => self.ddr & 0xFFFF,
+            GPIO_SWPORT_DDR_H => self.ddr >> 16,
+            GPIO_EXT_PORT => self.ext(),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, offset: usize, value: u32) {
+        match offset {
+            GPIO_SWPORT_DR_L => write_masked(&mut self.dr, 0, value),
+            GPIO_SWPORT_DR_H => write_masked(&mut self.dr, 16, value),
+            GPIO_SWPORT_DDR_L => write_masked(&mut self.ddr, 0, value),
+            GPIO_SWPORT_DDR_H => write_masked(&mut self.ddr, 16, value),
+            _ => {}
+        }
+    }
+}
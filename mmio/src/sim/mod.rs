@@ -0,0 +1,139 @@
+//! 主机端寄存器模型
+//!
+//! 测试先用 [`map`] 把设备模型挂到驱动使用的基址上，之后驱动的寄存器访问
+//! 都会转发到对应的模型。[`map`] 返回模型的共享句柄，测试通过它注入输入
+//! (例如 UART 接收数据、GPIO 输入电平) 并检查驱动写入的结果。
+//!
+//! 地址映射表是线程局部的，`cargo test` 并行运行的测试之间互不影响。
+//! 访问没有映射的地址会 panic，并给出地址，便于发现驱动访问了意料之外的寄存器。
+//!
+//! # 使用示例
+//! ```
+//! use mmio::sim::{self, Ram};
+//!
+//! let ram = sim::map(0xFE00_0000, 0x100, Ram::new(0x100));
+//! unsafe {
+//!     mmio::write_volatile(0xFE00_0010 as *mut u32, 0x55);
+//!     assert_eq!(mmio::read_volatile(0xFE00_0010 as *const u32), 0x55);
+//! }
+//! assert_eq!(ram.borrow().word(0x10), 0x55);
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::vec::Vec;
+
+mod gpio;
+mod mshc;
+mod uart;
+
+pub use gpio::GpioModel;
+pub use mshc::MshcModel;
+pub use uart::UartModel;
+
+/// 设备模型
+///
+/// `offset` 为相对设备基址的字节偏移
+pub trait Device {
+    /// 处理寄存器读取 (可以有副作用，例如读取 RBR 弹出 RX FIFO)
+    fn read(&mut self, offset: usize) -> u32;
+    /// 处理寄存器写入
+    fn write(&mut self, offset: usize, value: u32);
+}
+
+/// 设备模型的共享句柄
+pub type Handle<D> = Rc<RefCell<D>>;
+
+/// 一段地址映射
+struct Mapping {
+    base: usize,
+    size: usize,
+    device: Rc<RefCell<dyn Device>>,
+}
+
+thread_local! {
+    static BUS: RefCell<Vec<Mapping>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 把设备模型映射到 `[base, base + size)`
+///
+/// 与已有映射重叠时，新映射优先
+///
+/// # 返回值
+/// 模型的共享句柄
+pub fn map<D: Device + 'static>(base: usize, size: usize, device: D) -> Handle<D> {
+    let handle = Rc::new(RefCell::new(device));
+    BUS.with(|bus| {
+        bus.borrow_mut().insert(
+            0,
+            Mapping {
+                base,
+                size,
+                device: handle.clone(),
+            },
+        )
+    });
+    handle
+}
+
+/// 清除当前线程的所有映射
+pub fn reset() {
+    BUS.with(|bus| bus.borrow_mut().clear());
+}
+
+fn lookup(addr: usize) -> (Rc<RefCell<dyn Device>>, usize) {
+    BUS.with(|bus| {
+        bus.borrow()
+            .iter()
+            .find(|m| addr >= m.base && addr - m.base < m.size)
+            .map(|m| (m.device.clone(), addr - m.base))
+            .unwrap_or_else(|| panic!("sim: access to unmapped address {:#x}", addr))
+    })
+}
+
+pub(crate) fn read(addr: usize) -> u32 {
+    let (device, offset) = lookup(addr);
+    let value = device.borrow_mut().read(offset);
+    value
+}
+
+pub(crate) fn write(addr: usize, value: u32) {
+    let (device, offset) = lookup(addr);
+    device.borrow_mut().write(offset, value);
+}
+
+/// 普通寄存器文件: 读回最后写入的值，初始为 0
+///
+/// 用于没有专门模型的外设 (例如 CRU)
+pub struct Ram {
+    words: Vec<u32>,
+}
+
+impl Ram {
+    /// 创建 `size` 字节的寄存器文件
+    pub fn new(size: usize) -> Self {
+        Self {
+            words: vec![0; size.div_ceil(4)],
+        }
+    }
+
+    /// 读取 `offset` 处的值
+    pub fn word(&self, offset: usize) -> u32 {
+        self.words[offset / 4]
+    }
+
+    /// 设置 `offset` 处的值 (预置寄存器内容)
+    pub fn set_word(&mut self, offset: usize, value: u32) {
+        self.words[offset / 4] = value;
+    }
+}
+
+impl Device for Ram {
+    fn read(&mut self, offset: usize) -> u32 {
+        self.word(offset)
+    }
+
+    fn write(&mut self, offset: usize, value: u32) {
+        self.set_word(offset, value);
+    }
+}
@@ -0,0 +1,280 @@
+//! Designware MSHC (SDMMC) 控制器模型
+//!
+//! 只模拟正常流程：复位位立即清除，命令立即完成并按命令号返回响应。
+//! 默认响应对应一张已就绪的 SDHC 卡，可以用 [`MshcModel::set_response`] 覆盖。
+//!
+//! 读数据块命令 (CMD17) 把卡上的数据一次放入数据 FIFO 并置位 RXDR/DTO；
+//! 写数据块命令 (CMD24) 在 FIFO 收满一块后写入卡并置位 DTO，之后卡忙
+//! (STATUS.data_busy) 持续 [`MshcModel::set_busy_reads`] 次 STATUS 读取。
+//! 卡上的数据用 [`MshcModel::set_block`] 设置，未设置的块读出为 0。
+
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use super::Device;
+
+const SDMMC_CTRL: usize = 0x000;
+const SDMMC_PWREN: usize = 0x004;
+const SDMMC_CLKDIV: usize = 0x008;
+const SDMMC_CLKENA: usize = 0x010;
+const SDMMC_TMOUT: usize = 0x014;
+const SDMMC_CTYPE: usize = 0x018;
+const SDMMC_BLKSIZ: usize = 0x01C;
+const SDMMC_BYTCNT: usize = 0x020;
+const SDMMC_INTMASK: usize = 0x024;
+const SDMMC_CMDARG: usize = 0x028;
+const SDMMC_CMD: usize = 0x02C;
+const SDMMC_RESP0: usize = 0x030;
+const SDMMC_RINTSTS: usize = 0x044;
+const SDMMC_STATUS: usize = 0x048;
+const SDMMC_FIFOTH: usize = 0x04C;
+const SDMMC_CDETECT: usize = 0x050;
+const SDMMC_DATA: usize = 0x200;
+
+const CTRL_RESET_MASK: u32 = 0x07;
+const CTRL_FIFO_RESET: u32 = 1 << 1;
+
+const CMD_START: u32 = 1 << 31;
+const CMD_UPDATE_CLOCK: u32 = 1 << 21;
+const CMD_INDEX_MASK: u32 = 0x3F;
+const CMD_DATA_EXP: u32 = 1 << 9;
+const CMD_WRITE: u32 = 1 << 10;
+
+const RINTSTS_CMD_DONE: u32 = 1 << 2;
+const RINTSTS_DTO: u32 = 1 << 3;
+const RINTSTS_TXDR: u32 = 1 << 4;
+const RINTSTS_RXDR: u32 = 1 << 5;
+
+const STATUS_FIFO_COUNT_SHIFT: u32 = 17;
+const STATUS_DATA_BUSY: u32 = 1 << 9;
+
+/// 数据块大小
+const BLOCK_SIZE: usize = 512;
+
+/// SDMMC 控制器模型
+pub struct MshcModel {
+    regs: [u32; 0x60 / 4],
+    card_present: bool,
+    responses: Vec<(u32, u32)>,
+    commands: Vec<(u32, u32)>,
+    clock_updates: u32,
+    /// 卡上的数据块 (块地址, 数据)
+    blocks: Vec<(u32, Vec<u8>)>,
+    /// 数据 FIFO
+    fifo: VecDeque<u32>,
+    /// 下一次数据传输报告的错误位
+    data_error: u32,
+    /// 正在写入的块地址
+    write_addr: Option<u32>,
+    /// 每次写入后卡忙的 STATUS 读取次数
+    busy_reads: u32,
+    /// 剩余的卡忙读取次数
+    busy_left: u32,
+}
+
+impl Default for MshcModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MshcModel {
+    pub fn new() -> Self {
+        Self {
+            regs: [0; 0x60 / 4],
+            card_present: true,
+            responses: Vec::new(),
+            commands: Vec::new(),
+            clock_updates: 0,
+            blocks: Vec::new(),
+            fifo: VecDeque::new(),
+            data_error: 0,
+            write_addr: None,
+            busy_reads: 0,
+            busy_left: 0,
+        }
+    }
+
+    /// 设置是否插卡 (默认已插卡)
+    pub fn set_card_present(&mut self, present: bool) {
+        self.card_present = present;
+    }
+
+    /// 覆盖命令 `index` 的 RESP0
+    pub fn set_response(&mut self, index: u32, resp: u32) {
+        self.responses.retain(|&(i, _)| i != index);
+        self.responses.push((index, resp));
+    }
+
+    /// 设置卡上块地址 `addr` 处的数据 (不足一块时补 0)
+    pub fn set_block(&mut self, addr: u32, data: &[u8]) {
+        let mut block = data.to_vec();
+        block.resize(BLOCK_SIZE, 0);
+        self.blocks.retain(|(a, _)| *a != addr);
+        self.blocks.push((addr, block));
+    }
+
+    /// 卡上块地址 `addr` 处的数据，未写入过时为 `None`
+    pub fn block(&self, addr: u32) -> Option<&[u8]> {
+        self.blocks
+            .iter()
+            .find(|(a, _)| *a == addr)
+            .map(|(_, b)| b.as_slice())
+    }
+
+    /// 下一次数据传输以 RINTSTS 错误位 `bits` 结束 (例如数据 CRC 错误 `1 << 7`)
+    pub fn fail_next_data(&mut self, bits: u32) {
+        self.data_error = bits;
+    }
+
+    /// 每次写入后卡忙持续的 STATUS 读取次数 (默认 0，`u32::MAX` 表示一直忙)
+    pub fn set_busy_reads(&mut self, reads: u32) {
+        self.busy_reads = reads;
+    }
+
+    /// 已发送的命令 (命令号, 参数)，不含时钟更新命令
+    pub fn commands(&self) -> &[(u32, u32)] {
+        &self.commands
+    }
+
+    /// 时钟更新命令的次数
+    pub fn clock_updates(&self) -> u32 {
+        self.clock_updates
+    }
+
+    /// 寄存器当前值
+    pub fn reg(&self, offset: usize) -> u32 {
+        self.regs[offset / 4]
+    }
+
+    /// 是否已使能电源
+    pub fn powered(&self) -> bool {
+        self.reg(SDMMC_PWREN) & 1 != 0
+    }
+
+    /// 时钟分频系数
+    pub fn clock_div(&self) -> u32 {
+        self.reg(SDMMC_CLKDIV) & 0xFF
+    }
+
+    /// 是否已使能卡时钟
+    pub fn clock_enabled(&self) -> bool {
+        self.reg(SDMMC_CLKENA) & 1 != 0
+    }
+
+    fn default_response(index: u32, arg: u32) -> u32 {
+        match index {
+            // CMD8: 回显电压范围和检查模式
+            8 => arg & 0xFFF,
+            // CMD55: 卡状态 READY_FOR_DATA | APP_CMD
+            55 => 0x0000_0120,
+            // ACMD41: 上电完成, SDHC, 3.2-3.4V
+            41 => 0xC0FF_8000,
+            // CMD3: RCA 0x1234
+            3 => 0x1234_0500,
+            _ => 0,
+        }
+    }
+
+    fn command(&mut self, value: u32) {
+        if value & CMD_UPDATE_CLOCK != 0 {
+            self.clock_updates += 1;
+        } else {
+            let index = value & CMD_INDEX_MASK;
+            let arg = self.reg(SDMMC_CMDARG);
+            let resp = self
+                .responses
+                .iter()
+                .find(|&&(i, _)| i == index)
+                .map_or_else(|| Self::default_response(index, arg), |&(_, r)| r);
+            self.commands.push((index, arg));
+            self.regs[SDMMC_RESP0 / 4] = resp;
+            self.regs[SDMMC_RINTSTS / 4] |= RINTSTS_CMD_DONE;
+            if value & CMD_DATA_EXP != 0 {
+                if value & CMD_WRITE != 0 {
+                    self.write_addr = Some(arg);
+                    self.regs[SDMMC_RINTSTS / 4] |= RINTSTS_TXDR;
+                } else {
+                    self.start_read(arg);
+                }
+            }
+        }
+        self.regs[SDMMC_CMD / 4] = value & !CMD_START;
+    }
+}
+
+impl MshcModel {
+    fn start_read(&mut self, addr: u32) {
+        if self.data_error != 0 {
+            self.regs[SDMMC_RINTSTS / 4] |= core::mem::take(&mut self.data_error);
+            return;
+        }
+        let block = self.block(addr).map_or_else(|| vec![0; BLOCK_SIZE], |b| b.to_vec());
+        let words = block
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]));
+        self.fifo.extend(words);
+        self.regs[SDMMC_RINTSTS / 4] |= RINTSTS_RXDR | RINTSTS_DTO;
+    }
+
+    fn write_data(&mut self, word: u32) {
+        let Some(addr) = self.write_addr else {
+            return;
+        };
+        self.fifo.push_back(word);
+        if self.fifo.len() < BLOCK_SIZE / 4 {
+            return;
+        }
+        self.write_addr = None;
+        let block: Vec<u8> = self.fifo.drain(..).flat_map(u32::to_le_bytes).collect();
+        if self.data_error != 0 {
+            // 卡拒绝数据 (CRC 状态错误)，不写入
+            self.regs[SDMMC_RINTSTS / 4] |= core::mem::take(&mut self.data_error);
+            return;
+        }
+        self.set_block(addr, &block);
+        self.busy_left = self.busy_reads;
+        self.regs[SDMMC_RINTSTS / 4] |= RINTSTS_DTO;
+    }
+
+    fn status(&mut self) -> u32 {
+        let mut status = (self.fifo.len() as u32) << STATUS_FIFO_COUNT_SHIFT;
+        if self.busy_left > 0 {
+            status |= STATUS_DATA_BUSY;
+            if self.busy_left != u32::MAX {
+                self.busy_left -= 1;
+            }
+        }
+        status
+    }
+}
+
+impl Device for MshcModel {
+    fn read(&mut self, offset: usize) -> u32 {
+        match offset {
+            SDMMC_CDETECT => !self.card_present as u32,
+            SDMMC_STATUS => self.status(),
+            SDMMC_DATA => self.fifo.pop_front().unwrap_or(0),
+            _ if offset < 0x60 => self.reg(offset),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, offset: usize, value: u32) {
+        match offset {
+            SDMMC_CTRL => {
+                if value & CTRL_FIFO_RESET != 0 {
+                    self.fifo.clear();
+                }
+                self.regs[offset / 4] = value & !CTRL_RESET_MASK;
+            }
+            SDMMC_CMD if value & CMD_START != 0 => self.command(value),
+            SDMMC_RINTSTS => self.regs[offset / 4] &= !value,
+            SDMMC_DATA => self.write_data(value),
+            SDMMC_PWREN | SDMMC_CLKDIV | SDMMC_CLKENA | SDMMC_TMOUT | SDMMC_CTYPE
+            | SDMMC_BLKSIZ | SDMMC_BYTCNT | SDMMC_INTMASK | SDMMC_CMDARG | SDMMC_CMD
+            | SDMMC_FIFOTH => self.regs[offset / 4] = value,
+            _ => {}
+        }
+    }
+}
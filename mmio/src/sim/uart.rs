@@ -0,0 +1,351 @@
+//! Designware 16550 UART 模型
+//!
+//! 发送立即完成 (THRE/TEMT 总是置位，除非用 [`UartModel::set_tx_stalled`] 停住发送器)，
+//! 发送的字节记录在模型中；
+//! 接收通过 [`UartModel::receive`] 注入，RX FIFO 深度 64，溢出时置位 LSR.OE。
+
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use super::Device;
+
+const UART_RBR: usize = 0x00;
+const UART_IER: usize = 0x04;
+const UART_IIR: usize = 0x08;
+const UART_LCR: usize = 0x0C;
+const UART_MCR: usize = 0x10;
+const UART_LSR: usize = 0x14;
+const UART_MSR: usize = 0x18;
+const UART_SCR: usize = 0x1C;
+const UART_USR: usize = 0x7C;
+const UART_TFL: usize = 0x80;
+const UART_RFL: usize = 0x84;
+const UART_DLF: usize = 0xC0;
+
+const LSR_DR: u32 = 1 << 0;
+const LSR_OE: u32 = 1 << 1;
+const LSR_THRE: u32 = 1 << 5;
+const LSR_TEMT: u32 = 1 << 6;
+const LSR_ERR: u32 = 1 << 7;
+
+const LCR_BC: u32 = 1 << 6;
+const LCR_DLAB: u32 = 1 << 7;
+
+const IER_ERBFI: u32 = 1 << 0;
+const IER_ETBEI: u32 = 1 << 1;
+const IER_ELSI: u32 = 1 << 2;
+
+const FCR_FIFO_EN: u32 = 1 << 0;
+const FCR_RX_FIFO_RST: u32 = 1 << 1;
+
+const MSR_CTS: u32 = 1 << 4;
+
+const USR_BUSY: u32 = 1 << 0;
+const USR_TFNF: u32 = 1 << 1;
+const USR_TFE: u32 = 1 << 2;
+const USR_RFNE: u32 = 1 << 3;
+const USR_RFF: u32 = 1 << 4;
+
+/// RX FIFO 深度
+const FIFO_DEPTH: usize = 64;
+
+/// UART 控制器模型
+pub struct UartModel {
+    /// RX FIFO，每项为数据和该字节的 LSR 错误位
+    rx: VecDeque<(u8, u32)>,
+    /// 线路上等待进入 RX FIFO 的数据
+    wire: VecDeque<u8>,
+    tx: Vec<u8>,
+    ier: u32,
+    lcr: u32,
+    mcr: u32,
+    scr: u32,
+    dll: u32,
+    dlh: u32,
+    dlf: u32,
+    /// DLF 位宽，0 表示没有 DLF
+    dlf_bits: u32,
+    fifo_enabled: bool,
+    overrun: bool,
+    thre_pending: bool,
+    cts: bool,
+    busy: bool,
+    /// 复位 FIFO 也不能清除 BUSY
+    stuck_busy: bool,
+    /// 发送器停住 (例如自动流控下 CTS 一直无效)，THRE/TEMT 不再置位
+    tx_stalled: bool,
+    breaks_sent: u32,
+}
+
+impl Default for UartModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UartModel {
+    /// 奇偶校验错误 (LSR.PE)，用于 [`receive_with_error`](Self::receive_with_error)
+    pub const PARITY: u32 = 1 << 2;
+    /// 帧错误 (LSR.FE)
+    pub const FRAMING: u32 = 1 << 3;
+    /// Break (LSR.BI)
+    pub const BREAK: u32 = 1 << 4;
+
+    pub fn new() -> Self {
+        Self {
+            rx: VecDeque::new(),
+            wire: VecDeque::new(),
+            tx: Vec::new(),
+            ier: 0,
+            lcr: 0,
+            mcr: 0,
+            scr: 0,
+            dll: 0,
+            dlh: 0,
+            dlf: 0,
+            dlf_bits: 0,
+            fifo_enabled: false,
+            overrun: false,
+            thre_pending: false,
+            cts: true,
+            busy: false,
+            stuck_busy: false,
+            tx_stalled: false,
+            breaks_sent: 0,
+        }
+    }
+
+    fn depth(&self) -> usize {
+        if self.fifo_enabled {
+            FIFO_DEPTH
+        } else {
+            1
+        }
+    }
+
+    /// 对端发来数据，RX FIFO 满时丢弃并置位溢出标志
+    pub fn receive(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.receive_with_error(byte, 0);
+        }
+    }
+
+    /// 收到一个带线路错误的字节，`errors` 为 [`PARITY`](Self::PARITY) 等的组合
+    pub fn receive_with_error(&mut self, byte: u8, errors: u32) {
+        if self.rx.len() >= self.depth() {
+            self.overrun = true;
+        } else {
+            self.rx.push_back((byte, errors));
+        }
+    }
+
+    /// 对端发送的数据，RX FIFO 有空间时才进入 (相当于对端受流控，不会溢出)
+    pub fn queue(&mut self, bytes: &[u8]) {
+        self.wire.extend(bytes);
+        self.refill();
+    }
+
+    fn refill(&mut self) {
+        while self.rx.len() < self.depth() {
+            match self.wire.pop_front() {
+                Some(byte) => self.rx.push_back((byte, 0)),
+                None => break,
+            }
+        }
+    }
+
+    /// RX FIFO 中的字节数
+    pub fn rx_level(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// 已发送的全部字节
+    pub fn transmitted(&self) -> &[u8] {
+        &self.tx
+    }
+
+    /// 取出并清空已发送的字节
+    pub fn take_transmitted(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.tx)
+    }
+
+    /// 当前分频系数
+    pub fn divisor(&self) -> u32 {
+        (self.dlh << 8) | self.dll
+    }
+
+    /// 当前小数分频值
+    pub fn fraction(&self) -> u32 {
+        self.dlf
+    }
+
+    /// 模拟带小数分频寄存器 (DLF) 的 IP 版本，默认没有 DLF
+    pub fn set_dlf_bits(&mut self, bits: u32) {
+        self.dlf_bits = bits;
+    }
+
+    /// 当前 LCR 值
+    pub fn lcr(&self) -> u32 {
+        self.lcr
+    }
+
+    /// 当前 IER 值
+    pub fn ier(&self) -> u32 {
+        self.ier
+    }
+
+    /// 当前 MCR 值
+    pub fn mcr(&self) -> u32 {
+        self.mcr
+    }
+
+    /// 设置 CTS 输入 (`true` 为有效)
+    pub fn set_cts(&mut self, active: bool) {
+        self.cts = active;
+    }
+
+    /// 模拟控制器忙 (USR.BUSY)
+    ///
+    /// 忙时 LCR/DLL/DLH/DLF 的写入被忽略，复位 RX FIFO 后恢复空闲
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy = busy;
+    }
+
+    /// 模拟控制器持续忙 (例如线路上一直有数据进入)，复位 FIFO 也不能恢复空闲
+    pub fn set_stuck_busy(&mut self, stuck: bool) {
+        self.stuck_busy = stuck;
+        self.busy = stuck;
+    }
+
+    /// 模拟发送器停住 (例如自动流控下对端一直拉低 CTS)
+    ///
+    /// 停住期间 LSR.THRE/TEMT 和 USR.TFNF 保持清零，写入 THR 的数据被丢弃
+    pub fn set_tx_stalled(&mut self, stalled: bool) {
+        self.tx_stalled = stalled;
+    }
+
+    /// LCR.BC 被置位的次数
+    pub fn breaks_sent(&self) -> u32 {
+        self.breaks_sent
+    }
+
+    /// 与硬件相同，读 LSR 清除 RX FIFO 头部字节的错误位
+    fn lsr(&mut self) -> u32 {
+        let mut lsr = if self.tx_stalled { 0 } else { LSR_THRE | LSR_TEMT };
+        if self.rx.iter().any(|&(_, errors)| errors != 0) {
+            lsr |= LSR_ERR;
+        }
+        if let Some((_, errors)) = self.rx.front_mut() {
+            lsr |= LSR_DR | core::mem::take(errors);
+        }
+        if self.overrun {
+            lsr |= LSR_OE;
+            self.overrun = false;
+        }
+        lsr
+    }
+
+    fn iir(&mut self) -> u32 {
+        let fifo = if self.fifo_enabled { 0xC0 } else { 0 };
+        let line_error = self.overrun || self.rx.front().is_some_and(|&(_, e)| e != 0);
+        let id = if self.ier & IER_ELSI != 0 && line_error {
+            0x06
+        } else if self.ier & IER_ERBFI != 0 && !self.rx.is_empty() {
+            0x04
+        } else if self.ier & IER_ETBEI != 0 && self.thre_pending {
+            self.thre_pending = false;
+            0x02
+        } else {
+            0x01
+        };
+        fifo | id
+    }
+
+    fn usr(&self) -> u32 {
+        let mut usr = if self.tx_stalled { 0 } else { USR_TFNF | USR_TFE };
+        if self.busy {
+            usr |= USR_BUSY;
+        }
+        if !self.rx.is_empty() {
+            usr |= USR_RFNE;
+        }
+        if self.rx.len() >= self.depth() {
+            usr |= USR_RFF;
+        }
+        usr
+    }
+}
+
+impl Device for UartModel {
+    fn read(&mut self, offset: usize) -> u32 {
+        let dlab = self.lcr & LCR_DLAB != 0;
+        match offset {
+            UART_RBR if dlab => self.dll,
+            UART_RBR => {
+                let byte = self.rx.pop_front().map_or(0, |(byte, _)| byte as u32);
+                self.refill();
+                byte
+            }
+            UART_IER if dlab => self.dlh,
+            UART_IER => self.ier,
+            UART_IIR => self.iir(),
+            UART_LCR => self.lcr,
+            UART_MCR => self.mcr,
+            UART_LSR => self.lsr(),
+            UART_MSR => MSR_CTS * self.cts as u32,
+            UART_SCR => self.scr,
+            UART_USR => self.usr(),
+            UART_TFL => 0,
+            UART_RFL => self.rx.len() as u32,
+            UART_DLF => self.dlf,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, offset: usize, value: u32) {
+        let dlab = self.lcr & LCR_DLAB != 0;
+        // 忙时 LCR 和分频器的写入被忽略
+        if self.busy
+            && (offset == UART_LCR
+                || offset == UART_DLF
+                || (dlab && (offset == UART_RBR || offset == UART_IER)))
+        {
+            return;
+        }
+        match offset {
+            UART_RBR if dlab => self.dll = value & 0xFF,
+            UART_RBR => {
+                if self.lcr & LCR_BC == 0 && !self.tx_stalled {
+                    self.tx.push(value as u8);
+                }
+                self.thre_pending = true;
+            }
+            UART_IER if dlab => self.dlh = value & 0xFF,
+            UART_IER => {
+                if value & IER_ETBEI != 0 && self.ier & IER_ETBEI == 0 {
+                    self.thre_pending = true;
+                }
+                self.ier = value & 0xFF;
+            }
+            UART_IIR => {
+                self.fifo_enabled = value & FCR_FIFO_EN != 0;
+                if value & FCR_RX_FIFO_RST != 0 {
+                    self.rx.clear();
+                    self.overrun = false;
+                    self.busy = self.stuck_busy;
+                }
+                self.refill();
+            }
+            UART_LCR => {
+                if value & LCR_BC != 0 && self.lcr & LCR_BC == 0 {
+                    self.breaks_sent += 1;
+                }
+                self.lcr = value & 0xFF;
+            }
+            UART_MCR => self.mcr = value & 0xFF,
+            UART_SCR => self.scr = value & 0xFF,
+            UART_DLF => self.dlf = value & ((1 << self.dlf_bits) - 1),
+            _ => {}
+        }
+    }
+}
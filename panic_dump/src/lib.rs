@@ -0,0 +1,351 @@
+//! Panic 处理
+//!
+//! 提供 `#[panic_handler]`：在 [`PanicWriter`] 上输出 panic 信息、源码位置、当前异常级别、
+//! ESR/ELR/FAR、通用寄存器和栈顶内容，然后停住 CPU。链接本 crate 即可，不需要其他初始化：
+//! ```text
+//! !!! PANIC on core 0 at EL1
+//! panicked at src/main.rs:42:5:
+//! mmc init failed: Timeout
+//! ESR_EL1: 0x0000000096000045  ELR_EL1: 0x0000000000281a3c  FAR_EL1: 0x00000000fe2c0000
+//!  x0: 0x0000000000000001  x1: 0x00000000004f2e10  x2: ...
+//! stack:
+//! 004ffe40  60 fe 4f 00 00 00 00 00  3c 1a 28 00 00 00 00 00  |`.O.....<.(.....|
+//! ```
+//!
+//! # 注意
+//! - 输出依次选择 [`set_output`] 设置的输出函数、控制台 UART
+//!   ([`console_uart_base`](uart::console::console_uart_base))、
+//!   [`DEFAULT_EARLYCON`] 的串口；UART 轮询发送，不加控制台锁，持有控制台锁时 panic 也不会死锁
+//! - 没有 UART 的板子 (只有 JTAG DCC 等) 需要用 [`set_output`] 指定输出函数
+//! - 寄存器是进入 panic 处理时的值，不是 panic 位置的值；x29/x30 可用于回溯调用栈
+//! - ESR/ELR/FAR 只在异常处理中 panic 时有意义
+//! - 处理中再次 panic 时只输出一行提示后停住
+//! - 默认停在 `wfe` 循环中，可以用 [`set_halt_hook`] 改为触发看门狗、复位，
+//!   或用 `gpio::status_led::panic_blink` 快闪状态灯
+//! - 只在裸机目标 (`target_os = "none"`) 上定义 `#[panic_handler]`，主机端测试不受影响
+//!
+//! # 使用示例
+//! ```no_run
+//! // 链接即可生效
+//! use panic_dump as _;
+//!
+//! // panic 信息输出后复位系统，而不是停住
+//! fn reset() {
+//!     // 触发看门狗或 CRU 全局软复位
+//! }
+//! panic_dump::set_halt_hook(reset);
+//!
+//! // 只有 JTAG 的板子：panic 信息走 DCC
+//! fn dcc_puts(_s: &str) {
+//!     // 轮询写 DBGDTRTX
+//! }
+//! panic_dump::set_output(dcc_puts);
+//! ```
+
+#![no_std]
+
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+#[cfg(target_os = "none")]
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use uart::console::console_uart_base;
+use uart::early::{EarlyCon, DEFAULT_EARLYCON};
+
+/// 停机钩子，0 表示未设置
+static HALT_HOOK: AtomicUsize = AtomicUsize::new(0);
+
+/// 输出函数，0 表示未设置
+static OUTPUT_HOOK: AtomicUsize = AtomicUsize::new(0);
+
+/// 设置 panic 信息输出后调用的函数
+///
+/// 钩子返回后仍然停住 CPU
+pub fn set_halt_hook(hook: fn()) {
+    HALT_HOOK.store(hook as usize, Ordering::Release);
+}
+
+/// panic 信息中转储的栈字节数 (从 sp 开始)
+const STACK_DUMP_BYTES: usize = 128;
+
+/// 设置 panic 信息的输出函数，优先于控制台 UART
+///
+/// 输出函数在 panic 处理中调用，不能加锁或分配内存
+pub fn set_output(output: fn(&str)) {
+    OUTPUT_HOOK.store(output as usize, Ordering::Release);
+}
+
+/// panic 信息的输出目标
+///
+/// 构造时按 [`set_output`] 设置的输出函数、控制台 UART、[`DEFAULT_EARLYCON`]
+/// 的顺序选择第一个可用的；自定义的 panic 处理也可以用它配合 [`dump`]
+pub enum PanicWriter {
+    /// [`set_output`] 设置的输出函数
+    Hook(fn(&str)),
+    /// 轮询发送的串口
+    Uart(EarlyCon),
+}
+
+impl PanicWriter {
+    /// 选择当前的输出目标
+    pub fn new() -> Self {
+        let hook = OUTPUT_HOOK.load(Ordering::Acquire);
+        if hook != 0 {
+            return Self::Hook(unsafe { core::mem::transmute::<usize, fn(&str)>(hook) });
+        }
+        let base = console_uart_base().unwrap_or(DEFAULT_EARLYCON.base());
+        Self::Uart(EarlyCon::new(base))
+    }
+}
+
+impl Default for PanicWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for PanicWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self {
+            Self::Hook(output) => output(s),
+            Self::Uart(uart) => uart.puts(s),
+        }
+        Ok(())
+    }
+}
+
+/// 通用寄存器快照
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Registers {
+    /// x0-x30
+    pub x: [u64; 31],
+    pub sp: u64,
+}
+
+impl Registers {
+    /// 读取当前的通用寄存器
+    ///
+    /// 非 aarch64 目标上全部为 0
+    #[inline(always)]
+    pub fn capture() -> Self {
+        let mut regs = Self::default();
+        capture_into(&mut regs);
+        regs
+    }
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, x) in self.x.iter().enumerate() {
+            let pad = if i < 10 { " " } else { "" };
+            write!(f, "{}x{}: {:#018x}", pad, i, x)?;
+            f.write_str(if i % 4 == 3 { "\n" } else { "  " })?;
+        }
+        writeln!(f, " sp: {:#018x}", self.sp)
+    }
+}
+
+/// 异常相关的系统寄存器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaultRegisters {
+    /// 当前异常级别 (0-3)
+    pub el: u8,
+    pub esr: u64,
+    pub elr: u64,
+    pub far: u64,
+}
+
+impl FaultRegisters {
+    /// 读取当前异常级别的 ESR/ELR/FAR
+    ///
+    /// EL3 和非 aarch64 目标上寄存器值为 0
+    pub fn capture() -> Self {
+        read_fault_registers()
+    }
+}
+
+impl fmt::Display for FaultRegisters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "ESR_EL{el}: {:#018x}  ELR_EL{el}: {:#018x}  FAR_EL{el}: {:#018x}",
+            self.esr,
+            self.elr,
+            self.far,
+            el = self.el
+        )
+    }
+}
+
+/// 输出完整的 panic 信息
+///
+/// `#[panic_handler]` 的实现，也可以在自定义的 panic 处理中调用
+pub fn dump(info: &PanicInfo, regs: &Registers, mut out: &mut dyn Write) -> fmt::Result {
+    let fault = FaultRegisters::capture();
+    writeln!(out, "\n!!! PANIC on core {} at EL{}", core_id(), fault.el)?;
+    match info.location() {
+        Some(loc) => writeln!(
+            out,
+            "panicked at {}:{}:{}:",
+            loc.file(),
+            loc.line(),
+            loc.column()
+        )?,
+        None => writeln!(out, "panicked at unknown location:")?,
+    }
+    writeln!(out, "{}", info.message())?;
+    write!(out, "{}", fault)?;
+    write!(out, "{}", regs)?;
+    if regs.sp == 0 {
+        // 非 aarch64 目标上没有抓取寄存器
+        return Ok(());
+    }
+    writeln!(out, "stack:")?;
+    // sp 之上是 panic 处理自身和调用链上各帧的栈，一定已经映射
+    let stack = unsafe { core::slice::from_raw_parts(regs.sp as *const u8, STACK_DUMP_BYTES) };
+    kfmt::hexdump(&mut out, regs.sp as usize, stack)
+}
+
+/// 停住 CPU: 屏蔽中断后执行停机钩子，之后在 `wfe` 循环中等待
+pub fn halt() -> ! {
+    mask_interrupts();
+    let hook = HALT_HOOK.load(Ordering::Acquire);
+    if hook != 0 {
+        let hook: fn() = unsafe { core::mem::transmute(hook) };
+        hook();
+    }
+    loop {
+        wait_for_event();
+    }
+}
+
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    /// 是否已经在处理 panic
+    static PANICKING: AtomicBool = AtomicBool::new(false);
+
+    let regs = Registers::capture();
+    if PANICKING.swap(true, Ordering::AcqRel) {
+        // 输出函数本身可能就是 panic 的原因，嵌套时直接用默认串口
+        DEFAULT_EARLYCON.puts("\n!!! nested panic\n");
+    } else {
+        let _ = dump(info, &regs, &mut PanicWriter::new());
+    }
+    halt()
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+fn capture_into(regs: &mut Registers) {
+    let base = regs.x.as_mut_ptr();
+    let sp: u64;
+    unsafe {
+        core::arch::asm!(
+            "stp x0, x1, [{base}, #0]",
+            "stp x2, x3, [{base}, #16]",
+            "stp x4, x5, [{base}, #32]",
+            "stp x6, x7, [{base}, #48]",
+            "stp x8, x9, [{base}, #64]",
+            "stp x10, x11, [{base}, #80]",
+            "stp x12, x13, [{base}, #96]",
+            "stp x14, x15, [{base}, #112]",
+            "stp x16, x17, [{base}, #128]",
+            "stp x18, x19, [{base}, #144]",
+            "stp x20, x21, [{base}, #160]",
+            "stp x22, x23, [{base}, #176]",
+            "stp x24, x25, [{base}, #192]",
+            "stp x26, x27, [{base}, #208]",
+            "stp x28, x29, [{base}, #224]",
+            "str x30, [{base}, #240]",
+            "mov {sp}, sp",
+            base = in(reg) base,
+            sp = out(reg) sp,
+            options(nostack),
+        );
+    }
+    regs.sp = sp;
+}
+
+#[cfg(target_arch = "aarch64")]
+fn read_fault_registers() -> FaultRegisters {
+    let current_el: u64;
+    let (mut esr, mut elr, mut far) = (0u64, 0u64, 0u64);
+    unsafe {
+        core::arch::asm!("mrs {}, CurrentEL", out(reg) current_el, options(nomem, nostack));
+        let el = (current_el >> 2) & 0x3;
+        if el == 1 {
+            core::arch::asm!(
+                "mrs {esr}, esr_el1",
+                "mrs {elr}, elr_el1",
+                "mrs {far}, far_el1",
+                esr = out(reg) esr,
+                elr = out(reg) elr,
+                far = out(reg) far,
+                options(nomem, nostack),
+            );
+        } else if el == 2 {
+            core::arch::asm!(
+                "mrs {esr}, esr_el2",
+                "mrs {elr}, elr_el2",
+                "mrs {far}, far_el2",
+                esr = out(reg) esr,
+                elr = out(reg) elr,
+                far = out(reg) far,
+                options(nomem, nostack),
+            );
+        }
+    }
+    FaultRegisters {
+        el: ((current_el >> 2) & 0x3) as u8,
+        esr,
+        elr,
+        far,
+    }
+}
+
+/// MPIDR_EL1.Aff1 (RK3588 上为核号)
+#[cfg(target_arch = "aarch64")]
+fn core_id() -> u64 {
+    let mpidr: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, mpidr_el1", out(reg) mpidr, options(nomem, nostack));
+    }
+    (mpidr >> 8) & 0xFF
+}
+
+#[cfg(target_arch = "aarch64")]
+fn mask_interrupts() {
+    unsafe {
+        core::arch::asm!("msr daifset, #0xf", options(nomem, nostack));
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn wait_for_event() {
+    unsafe {
+        core::arch::asm!("wfe", options(nomem, nostack));
+    }
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn capture_into(_: &mut Registers) {}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn read_fault_registers() -> FaultRegisters {
+    FaultRegisters::default()
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn core_id() -> u64 {
+    0
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn mask_interrupts() {}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn wait_for_event() {
+    core::hint::spin_loop();
+}
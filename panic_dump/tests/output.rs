@@ -0,0 +1,42 @@
+//! panic 输出目标的选择顺序
+//!
+//! 运行: `cargo test -p panic_dump`
+
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use panic_dump::PanicWriter;
+use uart::early::DEFAULT_EARLYCON;
+use uart::{console, Uart, UART3_BASE};
+
+static CAPTURED: Mutex<String> = Mutex::new(String::new());
+
+fn capture(s: &str) {
+    CAPTURED.lock().unwrap().push_str(s);
+}
+
+fn uart_base(writer: &PanicWriter) -> Option<usize> {
+    match writer {
+        PanicWriter::Uart(uart) => Some(uart.base()),
+        PanicWriter::Hook(_) => None,
+    }
+}
+
+/// 输出函数和控制台都是全局状态，放在同一个测试中按顺序检查
+#[test]
+fn output_prefers_hook_then_console_uart_then_earlycon() {
+    static CONSOLE_UART: Uart = Uart::new(UART3_BASE);
+
+    assert_eq!(uart_base(&PanicWriter::new()), Some(DEFAULT_EARLYCON.base()));
+
+    console().register(&CONSOLE_UART).unwrap();
+    assert_eq!(uart_base(&PanicWriter::new()), Some(UART3_BASE));
+    console().unregister(&CONSOLE_UART);
+    assert_eq!(uart_base(&PanicWriter::new()), Some(DEFAULT_EARLYCON.base()));
+
+    panic_dump::set_output(capture);
+    let mut out = PanicWriter::new();
+    assert_eq!(uart_base(&out), None);
+    write!(out, "panicked at main.rs:{}", 42).unwrap();
+    assert_eq!(*CAPTURED.lock().unwrap(), "panicked at main.rs:42");
+}
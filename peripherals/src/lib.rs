@@ -0,0 +1,179 @@
+//! 外设实例的唯一所有权
+//!
+//! 驱动的构造函数 (`Uart::new(UART2_BASE)` 等) 可以在任何地方调用，
+//! 两处代码各自构造同一个控制器的实例时会互相破坏寄存器状态和缓冲区。
+//! [`Peripherals::take`] 在整个系统中只成功一次，返回每个 UART、GPIO Bank
+//! 和 SDMMC 控制器的所有权令牌；令牌不能复制，也不能在本模块外构造，
+//! 用 `into_*` 消耗令牌才能得到驱动实例，保证每个控制器只有一个驱动实例。
+//!
+//! GPIO Bank 令牌按引脚再分一次：[`GpioBankToken::pin`] 每个引脚只能取出一次，
+//! 其余令牌都是零大小类型，没有运行时开销。
+//!
+//! # 注意
+//! - 驱动的公开构造函数仍然保留，这里的保证只对通过令牌取得实例的代码有效
+//! - 调试串口通常已经由 `uart::init_console` 占用，不应再从 `uart2` 令牌构造实例
+//!
+//! # 使用示例
+//! ```no_run
+//! use gpio::claim::ClaimedPin;
+//! use gpio::GpioLevel;
+//! use peripherals::Peripherals;
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let uart = p.uart3.into_uart();
+//! uart.init(115200).unwrap();
+//!
+//! let mut gpio0 = p.gpio0;
+//! // 应用直接操作的引脚自己登记占用
+//! let led = ClaimedPin::new(gpio0.pin(13).unwrap(), "led").unwrap();
+//! led.set_level(GpioLevel::High);
+//! // 同一引脚不能再取出
+//! assert!(gpio0.pin(13).is_none());
+//!
+//! // 第二次调用失败
+//! assert!(Peripherals::take().is_none());
+//! ```
+
+#![no_std]
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use gpio::{GpioBank, GpioPin};
+use mmc::SdMmc;
+use uart::{Uart, UartPort};
+
+/// UART 控制器所有权令牌
+pub struct UartToken<const N: usize> {
+    _private: (),
+}
+
+impl<const N: usize> UartToken<N> {
+    /// 控制器基址
+    pub const fn base(&self) -> usize {
+        UartPort::<N>::BASE
+    }
+
+    /// 消耗令牌，创建该控制器的驱动实例
+    pub const fn into_uart(self) -> Uart {
+        // 令牌只能通过 Peripherals::take 取得一次，消耗后不会再有同一控制器的实例
+        unsafe { UartPort::<N>::uart() }
+    }
+}
+
+/// GPIO Bank 所有权令牌
+pub struct GpioBankToken<const N: u8> {
+    /// 已经取出的引脚 (位图)
+    taken: u32,
+}
+
+impl<const N: u8> GpioBankToken<N> {
+    /// 令牌对应的 Bank
+    pub const fn bank(&self) -> GpioBank {
+        match N {
+            0 => GpioBank::Gpio0,
+            1 => GpioBank::Gpio1,
+            2 => GpioBank::Gpio2,
+            3 => GpioBank::Gpio3,
+            _ => GpioBank::Gpio4,
+        }
+    }
+
+    /// 取出该 Bank 中的引脚，每个引脚只能取出一次
+    ///
+    /// # 参数
+    /// - `pin`: 引脚号 (0-31)
+    ///
+    /// # 返回值
+    /// - `Some(pin)`: 第一次取出
+    /// - `None`: 之前已经取出过
+    ///
+    /// # 注意
+    /// 取出时不登记引脚占用：交给会自己登记的驱动 (`FastGpio`、`SoftPwm` 等) 时直接传入，
+    /// 应用直接操作引脚时用 `gpio::claim::ClaimedPin` 登记
+    pub fn pin(&mut self, pin: u8) -> Option<GpioPin> {
+        assert!(pin < 32, "Pin number must be less than 32");
+        let bit = 1 << pin;
+        if self.taken & bit != 0 {
+            return None;
+        }
+        self.taken |= bit;
+        Some(GpioPin::new(self.bank(), pin))
+    }
+}
+
+/// SDMMC 控制器所有权令牌
+pub struct SdmmcToken {
+    _private: (),
+}
+
+impl SdmmcToken {
+    /// 消耗令牌，创建驱动实例
+    pub fn into_sdmmc(self) -> SdMmc {
+        SdMmc::new(mmc::SDMMC0_BASE)
+    }
+}
+
+/// 全部外设的所有权令牌
+pub struct Peripherals {
+    pub uart0: UartToken<0>,
+    pub uart1: UartToken<1>,
+    pub uart2: UartToken<2>,
+    pub uart3: UartToken<3>,
+    pub uart4: UartToken<4>,
+    pub uart5: UartToken<5>,
+    pub uart6: UartToken<6>,
+    pub uart7: UartToken<7>,
+    pub uart8: UartToken<8>,
+    pub uart9: UartToken<9>,
+    pub gpio0: GpioBankToken<0>,
+    pub gpio1: GpioBankToken<1>,
+    pub gpio2: GpioBankToken<2>,
+    pub gpio3: GpioBankToken<3>,
+    pub gpio4: GpioBankToken<4>,
+    pub sdmmc0: SdmmcToken,
+}
+
+/// `take` 是否已经成功过
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+impl Peripherals {
+    /// 取得全部外设的所有权
+    ///
+    /// # 返回值
+    /// - `Some(Peripherals)`: 第一次调用
+    /// - `None`: 之前已经成功调用过
+    pub fn take() -> Option<Self> {
+        if TAKEN.swap(true, Ordering::AcqRel) {
+            None
+        } else {
+            Some(unsafe { Self::steal() })
+        }
+    }
+
+    /// 不经检查地创建全部外设的令牌
+    ///
+    /// # Safety
+    /// 调用者必须保证与其他令牌持有者之间不会同时访问同一个控制器，
+    /// 例如只在 panic 处理中用于输出最后的错误信息
+    pub unsafe fn steal() -> Self {
+        Self {
+            uart0: UartToken { _private: () },
+            uart1: UartToken { _private: () },
+            uart2: UartToken { _private: () },
+            uart3: UartToken { _private: () },
+            uart4: UartToken { _private: () },
+            uart5: UartToken { _private: () },
+            uart6: UartToken { _private: () },
+            uart7: UartToken { _private: () },
+            uart8: UartToken { _private: () },
+            uart9: UartToken { _private: () },
+            gpio0: GpioBankToken { taken: 0 },
+            gpio1: GpioBankToken { taken: 0 },
+            gpio2: GpioBankToken { taken: 0 },
+            gpio3: GpioBankToken { taken: 0 },
+            gpio4: GpioBankToken { taken: 0 },
+            sdmmc0: SdmmcToken { _private: () },
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! 外设令牌只能取得一次，GPIO 引脚只能取出一次
+//!
+//! 运行: `cargo test -p peripherals`
+
+use peripherals::Peripherals;
+
+#[test]
+fn peripherals_and_pins_are_handed_out_once() {
+    let p = Peripherals::take().unwrap();
+    assert!(Peripherals::take().is_none());
+
+    let mut gpio1 = p.gpio1;
+    assert!(gpio1.pin(5).is_some());
+    assert!(gpio1.pin(5).is_none());
+    assert!(gpio1.pin(6).is_some());
+}
@@ -0,0 +1,259 @@
+//! 分时协作式轮询
+//!
+//! 现有驱动大多是轮询式的 (MMC 命令等待、UART 收发)，直接在主循环里阻塞等待会
+//! 饿死其他工作。这里让这些驱动把等待拆成轮询回调，各自声明每次运行的时间片，
+//! 由 [`run_once`] 按注册顺序轮流调用；空闲任务 (或目前的主循环) 反复调用 [`run_once`]，
+//! 驱动逐个迁移到中断方式之前可以与其他工作共存。
+//!
+//! # 回调约定
+//! - 每次调用至少完成一个工作单元 (一个字节、一个命令状态检查)，
+//!   之后在 [`Budget::expired`] 返回 `true` 时尽快返回
+//! - 返回 [`PollStatus::Busy`] 表示还有待处理的工作，[`PollStatus::Idle`] 表示没有
+//! - 超出时间片时记录到 [`PollStats::overruns`]，第一次超时输出警告日志
+//!
+//! # 已接入的驱动
+//! - `uart::poll` (uart `poll` feature)：把 RX FIFO 搬到接收缓冲区
+//!
+//! # 注意
+//! 时间片由 ARM 通用定时器计量 ([`timer::timestamp_us`])。没有可用定时器时
+//! (非 aarch64 目标、固件未设置 CNTFRQ_EL0) [`Budget::expired`] 总是返回 `true`，
+//! 每个回调每轮只完成一个工作单元。
+//!
+//! # 使用示例
+//! ```
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//! use poll_loop::{Budget, PollStatus};
+//!
+//! static PENDING: AtomicU32 = AtomicU32::new(3);
+//!
+//! fn drain(budget: &Budget) -> PollStatus {
+//!     loop {
+//!         if PENDING.load(Ordering::Relaxed) == 0 {
+//!             return PollStatus::Idle;
+//!         }
+//!         PENDING.fetch_sub(1, Ordering::Relaxed);
+//!         if budget.expired() {
+//!             return PollStatus::Busy;
+//!         }
+//!     }
+//! }
+//!
+//! poll_loop::register("drain", 200, drain).unwrap();
+//! while poll_loop::run_once() {}
+//! assert_eq!(PENDING.load(Ordering::Relaxed), 0);
+//! ```
+
+#![no_std]
+
+use core::fmt;
+
+use spinlock::SpinLock;
+
+/// 最多可注册的轮询回调数量
+pub const MAX_POLLERS: usize = 16;
+
+/// 轮询回调的返回值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollStatus {
+    /// 没有待处理的工作
+    Idle,
+    /// 还有待处理的工作，希望尽快再次被调用
+    Busy,
+}
+
+/// 轮询回调
+pub type PollFn = fn(budget: &Budget) -> PollStatus;
+
+/// 一次调用的时间预算
+pub struct Budget {
+    /// 截止时间 (微秒)，没有可用定时器时为 `None`
+    deadline_us: Option<u64>,
+}
+
+impl Budget {
+    fn new(slice_us: u32) -> Self {
+        Self {
+            deadline_us: timer::timestamp_us().map(|now| now + slice_us as u64),
+        }
+    }
+
+    /// 时间片是否已用完
+    pub fn expired(&self) -> bool {
+        match (self.deadline_us, timer::timestamp_us()) {
+            (Some(deadline), Some(now)) => now >= deadline,
+            _ => true,
+        }
+    }
+
+    /// 剩余时间 (微秒)，没有可用定时器时为 0
+    pub fn remaining_us(&self) -> u64 {
+        match (self.deadline_us, timer::timestamp_us()) {
+            (Some(deadline), Some(now)) => deadline.saturating_sub(now),
+            _ => 0,
+        }
+    }
+}
+
+/// 轮询回调的运行统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PollStats {
+    /// 调用次数
+    pub runs: u32,
+    /// 返回 [`PollStatus::Busy`] 的次数
+    pub busy: u32,
+    /// 超出时间片的次数
+    pub overruns: u32,
+    /// 单次调用的最长耗时 (微秒)
+    pub max_us: u32,
+}
+
+/// 注册轮询回调失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+    /// 已注册 [`MAX_POLLERS`] 个回调
+    Full,
+    /// 同名回调已存在
+    Duplicate,
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterError::Full => f.write_str("poller table full"),
+            RegisterError::Duplicate => f.write_str("poller already registered"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Poller {
+    name: &'static str,
+    slice_us: u32,
+    poll: PollFn,
+    stats: PollStats,
+}
+
+/// 回调表
+static REGISTRY: SpinLock<[Option<Poller>; MAX_POLLERS]> = SpinLock::new([None; MAX_POLLERS]);
+
+/// 注册轮询回调
+///
+/// # 参数
+/// - `name`: 回调名称 (用于统计和日志)
+/// - `slice_us`: 每次调用的时间片 (微秒)
+/// - `poll`: 回调函数
+///
+/// # 返回值
+/// - `Ok(())`: 注册成功
+/// - `Err(RegisterError)`: 回调表已满或重名
+pub fn register(name: &'static str, slice_us: u32, poll: PollFn) -> Result<(), RegisterError> {
+    let mut pollers = REGISTRY.lock();
+    if pollers.iter().flatten().any(|p| p.name == name) {
+        return Err(RegisterError::Duplicate);
+    }
+    let slot = pollers
+        .iter_mut()
+        .find(|p| p.is_none())
+        .ok_or(RegisterError::Full)?;
+    *slot = Some(Poller {
+        name,
+        slice_us,
+        poll,
+        stats: PollStats::default(),
+    });
+    Ok(())
+}
+
+/// 注销轮询回调
+///
+/// # 返回值
+/// 回调存在并已注销时返回 `true`
+pub fn unregister(name: &str) -> bool {
+    let mut pollers = REGISTRY.lock();
+    match pollers
+        .iter_mut()
+        .find(|p| p.is_some_and(|p| p.name == name))
+    {
+        Some(slot) => {
+            *slot = None;
+            true
+        }
+        None => false,
+    }
+}
+
+/// 按注册顺序调用每个回调一次
+///
+/// 回调在不持锁的情况下调用，可以在回调中注册或注销回调 (包括自己)
+///
+/// # 返回值
+/// 有回调返回 [`PollStatus::Busy`] 时返回 `true`，调用者应尽快再次调用
+pub fn run_once() -> bool {
+    let mut busy = false;
+    for i in 0..MAX_POLLERS {
+        let Some(poller) = REGISTRY.lock()[i] else {
+            continue;
+        };
+
+        let start = timer::timestamp_us();
+        let status = (poller.poll)(&Budget::new(poller.slice_us));
+        let elapsed = match (start, timer::timestamp_us()) {
+            (Some(start), Some(end)) => (end - start).min(u32::MAX as u64) as u32,
+            _ => 0,
+        };
+        busy |= status == PollStatus::Busy;
+
+        let overrun = elapsed > poller.slice_us;
+        let first_overrun = match &mut REGISTRY.lock()[i] {
+            // 回调执行期间可能被注销或替换
+            Some(p) if p.name == poller.name => {
+                let stats = &mut p.stats;
+                stats.runs = stats.runs.wrapping_add(1);
+                stats.busy = stats.busy.wrapping_add((status == PollStatus::Busy) as u32);
+                stats.max_us = stats.max_us.max(elapsed);
+                if overrun {
+                    stats.overruns = stats.overruns.wrapping_add(1);
+                }
+                overrun && stats.overruns == 1
+            }
+            _ => false,
+        };
+        if first_overrun {
+            klog::warn!(
+                "poller `{}` ran {} us, slice is {} us",
+                poller.name,
+                elapsed,
+                poller.slice_us
+            );
+        }
+    }
+    busy
+}
+
+/// 查询回调的运行统计
+pub fn stats(name: &str) -> Option<PollStats> {
+    REGISTRY
+        .lock()
+        .iter()
+        .flatten()
+        .find(|p| p.name == name)
+        .map(|p| p.stats)
+}
+
+/// 输出所有回调的统计表，可用于调试命令
+pub fn write_stats(w: &mut dyn fmt::Write) -> fmt::Result {
+    let pollers = *REGISTRY.lock();
+    writeln!(
+        w,
+        "{:<16} {:>8} {:>10} {:>10} {:>8} {:>8}",
+        "name", "slice", "runs", "busy", "overrun", "max_us"
+    )?;
+    for p in pollers.iter().flatten() {
+        writeln!(
+            w,
+            "{:<16} {:>8} {:>10} {:>10} {:>8} {:>8}",
+            p.name, p.slice_us, p.stats.runs, p.stats.busy, p.stats.overruns, p.stats.max_us
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! run_once 的时间片与运行统计
+//!
+//! 时间由 `timer::sim` 模拟时钟推进 (1MHz，一个计数一微秒)。
+//! 回调表是全局的，测试之间用锁串行，每个测试结束时注销自己的回调。
+//!
+//! 运行: `cargo test -p poll_loop`
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use poll_loop::{Budget, PollStatus};
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 待处理的工作单元，每个耗时 25µs
+static UNITS: AtomicU32 = AtomicU32::new(0);
+/// 每次调用完成的单元数
+static DONE: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+/// 每次调用开始时的剩余时间
+static REMAINING: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+fn chunked(budget: &Budget) -> PollStatus {
+    REMAINING.lock().unwrap().push(budget.remaining_us());
+    let mut done = 0;
+    let status = loop {
+        UNITS.fetch_sub(1, Ordering::Relaxed);
+        timer::sim::advance_us(25);
+        done += 1;
+        if UNITS.load(Ordering::Relaxed) == 0 {
+            break PollStatus::Idle;
+        }
+        if budget.expired() {
+            break PollStatus::Busy;
+        }
+    };
+    DONE.lock().unwrap().push(done);
+    status
+}
+
+fn reset_chunked(units: u32) {
+    UNITS.store(units, Ordering::Relaxed);
+    DONE.lock().unwrap().clear();
+    REMAINING.lock().unwrap().clear();
+}
+
+#[test]
+fn budget_splits_work_across_calls() {
+    let _serial = serial();
+    timer::sim::start(1_000_000);
+    reset_chunked(10);
+    poll_loop::register("chunked", 100, chunked).unwrap();
+
+    assert!(poll_loop::run_once());
+    assert!(poll_loop::run_once());
+    assert!(!poll_loop::run_once());
+    // 100µs 时间片容纳 4 个 25µs 的单元
+    assert_eq!(*DONE.lock().unwrap(), [4, 4, 2]);
+    assert_eq!(*REMAINING.lock().unwrap(), [100, 100, 100]);
+
+    let stats = poll_loop::stats("chunked").unwrap();
+    assert_eq!(stats.runs, 3);
+    assert_eq!(stats.busy, 2);
+    assert_eq!(stats.overruns, 0);
+    assert_eq!(stats.max_us, 100);
+
+    assert!(poll_loop::unregister("chunked"));
+    assert!(!poll_loop::run_once());
+}
+
+#[test]
+fn without_clock_each_call_does_one_unit() {
+    let _serial = serial();
+    timer::sim::stop();
+    reset_chunked(3);
+    poll_loop::register("chunked", 100, chunked).unwrap();
+
+    while poll_loop::run_once() {}
+    assert_eq!(*DONE.lock().unwrap(), [1, 1, 1]);
+    assert_eq!(*REMAINING.lock().unwrap(), [0, 0, 0]);
+    let stats = poll_loop::stats("chunked").unwrap();
+    assert_eq!((stats.runs, stats.busy, stats.overruns, stats.max_us), (3, 2, 0, 0));
+
+    assert!(poll_loop::unregister("chunked"));
+}
+
+/// 每次调用耗时 `SLOW_US`
+static SLOW_US: AtomicU32 = AtomicU32::new(0);
+
+fn slow(_budget: &Budget) -> PollStatus {
+    timer::sim::advance_us(SLOW_US.load(Ordering::Relaxed) as u64);
+    PollStatus::Idle
+}
+
+fn quick(_budget: &Budget) -> PollStatus {
+    timer::sim::advance_us(10);
+    PollStatus::Idle
+}
+
+#[test]
+fn overruns_are_counted_per_poller() {
+    let _serial = serial();
+    timer::sim::start(1_000_000);
+    poll_loop::register("slow", 50, slow).unwrap();
+    poll_loop::register("quick", 50, quick).unwrap();
+
+    SLOW_US.store(200, Ordering::Relaxed);
+    poll_loop::run_once();
+    SLOW_US.store(80, Ordering::Relaxed);
+    poll_loop::run_once();
+    SLOW_US.store(50, Ordering::Relaxed);
+    poll_loop::run_once();
+
+    // 恰好用完时间片不算超时
+    let slow = poll_loop::stats("slow").unwrap();
+    assert_eq!((slow.runs, slow.overruns, slow.max_us), (3, 2, 200));
+    let quick = poll_loop::stats("quick").unwrap();
+    assert_eq!((quick.runs, quick.overruns, quick.max_us), (3, 0, 10));
+
+    let mut table = String::new();
+    poll_loop::write_stats(&mut table).unwrap();
+    let row = table.lines().find(|line| line.starts_with("slow ")).unwrap();
+    let fields: Vec<&str> = row.split_whitespace().collect();
+    assert_eq!(fields, ["slow", "50", "3", "0", "2", "200"]);
+
+    assert!(poll_loop::unregister("slow"));
+    assert!(poll_loop::unregister("quick"));
+}
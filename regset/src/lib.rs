@@ -0,0 +1,131 @@
+//! 寄存器集合描述与解码输出
+//!
+//! 驱动用 [`RegisterSet`] 声明自己的关键寄存器 (偏移、访问方式、位域名称)，
+//! [`RegisterSet::dump`] 据此按位域名称输出寄存器内容 (供 `regdump <device>` 命令使用)。
+//!
+//! # 注意
+//! - 只应声明读取没有副作用的寄存器 (例如不要把 UART RBR 放进集合)
+//! - 挂起/恢复需要特殊的访问序列 (例如 UART 分频器要先置位 DLAB)，
+//!   由驱动自己实现 (见 uart 的 `pm` 模块)，不通过寄存器集合
+//!
+//! # 使用示例
+//! ```no_run
+//! use regset::{Access, Field, Register, RegisterSet};
+//!
+//! const CTRL_FIELDS: &[Field] = &[
+//!     Field::new("EN", 0, 1),
+//!     Field::new("MODE", 1, 2),
+//! ];
+//!
+//! const TIMER_REGS: RegisterSet = RegisterSet::new("timer", &[
+//!     Register::new("CTRL", 0x00, Access::ReadWrite, CTRL_FIELDS),
+//!     Register::new("COUNT", 0x04, Access::ReadOnly, &[]),
+//! ]);
+//!
+//! let mut out = String::new();
+//! TIMER_REGS.dump(&mut out, 0xFEAE0000).unwrap();
+//! ```
+
+#![no_std]
+
+use core::fmt;
+
+use mmio::read_volatile;
+
+/// 寄存器访问方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// 可读写 (控制、配置寄存器)
+    ReadWrite,
+    /// 只读 (状态寄存器)
+    ReadOnly,
+}
+
+/// 寄存器位域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    /// 起始位
+    pub shift: u8,
+    /// 位宽
+    pub width: u8,
+}
+
+impl Field {
+    pub const fn new(name: &'static str, shift: u8, width: u8) -> Self {
+        assert!(width > 0 && shift as u32 + width as u32 <= 32, "Field out of range");
+        Self { name, shift, width }
+    }
+
+    /// 从寄存器值中取出该位域
+    pub const fn extract(&self, value: u32) -> u32 {
+        let mask = if self.width == 32 { u32::MAX } else { (1 << self.width) - 1 };
+        (value >> self.shift) & mask
+    }
+}
+
+/// 寄存器描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Register {
+    pub name: &'static str,
+    /// 相对控制器基址的偏移
+    pub offset: usize,
+    pub access: Access,
+    /// 位域，为空时只输出原始值
+    pub fields: &'static [Field],
+}
+
+impl Register {
+    pub const fn new(name: &'static str, offset: usize, access: Access, fields: &'static [Field]) -> Self {
+        Self { name, offset, access, fields }
+    }
+}
+
+/// 一个设备的寄存器集合
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterSet {
+    /// 设备名称
+    pub name: &'static str,
+    pub registers: &'static [Register],
+}
+
+impl RegisterSet {
+    pub const fn new(name: &'static str, registers: &'static [Register]) -> Self {
+        Self { name, registers }
+    }
+
+    fn read(base: usize, reg: &Register) -> u32 {
+        unsafe { read_volatile((base + reg.offset) as *const u32) }
+    }
+
+    /// 读取并输出 `base` 处设备的寄存器，按位域解码
+    ///
+    /// 输出格式:
+    /// ```text
+    /// uart @ 0xfeb50000
+    ///   LCR    0x0c = 0x00000003  DLS=3 STOP=0 PEN=0 EPS=0 SP=0 BC=0 DLAB=0
+    /// ```
+    pub fn dump<W: fmt::Write>(&self, w: &mut W, base: usize) -> fmt::Result {
+        writeln!(w, "{} @ {:#010x}", self.name, base)?;
+        for reg in self.registers {
+            write_register(w, reg, Self::read(base, reg))?;
+        }
+        Ok(())
+    }
+}
+
+fn write_register<W: fmt::Write>(w: &mut W, reg: &Register, value: u32) -> fmt::Result {
+    write!(w, "  {:<6} {:#04x} = {:#010x}", reg.name, reg.offset, value)?;
+    if !reg.fields.is_empty() {
+        w.write_str(" ")?;
+    }
+    for field in reg.fields {
+        let v = field.extract(value);
+        if field.width >= 4 {
+            write!(w, " {}={:#x}", field.name, v)?;
+        } else {
+            write!(w, " {}={}", field.name, v)?;
+        }
+    }
+    writeln!(w)
+}
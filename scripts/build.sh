@@ -88,6 +88,8 @@ NC='\033[0m' # No Color
 PROJECT_ROOT=$(cd "$(dirname "$0")/.." && pwd)
 TARGET=aarch64-unknown-none
 OUTPUT_DIR="$PROJECT_ROOT/output"
+# 应用额外启用的 feature，例如 APP_FEATURES="uart/xmodem,gpio/claim" ./scripts/build.sh
+APP_FEATURES=${APP_FEATURES:-}
 
 echo -e "${GREEN}==================================="
 echo "  Building WhitcloudOS-1"
@@ -97,7 +99,7 @@ echo "===================================${NC}"
 mkdir -p "$OUTPUT_DIR"
 
 # 检查工具链
-echo -e "${YELLOW}[0/5] Checking toolchain...${NC}"
+echo -e "${YELLOW}[0/7] Checking toolchain...${NC}"
 if ! rustc --version > /dev/null 2>&1; then
     echo -e "${RED}Error: Rust not installed!${NC}"
     exit 1
@@ -108,36 +110,51 @@ if ! rustup target list | grep -q "$TARGET (installed)"; then
     rustup target add $TARGET
 fi
 
+# 检查 feature 组合 (关闭默认 feature、单独开启每个 feature 都能编译)
+echo -e "${YELLOW}[1/7] Checking feature combinations...${NC}"
+TARGET=$TARGET bash "$PROJECT_ROOT/scripts/check-features.sh"
+
 # 构建 GPIO 驱动
-echo -e "${YELLOW}[1/5] Building GPIO driver...${NC}"
+echo -e "${YELLOW}[2/7] Building GPIO driver...${NC}"
 cd "$PROJECT_ROOT/drivers/gpio"
 cargo build --release --target=$TARGET
 echo -e "${GREEN}✓ GPIO driver built${NC}"
 
 # 构建 UART 驱动
-echo -e "${YELLOW}[2/5] Building UART driver...${NC}"
+echo -e "${YELLOW}[3/7] Building UART driver...${NC}"
 cd "$PROJECT_ROOT/drivers/uart"
 cargo build --release --target=$TARGET
 echo -e "${GREEN}✓ UART driver built${NC}"
 
 # 构建 MMC 驱动
-echo -e "${YELLOW}[3/5] Building MMC driver...${NC}"
+echo -e "${YELLOW}[4/7] Building MMC driver...${NC}"
 cd "$PROJECT_ROOT/drivers/mmc"
 cargo build --release --target=$TARGET
 echo -e "${GREEN}✓ MMC driver built${NC}"
 
 # 构建应用程序
-echo -e "${YELLOW}[4/5] Building applications...${NC}"
+echo -e "${YELLOW}[5/7] Building applications...${NC}"
 cd "$PROJECT_ROOT/rust-app"
-cargo build --release --target=$TARGET
+# 启用的 feature 同时传给 buildinfo，version 命令和问题报告中可以看到
+WCOS_FEATURES="$APP_FEATURES" cargo build --release --target=$TARGET ${APP_FEATURES:+--features "$APP_FEATURES"}
 echo -e "${GREEN}✓ Applications built${NC}"
 
 # 复制输出文件
-echo -e "${YELLOW}[5/5] Copying binaries...${NC}"
+echo -e "${YELLOW}[6/7] Copying binaries...${NC}"
 cp "$PROJECT_ROOT/target/$TARGET/release/led_blink" "$OUTPUT_DIR/" 2>/dev/null || true
 cp "$PROJECT_ROOT/target/$TARGET/release/uart_hello" "$OUTPUT_DIR/" 2>/dev/null || true
 cp "$PROJECT_ROOT/target/$TARGET/release/mmc_test" "$OUTPUT_DIR/" 2>/dev/null || true
 
+# 生成启动镜像并填写镜像头 CRC
+echo -e "${YELLOW}[7/7] Generating boot images...${NC}"
+OBJCOPY=${OBJCOPY:-llvm-objcopy}
+for elf in "$OUTPUT_DIR"/led_blink "$OUTPUT_DIR"/uart_hello "$OUTPUT_DIR"/mmc_test; do
+    [ -f "$elf" ] || continue
+    $OBJCOPY -O binary "$elf" "$elf.bin"
+    python3 "$PROJECT_ROOT/scripts/image-crc.py" "$elf.bin"
+done
+echo -e "${GREEN}✓ Boot images generated${NC}"
+
 # 显示文件信息
 echo -e "${GREEN}==================================="
 echo "  Build completed!"
@@ -147,7 +164,6 @@ ls -lh "$OUTPUT_DIR/"
 
 echo ""
 echo -e "${GREEN}Next steps:${NC}"
-echo "  1. Convert binary: objcopy -O binary output/uart_hello output/uart_hello.bin"
-echo "  2. Flash to SD card: sudo dd if=output/uart_hello.bin of=/dev/sdX bs=4M"
-echo "  3. Boot and connect serial console"
+echo "  1. Flash to SD card: sudo dd if=output/uart_hello.bin of=/dev/sdX bs=4M"
+echo "  2. Boot and connect serial console"
 脚本/build.sh
\ No newline at end of file
@@ -0,0 +1,53 @@
+#!/bin/bash
+# WhitcloudOS-1 feature 组合检查
+#
+# 每个 crate 先关闭默认 feature 编译一次，再逐个单独开启每个 feature 编译，
+# 确认可选子系统之间没有意外的硬依赖 (裁剪后的 bootloader 镜像仍能构建)。
+
+set -e
+
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+YELLOW='\033[1;33m'
+NC='\033[0m'
+
+PROJECT_ROOT=$(cd "$(dirname "$0")/.." && pwd)
+TARGET=${TARGET:-aarch64-unknown-none}
+
+# sim 需要 std，只用于主机端测试
+SKIP_FEATURES="default sim"
+
+# 列出 Cargo.toml [features] 段中的 feature 名
+features() {
+    tr -d '\r' < "$1" | awk '
+        /^\[features\]/ { in_features = 1; next }
+        /^\[/           { in_features = 0 }
+        in_features && /^[A-Za-z0-9_-]+[ ]*=/ { print $1 }'
+}
+
+check() {
+    local manifest=$1
+    shift
+    if ! cargo check --quiet --manifest-path "$manifest" --target "$TARGET" "$@"; then
+        echo -e "${RED}✗ $manifest $*${NC}"
+        exit 1
+    fi
+}
+
+cd "$PROJECT_ROOT"
+for manifest in */Cargo.toml drivers/*/Cargo.toml; do
+    case "$manifest" in
+        configs/*) continue ;;
+    esac
+    crate=$(dirname "$manifest")
+    echo -e "${YELLOW}$crate${NC}"
+    check "$manifest" --no-default-features
+    for feature in $(features "$manifest"); do
+        case " $SKIP_FEATURES " in
+            *" $feature "*) continue ;;
+        esac
+        check "$manifest" --no-default-features --features "$feature"
+    done
+done
+
+echo -e "${GREEN}✓ All feature combinations build${NC}"
@@ -0,0 +1,42 @@
+#!/usr/bin/env python3
+"""WhitcloudOS-1 镜像头 CRC 填写工具
+
+用法: image-crc.py <image.bin>...
+
+对 `objcopy -O binary` 得到的镜像，计算镜像头之后到镜像结束 (image_size) 的
+CRC32，写入镜像头 0x30 处。镜像头布局见 layout crate 的 ImageHeader。
+"""
+
+import struct
+import sys
+import zlib
+
+IMAGE_MAGIC = b"WCOS"
+IMAGE_HEADER_VERSION = 2
+IMAGE_HEADER_SIZE = 64
+CRC_OFFSET = 0x30
+
+
+def patch(path):
+    with open(path, "r+b") as f:
+        image = bytearray(f.read())
+        if len(image) < IMAGE_HEADER_SIZE or image[4:8] != IMAGE_MAGIC:
+            sys.exit(f"{path}: no WCOS image header")
+        version, header_size = struct.unpack_from("<II", image, 0x08)
+        (image_size,) = struct.unpack_from("<Q", image, 0x18)
+        if version != IMAGE_HEADER_VERSION or header_size != IMAGE_HEADER_SIZE:
+            sys.exit(f"{path}: unsupported header version {version}")
+        if len(image) < image_size:
+            sys.exit(f"{path}: file is {len(image)} bytes, header says {image_size}")
+        crc = zlib.crc32(image[header_size:image_size])
+        struct.pack_into("<I", image, CRC_OFFSET, crc)
+        f.seek(0)
+        f.write(image[:IMAGE_HEADER_SIZE])
+    print(f"{path}: {image_size} bytes, crc32 {crc:#010x}")
+
+
+if __name__ == "__main__":
+    if len(sys.argv) < 2:
+        sys.exit(__doc__)
+    for path in sys.argv[1:]:
+        patch(path)
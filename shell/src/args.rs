@@ -0,0 +1,56 @@
+//! 命令行参数拆分
+//!
+//! 参数以空白分隔；双引号括起的部分作为一个参数，其中的空白保留，引号本身去掉。
+//! 不支持转义字符。
+
+use core::fmt;
+
+/// 参数拆分错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitError {
+    /// 参数个数超过缓冲区长度
+    TooManyArgs,
+    /// 双引号没有闭合
+    UnclosedQuote,
+}
+
+impl fmt::Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitError::TooManyArgs => f.write_str("too many arguments"),
+            SplitError::UnclosedQuote => f.write_str("unclosed quote"),
+        }
+    }
+}
+
+/// 把 `line` 拆分为参数，依次写入 `argv`
+///
+/// # 返回值
+/// 参数个数，空行返回 `Ok(0)`
+///
+/// # 示例
+/// ```
+/// let mut argv = [""; 4];
+/// let argc = shell::args::split(r#"echo "hello world" 1"#, &mut argv).unwrap();
+/// assert_eq!(&argv[..argc], &["echo", "hello world", "1"]);
+/// ```
+pub fn split<'a>(line: &'a str, argv: &mut [&'a str]) -> Result<usize, SplitError> {
+    let mut argc = 0;
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let (arg, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').ok_or(SplitError::UnclosedQuote)?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                rest.split_at(end)
+            }
+        };
+        *argv.get_mut(argc).ok_or(SplitError::TooManyArgs)? = arg;
+        argc += 1;
+        rest = tail.trim_start();
+    }
+    Ok(argc)
+}
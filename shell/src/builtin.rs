@@ -0,0 +1,179 @@
+//! 内置命令
+//!
+//! | 命令 | 功能 |
+//! |------|------|
+//! | `help [cmd]` | 列出所有命令，或显示一个命令的说明 |
+//! | `version` | 启动横幅 (版本、git 提交、目标板) |
+//! | `buildinfo` | 完整构建信息 |
+//! | `log [level]` | 查看或设置运行时日志级别 |
+//! | `md <addr> [len]` | 以 `hexdump -C` 布局显示内存 (DRAM/SRAM，外设寄存器用 `regdump`) |
+//! | `reboot [loader\|maskrom]` | 全局软复位，可指定下次启动进入 Loader 或 MaskROM 下载模式 |
+//! | `regdump uart<N>\|gpio<N>` | 按位域解码输出外设寄存器 (`regset` feature) |
+
+use core::fmt::{self, Write};
+
+use klog::LevelFilter;
+use mmio::write_volatile;
+
+use crate::Command;
+
+/// CRU 基地址
+const CRU_BASE: usize = 0xFD7C0000;
+/// 全局第一级软复位寄存器
+const CRU_GLB_SRST_FST: usize = 0x0C08;
+/// 写入 GLB_SRST_FST 触发复位的值
+const GLB_SRST_FST_VALUE: u32 = 0xFDB9;
+
+/// 启动模式寄存器 (PMU0_GRF + 0x80，U-Boot `CONFIG_ROCKCHIP_BOOT_MODE_REG`)
+///
+/// 软复位不清除，U-Boot 启动时读取并按其中的标志选择启动流程
+const BOOT_MODE_REG: usize = 0xFD58_8080;
+/// 正常启动 (Linux dt-bindings `rockchip,boot-mode.h` 中的 BOOT_NORMAL)
+const BOOT_NORMAL: u32 = 0x5242_C300;
+/// 停在 U-Boot 的 Rockusb Loader 下载模式 (BOOT_BL_DOWNLOAD)
+const BOOT_LOADER: u32 = 0x5242_C301;
+/// 跳过 U-Boot，停在 BootROM 的 MaskROM 下载模式 (BOOT_BROM_DOWNLOAD)
+const BOOT_MASKROM: u32 = 0xEF08_A53C;
+
+/// `md` 不指定长度时显示的字节数
+const MD_DEFAULT_LEN: usize = 64;
+/// `md` 一次最多显示的字节数
+const MD_MAX_LEN: usize = 256;
+
+/// 内置命令表，注册的命令不能与这些名称重复
+pub const BUILTINS: &[Command] = &[
+    Command::new("help", "list commands, or `help <cmd>`", help),
+    Command::new("version", "show firmware version", version),
+    Command::new("buildinfo", "show build information", buildinfo),
+    Command::new(
+        "log",
+        "show or set log level (off|error|warn|info|debug|trace)",
+        log,
+    ),
+    Command::new("md", "dump memory, `md <addr> [len]`", md),
+    Command::new("reboot", "reset the SoC, `reboot [loader|maskrom]`", reboot),
+    #[cfg(feature = "regset")]
+    Command::new("regdump", "dump device registers (uart<N>|gpio<N>)", regdump),
+];
+
+fn help(args: &[&str], out: &mut dyn Write) -> fmt::Result {
+    if let Some(&name) = args.get(1) {
+        return match crate::find(name) {
+            Some(cmd) => writeln!(out, "{}: {}", cmd.name, cmd.help),
+            None => writeln!(out, "help: unknown command `{}`", name),
+        };
+    }
+    for cmd in BUILTINS.iter().chain(crate::commands().iter().flatten()) {
+        writeln!(out, "  {:<12} {}", cmd.name, cmd.help)?;
+    }
+    Ok(())
+}
+
+fn version(_args: &[&str], mut out: &mut dyn Write) -> fmt::Result {
+    buildinfo::write_banner(&mut out)?;
+    writeln!(out)
+}
+
+fn buildinfo(_args: &[&str], mut out: &mut dyn Write) -> fmt::Result {
+    buildinfo::write_info(&mut out)
+}
+
+fn parse_level(s: &str) -> Option<LevelFilter> {
+    match s {
+        "off" => Some(LevelFilter::Off),
+        "error" => Some(LevelFilter::Error),
+        "warn" => Some(LevelFilter::Warn),
+        "info" => Some(LevelFilter::Info),
+        "debug" => Some(LevelFilter::Debug),
+        "trace" => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+fn log(args: &[&str], out: &mut dyn Write) -> fmt::Result {
+    match args.get(1) {
+        None => writeln!(out, "log level: {:?}", klog::max_level()),
+        Some(&s) => match parse_level(s) {
+            Some(filter) => {
+                klog::set_max_level(filter);
+                Ok(())
+            }
+            None => writeln!(out, "log: unknown level `{}`", s),
+        },
+    }
+}
+
+/// 由 `uart<N>`/`gpio<N>` 得到寄存器集合和基址
+/// 解析十进制或 `0x` 开头的十六进制数
+fn parse_number(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn md(args: &[&str], mut out: &mut dyn Write) -> fmt::Result {
+    let addr = args.get(1).and_then(|s| parse_number(s));
+    let len = args.get(2).map_or(Some(MD_DEFAULT_LEN), |s| parse_number(s));
+    let (Some(addr), Some(len)) = (addr, len) else {
+        return writeln!(out, "usage: md <addr> [len]");
+    };
+    let len = len.min(MD_MAX_LEN);
+    if addr.checked_add(len).is_none() {
+        return writeln!(out, "md: range overflows the address space");
+    }
+    // 先读到缓冲区再格式化，输出期间不反复访问目标内存
+    let mut buf = [0u8; MD_MAX_LEN];
+    for (i, byte) in buf[..len].iter_mut().enumerate() {
+        *byte = unsafe { core::ptr::read_volatile((addr + i) as *const u8) };
+    }
+    kfmt::hexdump(&mut out, addr, &buf[..len])
+}
+
+#[cfg(feature = "regset")]
+fn regdump_target(device: &str) -> Option<(&'static regset::RegisterSet, usize)> {
+    if let Some(index) = device.strip_prefix("uart") {
+        let id = uart::UartId::from_index(index.parse().ok()?)?;
+        return Some((&uart::regs::UART_REGS, id.base()));
+    }
+    let base = match device.strip_prefix("gpio")? {
+        "0" => gpio::GPIO0_BASE,
+        "1" => gpio::GPIO1_BASE,
+        "2" => gpio::GPIO2_BASE,
+        "3" => gpio::GPIO3_BASE,
+        "4" => gpio::GPIO4_BASE,
+        _ => return None,
+    };
+    Some((&gpio::regs::GPIO_REGS, base))
+}
+
+#[cfg(feature = "regset")]
+fn regdump(args: &[&str], mut out: &mut dyn Write) -> fmt::Result {
+    match args.get(1).and_then(|device| regdump_target(device)) {
+        Some((regs, base)) => regs.dump(&mut out, base),
+        None => writeln!(out, "usage: regdump uart<0-9>|gpio<0-4>"),
+    }
+}
+
+fn reboot(args: &[&str], out: &mut dyn Write) -> fmt::Result {
+    let (mode, flag) = match args.get(1..) {
+        Some([]) | None => ("", BOOT_NORMAL),
+        Some(["loader"]) => (" into loader", BOOT_LOADER),
+        Some(["maskrom"]) => (" into maskrom", BOOT_MASKROM),
+        Some(_) => return writeln!(out, "usage: reboot [loader|maskrom]"),
+    };
+    writeln!(out, "rebooting{}...", mode)?;
+    // 等待提示输出完成，否则 FIFO 中的字符随复位丢失
+    uart::console().flush();
+    unsafe {
+        // 普通重启也写入标志，覆盖之前残留的下载模式请求
+        write_volatile(BOOT_MODE_REG as *mut u32, flag);
+        write_volatile(
+            (CRU_BASE + CRU_GLB_SRST_FST) as *mut u32,
+            GLB_SRST_FST_VALUE,
+        );
+    }
+    loop {
+        core::hint::spin_loop();
+    }
+}
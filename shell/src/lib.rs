@@ -0,0 +1,284 @@
+//! 交互式 Shell
+//!
+//! 在控制台 UART 上提供命令行：提示符、行编辑 (退格、Ctrl-U 删除整行、Ctrl-C 取消)、
+//! 上下键命令历史、Ctrl-R 搜索历史、Tab 补全命令名、参数拆分和命令分发。
+//! 驱动和板级代码用 [`register`] 挂接调试命令 (gpio、mmc、寄存器读写等)，
+//! 内置命令见 [`builtin`]。
+//!
+//! # 命令处理函数
+//! 处理函数收到拆分后的参数 (`args[0]` 为命令名) 和输出目标，
+//! 用法错误等直接写到 `out` 即可。
+//!
+//! # 注意
+//! - 处理函数在 Shell 循环中直接调用，执行期间不处理输入
+//! - `out` 每次写入单独获取控制台锁，处理函数中可以正常使用 `println!` 和 klog
+//! - 只接受可打印 ASCII 字符，一行最多 [`LINE_MAX`] 字节、[`MAX_ARGS`] 个参数
+//!
+//! # 使用示例
+//! ```no_run
+//! use core::fmt::{self, Write};
+//! use uart::{init_console, UART2_BASE};
+//!
+//! fn cmd_led(args: &[&str], out: &mut dyn Write) -> fmt::Result {
+//!     match args.get(1) {
+//!         Some(&"on") => writeln!(out, "led on"),
+//!         Some(&"off") => writeln!(out, "led off"),
+//!         _ => writeln!(out, "usage: led on|off"),
+//!     }
+//! }
+//!
+//! init_console(UART2_BASE, 1_500_000).unwrap();
+//! shell::register("led", cmd_led).unwrap();
+//! shell::Shell::new("wcos> ").run();
+//! ```
+
+#![no_std]
+
+pub mod args;
+pub mod builtin;
+
+use core::fmt::{self, Write};
+
+use spinlock::SpinLock;
+use uart::line::{Complete, LineEditor, LineEvent};
+
+/// 最多可注册的命令数量 (不含内置命令)
+pub const MAX_COMMANDS: usize = 32;
+
+/// 一行输入的最大长度
+pub const LINE_MAX: usize = 128;
+
+/// 一条命令的最大参数个数 (含命令名)
+pub const MAX_ARGS: usize = 16;
+
+/// 命令历史缓冲区大小 (字节)，满时丢弃最旧的命令
+pub const HISTORY_BYTES: usize = 512;
+
+/// 命令处理函数
+///
+/// `args[0]` 为命令名，输出写到 `out`
+pub type Handler = fn(args: &[&str], out: &mut dyn Write) -> fmt::Result;
+
+/// 一条命令
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+    /// `help` 中显示的一行说明
+    pub help: &'static str,
+    pub handler: Handler,
+}
+
+impl Command {
+    pub const fn new(name: &'static str, help: &'static str, handler: Handler) -> Self {
+        Self {
+            name,
+            help,
+            handler,
+        }
+    }
+}
+
+/// 注册命令失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+    /// 已注册 [`MAX_COMMANDS`] 条命令
+    Full,
+    /// 同名命令已存在 (包括内置命令)
+    Duplicate,
+    /// 命令名为空或含空白字符
+    InvalidName,
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterError::Full => f.write_str("command table full"),
+            RegisterError::Duplicate => f.write_str("command already registered"),
+            RegisterError::InvalidName => f.write_str("invalid command name"),
+        }
+    }
+}
+
+/// 命令表
+static REGISTRY: SpinLock<[Option<Command>; MAX_COMMANDS]> = SpinLock::new([None; MAX_COMMANDS]);
+
+/// 注册命令，`help` 中的说明为空
+///
+/// # 返回值
+/// - `Ok(())`: 注册成功
+/// - `Err(RegisterError)`: 命令表已满、重名或命令名无效
+pub fn register(name: &'static str, handler: Handler) -> Result<(), RegisterError> {
+    register_command(Command::new(name, "", handler))
+}
+
+/// 注册带说明的命令，参见 [`register`]
+pub fn register_with_help(
+    name: &'static str,
+    help: &'static str,
+    handler: Handler,
+) -> Result<(), RegisterError> {
+    register_command(Command::new(name, help, handler))
+}
+
+/// 注册命令，参见 [`register`]
+pub fn register_command(cmd: Command) -> Result<(), RegisterError> {
+    if cmd.name.is_empty() || cmd.name.contains(char::is_whitespace) {
+        return Err(RegisterError::InvalidName);
+    }
+    if builtin::BUILTINS.iter().any(|b| b.name == cmd.name) {
+        return Err(RegisterError::Duplicate);
+    }
+    let mut commands = REGISTRY.lock();
+    if commands.iter().flatten().any(|c| c.name == cmd.name) {
+        return Err(RegisterError::Duplicate);
+    }
+    let slot = commands
+        .iter_mut()
+        .find(|c| c.is_none())
+        .ok_or(RegisterError::Full)?;
+    *slot = Some(cmd);
+    Ok(())
+}
+
+/// 注销命令
+///
+/// # 返回值
+/// 命令存在并已注销时返回 `true` (内置命令不能注销)
+pub fn unregister(name: &str) -> bool {
+    let mut commands = REGISTRY.lock();
+    match commands
+        .iter_mut()
+        .find(|c| c.is_some_and(|c| c.name == name))
+    {
+        Some(slot) => {
+            *slot = None;
+            true
+        }
+        None => false,
+    }
+}
+
+/// 查找命令 (先查内置命令)
+pub fn find(name: &str) -> Option<Command> {
+    builtin::BUILTINS
+        .iter()
+        .find(|c| c.name == name)
+        .copied()
+        .or_else(|| REGISTRY.lock().iter().flatten().find(|c| c.name == name).copied())
+}
+
+/// 已注册命令的副本 (不含内置命令)
+pub fn commands() -> [Option<Command>; MAX_COMMANDS] {
+    *REGISTRY.lock()
+}
+
+/// 拆分并执行一行命令
+///
+/// 空行不做任何事；命令不存在或参数拆分失败时向 `out` 输出错误信息
+///
+/// # 示例
+/// ```
+/// let mut out = String::new();
+/// shell::execute("nosuchcmd 1 2", &mut out).unwrap();
+/// assert!(out.contains("command not found"));
+/// ```
+pub fn execute(line: &str, out: &mut dyn Write) -> fmt::Result {
+    let mut argv = [""; MAX_ARGS];
+    let argc = match args::split(line, &mut argv) {
+        Ok(0) => return Ok(()),
+        Ok(argc) => argc,
+        Err(e) => return writeln!(out, "error: {}", e),
+    };
+    let args = &argv[..argc];
+    match find(args[0]) {
+        Some(cmd) => (cmd.handler)(args, out),
+        None => writeln!(out, "{}: command not found (try `help`)", args[0]),
+    }
+}
+
+/// 控制台输出，每次写入单独加锁
+struct ConsoleOut;
+
+impl Write for ConsoleOut {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        uart::console().lock().write_str(s)
+    }
+}
+
+/// Tab 补全候选：内置命令和已注册命令的名称
+struct CommandNames;
+
+impl Complete for CommandNames {
+    fn candidates(&self, candidate: &mut dyn FnMut(&str)) {
+        for cmd in builtin::BUILTINS.iter().chain(commands().iter().flatten()) {
+            candidate(cmd.name);
+        }
+    }
+}
+
+/// Shell 状态 (提示符、当前输入行和命令历史)
+pub struct Shell {
+    prompt: &'static str,
+    editor: LineEditor<[u8; LINE_MAX], HISTORY_BYTES>,
+}
+
+impl Shell {
+    pub const fn new(prompt: &'static str) -> Self {
+        Self {
+            prompt,
+            editor: LineEditor::with_history([0; LINE_MAX]),
+        }
+    }
+
+    /// 输出提示符
+    pub fn prompt(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(self.prompt)
+    }
+
+    /// 处理一个输入字节
+    ///
+    /// 行编辑、历史和补全规则见 `uart::line`；一行结束后执行该行并输出提示符，
+    /// Ctrl-C 放弃当前行后重新输出提示符，Tab 列出候选后重新输出提示符和当前行。
+    ///
+    /// # 示例
+    /// ```
+    /// let mut shell = shell::Shell::new("> ");
+    /// let mut out = String::new();
+    /// for &b in b"helq\x7fp\r" {
+    ///     shell.feed(b, &mut out).unwrap();
+    /// }
+    /// assert!(out.starts_with("helq\x08 \x08p\n"));
+    /// assert!(out.ends_with("> "));
+    /// ```
+    pub fn feed(&mut self, byte: u8, out: &mut dyn Write) -> fmt::Result {
+        match self.editor.feed_with(byte, out, &CommandNames) {
+            LineEvent::Pending => Ok(()),
+            LineEvent::Done => {
+                execute(self.editor.line(), out)?;
+                self.editor.clear();
+                self.prompt(out)
+            }
+            LineEvent::Interrupted => self.prompt(out),
+            LineEvent::Redraw => {
+                self.prompt(out)?;
+                out.write_str(self.editor.line())
+            }
+        }
+    }
+
+    /// 在控制台上运行 Shell，不返回
+    ///
+    /// 轮询所有控制台后端的输入 (参见 `uart::Console::getc`)，
+    /// 没有输入时空转等待
+    pub fn run(&mut self) -> ! {
+        let _ = self.prompt(&mut ConsoleOut);
+        loop {
+            match uart::console().getc() {
+                Some(byte) => {
+                    let _ = self.feed(byte, &mut ConsoleOut);
+                }
+                None => core::hint::spin_loop(),
+            }
+        }
+    }
+}
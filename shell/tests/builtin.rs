@@ -0,0 +1,39 @@
+//! 内置命令测试，经 [`shell::execute`] 分发
+//!
+//! 运行: `cargo test -p shell --features sim,regset`
+
+#![cfg(all(feature = "sim", feature = "regset"))]
+
+use mmio::sim::{self, GpioModel, UartModel};
+
+#[test]
+fn regdump_decodes_uart_registers() {
+    let _model = sim::map(uart::UART3_BASE, 0x100, UartModel::new());
+    // 8N1
+    unsafe { mmio::write_volatile((uart::UART3_BASE + 0x0C) as *mut u32, 0x03) };
+
+    let mut out = String::new();
+    shell::execute("regdump uart3", &mut out).unwrap();
+    assert!(out.starts_with("uart @ 0xfeb60000\n"), "{}", out);
+    assert!(out.contains("LCR    0x0c = 0x00000003  DLS=3"), "{}", out);
+}
+
+#[test]
+fn regdump_reads_gpio_bank() {
+    let model = sim::map(gpio::GPIO1_BASE, 0x100, GpioModel::new());
+    model.borrow_mut().set_input(5, true);
+
+    let mut out = String::new();
+    shell::execute("regdump gpio1", &mut out).unwrap();
+    assert!(out.starts_with("gpio @ 0xfec20000\n"), "{}", out);
+    assert!(out.contains("EXT    0x70 = 0x00000020"), "{}", out);
+}
+
+#[test]
+fn regdump_rejects_unknown_device() {
+    for line in ["regdump", "regdump uart10", "regdump gpio5", "regdump spi0"] {
+        let mut out = String::new();
+        shell::execute(line, &mut out).unwrap();
+        assert_eq!(out, "usage: regdump uart<0-9>|gpio<0-4>\n", "{}", line);
+    }
+}
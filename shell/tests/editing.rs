@@ -0,0 +1,38 @@
+//! Shell 的命令历史与 Tab 补全
+//!
+//! 运行: `cargo test -p shell --test editing`
+
+use core::fmt::{self, Write};
+
+fn hello(_args: &[&str], out: &mut dyn Write) -> fmt::Result {
+    writeln!(out, "hello!")
+}
+
+fn run(shell: &mut shell::Shell, input: &[u8]) -> String {
+    let mut out = String::new();
+    for &b in input {
+        shell.feed(b, &mut out).unwrap();
+    }
+    out
+}
+
+#[test]
+fn tab_completes_registered_and_builtin_commands() {
+    shell::register("hello", hello).unwrap();
+    let mut shell = shell::Shell::new("> ");
+
+    assert_eq!(run(&mut shell, b"hell\t\r"), "hello \nhello!\n> ");
+    assert_eq!(run(&mut shell, b"vers\t\r").lines().next(), Some("version "));
+
+    // `help` 和 `hello` 公共前缀为 `hel`，再按 Tab 列出候选并重绘当前行
+    assert_eq!(run(&mut shell, b"he\t"), "hel");
+    assert_eq!(run(&mut shell, b"\t"), "\nhelp  hello  \n> hel");
+}
+
+#[test]
+fn up_arrow_reruns_previous_command() {
+    let mut shell = shell::Shell::new("> ");
+    let first = run(&mut shell, b"log\r");
+    let again = run(&mut shell, b"\x1b[A\r");
+    assert_eq!(again, first);
+}
@@ -0,0 +1,49 @@
+//! `fault` 命令测试，经 [`shell::execute`] 分发
+//!
+//! 运行: `cargo test -p shell`
+
+#![cfg(debug_assertions)]
+
+use fault::FaultPoint;
+
+static DEMO: FaultPoint = FaultPoint::new("shell.demo");
+
+/// 注册命令和注入点后执行一行输入
+fn run(line: &str) -> String {
+    let _ = shell::register_with_help("fault", "inject driver faults", fault::command);
+    fault::register(&DEMO).unwrap();
+    let mut out = String::new();
+    shell::execute(line, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn fault_command_arms_and_disarms_by_name() {
+    assert_eq!(run("fault arm shell.demo 2 1"), "");
+    assert!(DEMO.is_armed());
+    assert!(!DEMO.should_fail());
+    assert!(DEMO.should_fail());
+    assert!(DEMO.should_fail());
+    assert!(!DEMO.should_fail());
+    assert_eq!(DEMO.injected(), 2);
+
+    assert_eq!(run("fault arm shell.demo always"), "");
+    let out = run("fault");
+    assert!(out.starts_with("name "), "{}", out);
+    assert!(out.contains("shell.demo"), "{}", out);
+    assert!(out.contains("always"), "{}", out);
+    assert_eq!(run("fault list"), out);
+
+    assert_eq!(run("fault disarm shell.demo"), "");
+    assert!(!DEMO.is_armed());
+}
+
+#[test]
+fn fault_command_reports_errors() {
+    assert_eq!(run("fault arm nosuch.point"), "fault: no such fault point\n");
+    assert_eq!(run("fault arm shell.demo x"), "fault: invalid number\n");
+    assert_eq!(
+        run("fault frobnicate"),
+        "usage: fault [list | arm <name> [times] [skip] | disarm <name|all>]\n"
+    );
+}
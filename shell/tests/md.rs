@@ -0,0 +1,43 @@
+//! `md` 命令测试，经 [`shell::execute`] 分发
+//!
+//! 运行: `cargo test -p shell`
+
+static DATA: [u8; 20] = *b"Hello, World!\n\0\0\0\0\0\0";
+
+fn run(line: &str) -> String {
+    let mut out = String::new();
+    shell::execute(line, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn md_dumps_memory_in_hexdump_layout() {
+    let addr = DATA.as_ptr() as usize;
+    let out = run(&format!("md {:#x} 20", addr));
+    assert_eq!(
+        out,
+        format!(
+            "{:08x}  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 0a 00 00  |Hello, World!...|\n\
+             {:08x}  00 00 00 00                                       |....|\n",
+            addr,
+            addr + 16
+        )
+    );
+}
+
+#[test]
+fn md_accepts_decimal_address() {
+    let addr = DATA.as_ptr() as usize;
+    assert_eq!(run(&format!("md {} 4", addr)), run(&format!("md {:#x} 4", addr)));
+}
+
+#[test]
+fn md_rejects_bad_arguments() {
+    for line in ["md", "md zz", "md 0x1000 lots"] {
+        assert_eq!(run(line), "usage: md <addr> [len]\n", "{}", line);
+    }
+    assert_eq!(
+        run("md 0xffffffffffffffff 2"),
+        "md: range overflows the address space\n"
+    );
+}
@@ -0,0 +1,19 @@
+//! `reboot` 的参数检查 (合法参数会复位，不在主机上执行)
+//!
+//! 运行: `cargo test -p shell`
+
+#[test]
+fn reboot_rejects_unknown_mode_without_resetting() {
+    for line in ["reboot recovery", "reboot loader now", "reboot LOADER"] {
+        let mut out = String::new();
+        shell::execute(line, &mut out).unwrap();
+        assert_eq!(out, "usage: reboot [loader|maskrom]\n", "{}", line);
+    }
+}
+
+#[test]
+fn help_lists_reboot_modes() {
+    let mut out = String::new();
+    shell::execute("help reboot", &mut out).unwrap();
+    assert_eq!(out, "reboot: reset the SoC, `reboot [loader|maskrom]`\n");
+}
@@ -0,0 +1,162 @@
+//! 自旋锁
+//!
+//! 各子系统的全局表 (命令表、引脚占用表、注入点表等) 和控制台共用的互斥原语。
+//! 没有依赖，klog 等底层 crate 也可以使用。
+//!
+//! - [`SpinLock::lock`] 普通加锁，用于不会在中断处理函数中访问的数据
+//! - [`SpinLock::lock_irqsave`] 先屏蔽本核 IRQ 再加锁，释放时恢复之前的中断状态，
+//!   用于中断处理函数中也会访问的数据 (例如控制台)
+//! - [`SpinLock::try_lock`] 不等待，已被占用时返回 `None`
+//!
+//! # 注意
+//! - 不可重入：持锁期间再次加锁同一把锁会死锁
+//! - 普通加锁期间如果中断处理函数在本核上加同一把锁也会死锁，
+//!   这种情况应使用 `lock_irqsave`，或在中断处理函数中使用 `try_lock`
+//! - 非 aarch64 目标 (主机端测试) 上 `lock_irqsave` 与 `lock` 相同
+//!
+//! # 使用示例
+//! ```
+//! use spinlock::SpinLock;
+//!
+//! static COUNTERS: SpinLock<[u32; 4]> = SpinLock::new([0; 4]);
+//!
+//! COUNTERS.lock()[1] += 1;
+//! assert_eq!(COUNTERS.lock_irqsave()[1], 1);
+//! ```
+
+#![no_std]
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// 自旋锁保护的数据
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// value 只在持有 locked 时访问
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// 加锁，被占用时自旋等待
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        SpinLockGuard { lock: self }
+    }
+
+    /// 不等待的加锁
+    ///
+    /// # 返回值
+    /// 已被占用时返回 `None`
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| SpinLockGuard { lock: self })
+    }
+
+    /// 屏蔽本核 IRQ 后加锁，释放时先解锁再恢复之前的中断状态
+    pub fn lock_irqsave(&self) -> IrqSpinLockGuard<'_, T> {
+        let daif = irq_save();
+        // 解锁由 IrqSpinLockGuard 负责，必须在恢复中断之前
+        core::mem::forget(self.lock());
+        IrqSpinLockGuard { lock: self, daif }
+    }
+
+    fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+/// [`SpinLock::lock`] 的锁，释放时解锁
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+/// [`SpinLock::lock_irqsave`] 的锁，释放时解锁并恢复 IRQ 状态
+pub struct IrqSpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+    daif: u64,
+}
+
+impl<T> Deref for IrqSpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for IrqSpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for IrqSpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+        irq_restore(self.daif);
+    }
+}
+
+/// 屏蔽本核 IRQ，返回之前的 DAIF
+#[cfg(target_arch = "aarch64")]
+fn irq_save() -> u64 {
+    let daif: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, daif", "msr daifset, #2", out(reg) daif, options(nostack));
+    }
+    daif
+}
+
+/// 恢复 DAIF
+#[cfg(target_arch = "aarch64")]
+fn irq_restore(daif: u64) {
+    unsafe {
+        core::arch::asm!("msr daif, {}", in(reg) daif, options(nostack));
+    }
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn irq_save() -> u64 {
+    0
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+fn irq_restore(_: u64) {}
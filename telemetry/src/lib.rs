@@ -0,0 +1,291 @@
+//! 遥测记录格式
+//!
+//! 数据记录 (SD 卡)、MQTT 发布和 UART 跟踪通道共用的二进制记录格式。
+//! 一条记录是同一时刻的一组采样值，带版本号和 CRC，可以直接拼接存储或放进
+//! 其他协议的负载中 (例如 `uart::mux` 的 `Data` 通道)。
+//!
+//! # 记录格式 (版本 1，主机端解码说明)
+//! 所有多字节字段为小端序。
+//! ```text
+//! 偏移  长度   字段
+//! 0     1      version    格式版本，当前为 1
+//! 1     1      count      采样个数 N (1..=64)
+//! 2     8      timestamp  采样时间 (微秒，通用定时器，起点为上电)
+//! 10    7*N    samples    N 个采样，每个为:
+//!                           metric (2)  指标编号
+//!                           kind   (1)  值类型: 0 = u32, 1 = i32, 2 = f32 (IEEE 754)
+//!                           value  (4)  值
+//! 10+7N 2      crc        CRC-16/XMODEM (多项式 0x1021，初值 0)，覆盖之前的所有字节
+//! ```
+//! 记录长度为 `12 + 7 * N` 字节。
+//!
+//! 主机端解码流程：
+//! 1. 读取 `version`，不认识的版本丢弃整个数据流 (长度规则可能不同)
+//! 2. 读取 `count`，按上表计算记录长度并校验 CRC，失败则丢弃该记录
+//! 3. `kind` 未知的采样跳过 (值仍为 4 字节)
+//!
+//! # 指标编号
+//! - `0x0000-0x00FF`: 系统指标，见 [`metric`]
+//! - `0x0100-0xFFFF`: 应用自定义，编号与名称、单位的对应表由应用随数据一起发布
+//!
+//! 编号一旦发布不再改变含义；不再使用的编号保留，不分配给新指标。
+//!
+//! # 使用示例
+//! ```
+//! use telemetry::{metric, Record, RecordBuilder, Value};
+//!
+//! let mut buf = [0u8; 64];
+//! let mut rec = RecordBuilder::new(&mut buf, 1_000_000);
+//! rec.push(metric::UPTIME_S, Value::U32(1)).unwrap();
+//! rec.push(metric::SOC_TEMP_MC, Value::I32(45_250)).unwrap();
+//! let len = rec.finish().unwrap();
+//!
+//! let record = Record::decode(&buf[..len]).unwrap();
+//! assert_eq!(record.timestamp_us(), 1_000_000);
+//! assert_eq!(record.samples().nth(1).unwrap().value, Value::I32(45_250));
+//! ```
+
+#![no_std]
+
+use core::fmt;
+
+use crc::crc16_xmodem as crc16;
+
+/// 当前格式版本
+pub const VERSION: u8 = 1;
+
+/// 一条记录最多包含的采样个数
+pub const MAX_SAMPLES: usize = 64;
+
+/// 记录头长度 (version、count、timestamp)
+const HEADER_LEN: usize = 10;
+/// 一个采样的长度
+const SAMPLE_LEN: usize = 7;
+/// CRC 长度
+const CRC_LEN: usize = 2;
+
+/// 包含 `samples` 个采样的记录长度
+pub const fn record_len(samples: usize) -> usize {
+    HEADER_LEN + SAMPLE_LEN * samples + CRC_LEN
+}
+
+/// 系统指标编号
+pub mod metric {
+    /// 运行时间 (秒, u32)
+    pub const UPTIME_S: u16 = 0x0001;
+    /// SoC 温度 (毫摄氏度, i32)
+    pub const SOC_TEMP_MC: u16 = 0x0002;
+    /// 空闲内存 (KiB, u32)
+    pub const FREE_MEM_KB: u16 = 0x0003;
+    /// 日志丢弃条数 (u32)
+    pub const LOG_DROPPED: u16 = 0x0004;
+    /// UART 接收错误计数 (u32)
+    pub const UART_RX_ERRORS: u16 = 0x0010;
+    /// SD 卡读写错误计数 (u32)
+    pub const MMC_ERRORS: u16 = 0x0011;
+    /// 系统指标编号上限 (不含)
+    pub const SYSTEM_END: u16 = 0x0100;
+}
+
+/// 采样值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    U32(u32),
+    I32(i32),
+    F32(f32),
+}
+
+impl Value {
+    const fn kind(self) -> u8 {
+        match self {
+            Value::U32(_) => 0,
+            Value::I32(_) => 1,
+            Value::F32(_) => 2,
+        }
+    }
+
+    fn to_le_bytes(self) -> [u8; 4] {
+        match self {
+            Value::U32(v) => v.to_le_bytes(),
+            Value::I32(v) => v.to_le_bytes(),
+            Value::F32(v) => v.to_le_bytes(),
+        }
+    }
+
+    fn from_le_bytes(kind: u8, bytes: [u8; 4]) -> Option<Self> {
+        match kind {
+            0 => Some(Value::U32(u32::from_le_bytes(bytes))),
+            1 => Some(Value::I32(i32::from_le_bytes(bytes))),
+            2 => Some(Value::F32(f32::from_le_bytes(bytes))),
+            _ => None,
+        }
+    }
+}
+
+/// 一个采样
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub metric: u16,
+    pub value: Value,
+}
+
+/// 编码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// 输出缓冲区放不下
+    BufferTooSmall,
+    /// 采样个数超过 [`MAX_SAMPLES`]
+    TooManySamples,
+    /// 记录中没有采样
+    Empty,
+}
+
+/// 解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// 数据不完整
+    Truncated,
+    /// 不支持的格式版本
+    Version(u8),
+    /// 采样个数为 0 或超过 [`MAX_SAMPLES`]
+    BadCount,
+    /// CRC 校验失败
+    Crc,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall => f.write_str("buffer too small"),
+            EncodeError::TooManySamples => f.write_str("too many samples"),
+            EncodeError::Empty => f.write_str("record has no samples"),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated => f.write_str("truncated record"),
+            DecodeError::Version(v) => write!(f, "unsupported version {}", v),
+            DecodeError::BadCount => f.write_str("bad sample count"),
+            DecodeError::Crc => f.write_str("CRC mismatch"),
+        }
+    }
+}
+
+/// 在缓冲区中逐个写入采样，最后用 [`finish`](Self::finish) 填写个数和 CRC
+pub struct RecordBuilder<'a> {
+    buf: &'a mut [u8],
+    timestamp_us: u64,
+    count: usize,
+}
+
+impl<'a> RecordBuilder<'a> {
+    pub fn new(buf: &'a mut [u8], timestamp_us: u64) -> Self {
+        Self {
+            buf,
+            timestamp_us,
+            count: 0,
+        }
+    }
+
+    /// 追加一个采样
+    pub fn push(&mut self, metric: u16, value: Value) -> Result<(), EncodeError> {
+        if self.count >= MAX_SAMPLES {
+            return Err(EncodeError::TooManySamples);
+        }
+        let offset = HEADER_LEN + SAMPLE_LEN * self.count;
+        let dst = self
+            .buf
+            .get_mut(offset..offset + SAMPLE_LEN)
+            .ok_or(EncodeError::BufferTooSmall)?;
+        dst[..2].copy_from_slice(&metric.to_le_bytes());
+        dst[2] = value.kind();
+        dst[3..].copy_from_slice(&value.to_le_bytes());
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 写入记录头和 CRC
+    ///
+    /// # 返回值
+    /// 记录长度
+    pub fn finish(self) -> Result<usize, EncodeError> {
+        if self.count == 0 {
+            return Err(EncodeError::Empty);
+        }
+        let len = record_len(self.count);
+        let rec = self.buf.get_mut(..len).ok_or(EncodeError::BufferTooSmall)?;
+        rec[0] = VERSION;
+        rec[1] = self.count as u8;
+        rec[2..HEADER_LEN].copy_from_slice(&self.timestamp_us.to_le_bytes());
+        let crc = crc16(&rec[..len - CRC_LEN]);
+        rec[len - CRC_LEN..].copy_from_slice(&crc.to_le_bytes());
+        Ok(len)
+    }
+}
+
+/// 编码一条记录
+///
+/// # 返回值
+/// 记录长度
+pub fn encode(timestamp_us: u64, samples: &[Sample], buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let mut rec = RecordBuilder::new(buf, timestamp_us);
+    for s in samples {
+        rec.push(s.metric, s.value)?;
+    }
+    rec.finish()
+}
+
+/// 已校验的记录
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Record<'a> {
+    /// 解码 `buf` 开头的一条记录 (之后的数据被忽略)
+    pub fn decode(buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let (&version, rest) = buf.split_first().ok_or(DecodeError::Truncated)?;
+        if version != VERSION {
+            return Err(DecodeError::Version(version));
+        }
+        let count = *rest.first().ok_or(DecodeError::Truncated)? as usize;
+        if count == 0 || count > MAX_SAMPLES {
+            return Err(DecodeError::BadCount);
+        }
+        let len = record_len(count);
+        let bytes = buf.get(..len).ok_or(DecodeError::Truncated)?;
+        let crc = u16::from_le_bytes([bytes[len - 2], bytes[len - 1]]);
+        if crc != crc16(&bytes[..len - CRC_LEN]) {
+            return Err(DecodeError::Crc);
+        }
+        Ok(Self { bytes })
+    }
+
+    /// 记录的编码长度，用于定位下一条记录
+    pub fn encoded_len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// 采样时间 (微秒)
+    pub fn timestamp_us(&self) -> u64 {
+        let mut ts = [0u8; 8];
+        ts.copy_from_slice(&self.bytes[2..HEADER_LEN]);
+        u64::from_le_bytes(ts)
+    }
+
+    /// 采样，跳过值类型未知的采样
+    pub fn samples(&self) -> impl Iterator<Item = Sample> + 'a {
+        let count = self.bytes[1] as usize;
+        self.bytes[HEADER_LEN..HEADER_LEN + SAMPLE_LEN * count]
+            .chunks_exact(SAMPLE_LEN)
+            .filter_map(|s| {
+                Some(Sample {
+                    metric: u16::from_le_bytes([s[0], s[1]]),
+                    value: Value::from_le_bytes(s[2], [s[3], s[4], s[5], s[6]])?,
+                })
+            })
+    }
+}
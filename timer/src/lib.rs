@@ -0,0 +1,139 @@
+//! ARM 通用定时器
+//!
+//! 启动以来的微秒时间戳和轮询等待的超时计时。klog 的行首时间戳、驱动的超时等待
+//! 和 poll_loop 的时间片都从这里取时间，驱动计时不必为此依赖日志 crate。
+//!
+//! - [`timestamp_us`] 从 CNTPCT_EL0/CNTFRQ_EL0 换算的当前时间
+//! - [`ticks`]/[`frequency`] 原始计数和计数频率，用于亚微秒级的测量
+//! - [`Timeout`] 轮询等待的超时计时，定时器不可用时按轮询次数估计
+//!
+//! # 注意
+//! - 非 aarch64 目标 (主机端测试) 和固件未设置 CNTFRQ_EL0 时没有时间戳，
+//!   [`Timeout`] 退化为按 [`FALLBACK_POLLS_PER_US`] 计数
+//! - `sim` feature 下改为读取主机端模拟时钟，见 `sim` 模块
+//!
+//! # 使用示例
+//! ```
+//! if let Some(us) = timer::timestamp_us() {
+//!     // 记录启动耗时
+//!     let _ = us;
+//! }
+//!
+//! let mut timeout = timer::Timeout::new(100);
+//! while !timeout.expired() {
+//!     // 检查硬件状态
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "sim"), no_std)]
+
+#[cfg(feature = "sim")]
+pub mod sim;
+
+/// ARM 通用定时器的当前时间 (微秒)
+///
+/// 从 CNTPCT_EL0 计数和 CNTFRQ_EL0 频率换算，起点为定时器复位 (通常是上电)。
+/// 非 aarch64 目标或固件未设置 CNTFRQ_EL0 时返回 `None`。
+pub fn timestamp_us() -> Option<u64> {
+    let (count, freq) = read_generic_timer();
+    if freq == 0 {
+        return None;
+    }
+    Some((count as u128 * 1_000_000 / freq as u128) as u64)
+}
+
+/// ARM 通用定时器的原始计数 (CNTPCT_EL0)
+///
+/// 分辨率为 1/[`frequency`] 秒 (RK3588 上为 24MHz，约 42ns)，用于微秒分辨率
+/// 不够的测量，例如 UART 起始位宽度。定时器不可用时返回 `None`。
+pub fn ticks() -> Option<u64> {
+    let (count, freq) = read_generic_timer();
+    (freq != 0).then_some(count)
+}
+
+/// ARM 通用定时器的计数频率 (CNTFRQ_EL0，Hz)，定时器不可用时返回 `None`
+pub fn frequency() -> Option<u64> {
+    let (_, freq) = read_generic_timer();
+    (freq != 0).then_some(freq)
+}
+
+/// 读取 (CNTPCT_EL0, CNTFRQ_EL0)
+#[cfg(all(target_arch = "aarch64", not(feature = "sim")))]
+fn read_generic_timer() -> (u64, u64) {
+    let (count, freq): (u64, u64);
+    unsafe {
+        // isb 保证计数器不会被提前读取
+        core::arch::asm!(
+            "isb",
+            "mrs {count}, cntpct_el0",
+            "mrs {freq}, cntfrq_el0",
+            count = out(reg) count,
+            freq = out(reg) freq,
+            options(nomem, nostack),
+        );
+    }
+    (count, freq)
+}
+
+#[cfg(all(not(target_arch = "aarch64"), not(feature = "sim")))]
+fn read_generic_timer() -> (u64, u64) {
+    (0, 0)
+}
+
+#[cfg(feature = "sim")]
+fn read_generic_timer() -> (u64, u64) {
+    sim::read()
+}
+
+/// 没有通用定时器时每微秒的轮询次数估计
+///
+/// 按一次 APB 寄存器读取约 100ns 估算，宁可多等也不提前超时
+pub const FALLBACK_POLLS_PER_US: u64 = 10;
+
+/// 轮询等待的超时计时
+///
+/// 用 [`timestamp_us`] 计时；定时器不可用 (CNTFRQ_EL0 为 0、主机端测试) 时
+/// 退化为按 [`FALLBACK_POLLS_PER_US`] 计数调用 [`expired`](Self::expired) 的次数。
+///
+/// # 示例
+/// ```
+/// let mut timeout = timer::Timeout::new(100);
+/// while !timeout.expired() {
+///     // 检查硬件状态
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    /// 开始时间，`None` 表示按轮询次数计时
+    start: Option<u64>,
+    limit_us: u64,
+    polls: u64,
+}
+
+impl Timeout {
+    /// 从现在开始计时 `limit_us` 微秒
+    pub fn new(limit_us: u64) -> Self {
+        Self {
+            start: timestamp_us(),
+            limit_us,
+            polls: 0,
+        }
+    }
+
+    /// 重新开始计时
+    pub fn restart(&mut self) {
+        *self = Self::new(self.limit_us);
+    }
+
+    /// 是否已经超时
+    pub fn expired(&mut self) -> bool {
+        match self.start {
+            Some(start) => timestamp_us()
+                .is_none_or(|now| now.wrapping_sub(start) >= self.limit_us),
+            None => {
+                self.polls += 1;
+                self.polls > self.limit_us.saturating_mul(FALLBACK_POLLS_PER_US)
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! 主机端模拟时钟
+//!
+//! 开启 `sim` feature 后 [`timestamp_us`](crate::timestamp_us) 读取这里的计数，
+//! 测试用 [`start`] 打开时钟、用 [`advance_us`]/[`advance_ticks`] 推进时间，
+//! 从而检查超时和时间片逻辑而不必真的等待。
+//!
+//! 忙等计时的代码 (例如等到某个计数为止) 在测试中没有别的地方推进时间，
+//! 可以用 [`set_auto_advance`] 让每次读取时钟后自动前进，模拟读取本身的耗时。
+//!
+//! 时钟是线程局部的，`cargo test` 并行运行的测试之间互不影响。
+//! 没有调用 [`start`] 的线程与没有通用定时器时一样，`timestamp_us` 返回 `None`。
+//!
+//! # 使用示例
+//! ```
+//! timer::sim::start(1_000_000);
+//! let t0 = timer::timestamp_us().unwrap();
+//! timer::sim::advance_us(250);
+//! assert_eq!(timer::timestamp_us(), Some(t0 + 250));
+//! ```
+
+use std::cell::Cell;
+
+thread_local! {
+    /// (计数, 频率)，频率为 0 表示时钟未开启
+    static CLOCK: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+    /// 每次读取后自动前进的计数
+    static AUTO_ADVANCE: Cell<u64> = const { Cell::new(0) };
+}
+
+/// 以 `freq_hz` 开启本线程的时钟，计数从 0 开始，不自动前进
+pub fn start(freq_hz: u64) {
+    CLOCK.with(|clock| clock.set((0, freq_hz)));
+    set_auto_advance(0);
+}
+
+/// 关闭本线程的时钟
+pub fn stop() {
+    CLOCK.with(|clock| clock.set((0, 0)));
+    set_auto_advance(0);
+}
+
+/// 每次读取时钟后自动前进 `ticks` 个计数，0 表示只由测试推进
+///
+/// # 示例
+/// ```
+/// timer::sim::start(24_000_000);
+/// timer::sim::set_auto_advance(3);
+/// assert_eq!(timer::ticks(), Some(0));
+/// assert_eq!(timer::ticks(), Some(3));
+/// ```
+pub fn set_auto_advance(ticks: u64) {
+    AUTO_ADVANCE.with(|step| step.set(ticks));
+}
+
+/// 推进 `ticks` 个计数
+pub fn advance_ticks(ticks: u64) {
+    CLOCK.with(|clock| {
+        let (count, freq) = clock.get();
+        clock.set((count.wrapping_add(ticks), freq));
+    });
+}
+
+/// 推进 `us` 微秒 (按当前频率换算为计数)
+pub fn advance_us(us: u64) {
+    let freq = CLOCK.with(|clock| clock.get().1);
+    advance_ticks((us as u128 * freq as u128 / 1_000_000) as u64);
+}
+
+/// (CNTPCT_EL0, CNTFRQ_EL0) 的模拟值
+pub(crate) fn read() -> (u64, u64) {
+    let now = CLOCK.with(Cell::get);
+    advance_ticks(AUTO_ADVANCE.with(Cell::get));
+    now
+}